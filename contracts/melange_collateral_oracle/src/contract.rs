@@ -0,0 +1,273 @@
+use crate::{
+    querier::query_price,
+    state::{
+        read_collateral_info, read_collateral_infos, read_config, store_collateral_info,
+        store_config, CollateralAssetInfo, Config,
+    },
+};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, to_binary, Binary, CanonicalAddr, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult,
+};
+use melange_protocol::collateral_oracle::{
+    CollateralInfoResponse, CollateralInfosResponse, CollateralPriceResponse, ConfigResponse,
+    ExecuteMsg, InstantiateMsg, QueryMsg, SourceType,
+};
+use sei_cosmwasm::SeiQueryWrapper;
+use terraswap::asset::AssetInfo;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut<SeiQueryWrapper>,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let config = Config {
+        owner: deps.api.addr_canonicalize(&msg.owner)?,
+        mint_contract: deps.api.addr_canonicalize(&msg.mint_contract)?,
+        base_denom: msg.base_denom,
+    };
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut<SeiQueryWrapper>,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::UpdateConfig {
+            owner,
+            mint_contract,
+            base_denom,
+        } => update_config(deps, info, owner, mint_contract, base_denom),
+        ExecuteMsg::RegisterCollateralAsset {
+            asset,
+            price_source,
+            multiplier,
+        } => register_collateral(deps, info, asset, price_source, multiplier),
+        ExecuteMsg::RevokeCollateralAsset { asset } => revoke_collateral(deps, info, asset),
+        ExecuteMsg::UpdateCollateralPriceSource {
+            asset,
+            price_source,
+        } => update_collateral_source(deps, info, asset, price_source),
+        ExecuteMsg::UpdateCollateralMultiplier { asset, multiplier } => {
+            update_collateral_multiplier(deps, info, asset, multiplier)
+        }
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    owner: Option<String>,
+    mint_contract: Option<String>,
+    base_denom: Option<String>,
+) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    if let Some(mint_contract) = mint_contract {
+        config.mint_contract = deps.api.addr_canonicalize(&mint_contract)?;
+    }
+
+    if let Some(base_denom) = base_denom {
+        config.base_denom = base_denom;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+pub fn register_collateral(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    asset: AssetInfo,
+    price_source: SourceType,
+    multiplier: Decimal,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_address_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    // only contract owner and mint contract can register a new collateral
+    if config.owner != sender_address_raw && config.mint_contract != sender_address_raw {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if read_collateral_info(deps.storage, &asset.to_string()).is_ok() {
+        return Err(StdError::generic_err("Collateral was already registered"));
+    }
+
+    if multiplier.is_zero() {
+        return Err(StdError::generic_err("Multiplier must be bigger than 0"));
+    }
+
+    store_collateral_info(
+        deps.storage,
+        &CollateralAssetInfo {
+            asset: asset.to_string(),
+            price_source,
+            multiplier,
+            is_revoked: false,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_collateral"),
+        attr("collateral_asset", asset.to_string()),
+    ]))
+}
+
+pub fn revoke_collateral(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    asset: AssetInfo,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_address_raw: CanonicalAddr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    // only owner and mint contract can revoke a collateral
+    if config.owner != sender_address_raw && config.mint_contract != sender_address_raw {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let mut collateral_info: CollateralAssetInfo =
+        read_collateral_info(deps.storage, &asset.to_string())?;
+    collateral_info.is_revoked = true;
+    store_collateral_info(deps.storage, &collateral_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_collateral"),
+        attr("collateral_asset", asset.to_string()),
+    ]))
+}
+
+pub fn update_collateral_source(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    asset: AssetInfo,
+    price_source: SourceType,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let mut collateral_info: CollateralAssetInfo =
+        read_collateral_info(deps.storage, &asset.to_string())?;
+    collateral_info.price_source = price_source;
+    store_collateral_info(deps.storage, &collateral_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_collateral_source"),
+        attr("collateral_asset", asset.to_string()),
+    ]))
+}
+
+pub fn update_collateral_multiplier(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    asset: AssetInfo,
+    multiplier: Decimal,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if multiplier.is_zero() {
+        return Err(StdError::generic_err("Multiplier must be bigger than 0"));
+    }
+
+    let mut collateral_info: CollateralAssetInfo =
+        read_collateral_info(deps.storage, &asset.to_string())?;
+    collateral_info.multiplier = multiplier;
+    store_collateral_info(deps.storage, &collateral_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_collateral_multiplier"),
+        attr("collateral_asset", asset.to_string()),
+        attr("multiplier", multiplier.to_string()),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<SeiQueryWrapper>, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::CollateralPrice { asset, timeframe } => {
+            to_binary(&query_collateral_price(deps, asset, timeframe)?)
+        }
+        QueryMsg::CollateralAssetInfo { asset } => to_binary(&query_collateral_info(deps, asset)?),
+        QueryMsg::CollateralAssetInfos {} => to_binary(&query_collateral_infos(deps)?),
+    }
+}
+
+pub fn query_config(deps: Deps<SeiQueryWrapper>) -> StdResult<ConfigResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        mint_contract: deps.api.addr_humanize(&config.mint_contract)?.to_string(),
+        base_denom: config.base_denom,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_collateral_price(
+    deps: Deps<SeiQueryWrapper>,
+    asset: String,
+    timeframe: Option<u64>,
+) -> StdResult<CollateralPriceResponse> {
+    let collateral: CollateralAssetInfo = read_collateral_info(deps.storage, &asset)?;
+
+    let (price, last_updated): (Decimal, u64) =
+        query_price(deps, asset, &collateral.price_source, timeframe)?;
+
+    Ok(CollateralPriceResponse {
+        asset: collateral.asset,
+        rate: price,
+        last_updated,
+        multiplier: collateral.multiplier,
+        is_revoked: collateral.is_revoked,
+    })
+}
+
+pub fn query_collateral_info(
+    deps: Deps<SeiQueryWrapper>,
+    asset: String,
+) -> StdResult<CollateralInfoResponse> {
+    let collateral: CollateralAssetInfo = read_collateral_info(deps.storage, &asset)?;
+
+    Ok(CollateralInfoResponse {
+        asset: collateral.asset,
+        source_type: collateral.price_source.to_string(),
+        multiplier: collateral.multiplier,
+        is_revoked: collateral.is_revoked,
+    })
+}
+
+pub fn query_collateral_infos(deps: Deps<SeiQueryWrapper>) -> StdResult<CollateralInfosResponse> {
+    let collaterals: Vec<CollateralInfoResponse> = read_collateral_infos(deps.storage)?
+        .into_iter()
+        .map(|collateral| CollateralInfoResponse {
+            asset: collateral.asset,
+            source_type: collateral.price_source.to_string(),
+            multiplier: collateral.multiplier,
+            is_revoked: collateral.is_revoked,
+        })
+        .collect();
+
+    Ok(CollateralInfosResponse { collaterals })
+}
@@ -0,0 +1,7 @@
+pub mod contract;
+mod math;
+mod querier;
+mod state;
+
+#[cfg(test)]
+mod testing;
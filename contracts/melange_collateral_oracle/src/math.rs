@@ -0,0 +1,86 @@
+use std::convert::TryFrom;
+
+use cosmwasm_std::{
+    CheckedFromRatioError, Decimal, DivideByZeroError, OverflowError, OverflowOperation, StdError,
+    StdResult, Uint128, Uint256,
+};
+
+const DECIMAL_FRACTIONAL: Uint128 = Uint128::new(1_000_000_000u128);
+
+/// return amount * decimal, rounded down. The product is computed
+/// on 256 bits so only a result exceeding `Uint128` fails
+fn decimal_mul(amount: Uint128, decimal: Decimal) -> StdResult<Uint128> {
+    let product = Uint256::from(amount).checked_mul(Uint256::from(decimal.atomics()))?
+        / Uint256::from(Decimal::one().atomics());
+
+    Uint128::try_from(product).map_err(|_| {
+        StdError::overflow(OverflowError::new(
+            OverflowOperation::Mul,
+            amount,
+            decimal,
+        ))
+    })
+}
+
+/// return numerator / denominator
+pub fn decimal_from_ratio(numerator: Uint128, denominator: Uint128) -> StdResult<Decimal> {
+    Decimal::checked_from_ratio(numerator, denominator).map_err(|err| match err {
+        CheckedFromRatioError::DivideByZero => {
+            StdError::divide_by_zero(DivideByZeroError::new(numerator))
+        }
+        CheckedFromRatioError::Overflow => StdError::overflow(OverflowError::new(
+            OverflowOperation::Mul,
+            numerator,
+            denominator,
+        )),
+    })
+}
+
+/// return a / b
+pub fn decimal_division(a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    decimal_from_ratio(
+        decimal_mul(DECIMAL_FRACTIONAL, a)?,
+        decimal_mul(DECIMAL_FRACTIONAL, b)?,
+    )
+}
+
+/// return a * b
+pub fn decimal_multiplication(a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    decimal_from_ratio(
+        decimal_mul(decimal_mul(DECIMAL_FRACTIONAL, a)?, b)?,
+        DECIMAL_FRACTIONAL,
+    )
+}
+
+#[cfg(test)]
+mod math_tests {
+    use super::*;
+
+    #[test]
+    fn decimal_operations() {
+        assert_eq!(
+            decimal_division(Decimal::percent(150), Decimal::percent(50)).unwrap(),
+            Decimal::percent(300)
+        );
+        assert_eq!(
+            decimal_multiplication(Decimal::percent(150), Decimal::percent(50)).unwrap(),
+            Decimal::percent(75)
+        );
+    }
+
+    #[test]
+    fn decimal_operations_fail_without_panic() {
+        assert!(matches!(
+            decimal_division(Decimal::one(), Decimal::zero()),
+            Err(StdError::DivideByZero { .. })
+        ));
+        assert!(matches!(
+            decimal_multiplication(Decimal::MAX, Decimal::MAX),
+            Err(StdError::Overflow { .. })
+        ));
+        assert!(matches!(
+            decimal_from_ratio(Uint128::MAX, Uint128::one()),
+            Err(StdError::Overflow { .. })
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+use cosmwasm_std::{
+    to_binary, Decimal, Deps, QuerierWrapper, QueryRequest, StdError, StdResult, WasmQuery,
+};
+use sei_cosmwasm::{
+    ExchangeRatesResponse, OracleExchangeRate, OracleTwapsResponse, SeiQuerier, SeiQueryWrapper,
+};
+use terraswap::asset::AssetInfo;
+use terraswap::pair::{PoolResponse, QueryMsg as PairQueryMsg};
+
+use crate::math::{decimal_division, decimal_from_ratio, decimal_multiplication};
+use crate::state::{read_config, Config};
+use melange_protocol::collateral_oracle::SourceType;
+use melange_protocol::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
+
+/// Resolves the price source of a collateral into its price in base denom
+/// and the time of its last update. Sources that are always up to date
+/// (fixed prices and pools) report `u64::MAX`. Pools are read at their spot
+/// reserves, which a single swap can move, so only trusted pools deep enough
+/// to resist manipulation should back a collateral
+pub fn query_price(
+    deps: Deps<SeiQueryWrapper>,
+    asset: String,
+    price_source: &SourceType,
+    timeframe: Option<u64>,
+) -> StdResult<(Decimal, u64)> {
+    let config: Config = read_config(deps.storage)?;
+
+    match price_source {
+        SourceType::MelangeOracle { oracle_addr } => {
+            let res: PriceResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: oracle_addr.to_string(),
+                msg: to_binary(&OracleQueryMsg::Price {
+                    asset_token: asset,
+                    timeframe,
+                })?,
+            }))?;
+
+            Ok((res.rate, res.last_updated))
+        }
+        SourceType::FixedPrice { price } => Ok((*price, u64::MAX)),
+        SourceType::AmmPair {
+            pair_addr,
+            intermediate_denom,
+        } => {
+            let res: PoolResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: pair_addr.to_string(),
+                msg: to_binary(&PairQueryMsg::Pool {})?,
+            }))?;
+
+            // the pool is quoted in the intermediate denom when provided
+            let quote_denom: &String = intermediate_denom.as_ref().unwrap_or(&config.base_denom);
+            let (quote_pool, collateral_pool) = match &res.assets[0].info {
+                AssetInfo::NativeToken { denom } if denom == quote_denom => {
+                    (res.assets[0].amount, res.assets[1].amount)
+                }
+                _ => match &res.assets[1].info {
+                    AssetInfo::NativeToken { denom } if denom == quote_denom => {
+                        (res.assets[1].amount, res.assets[0].amount)
+                    }
+                    _ => {
+                        return Err(StdError::generic_err(format!(
+                            "The pair does not contain {}",
+                            quote_denom
+                        )))
+                    }
+                },
+            };
+
+            if collateral_pool.is_zero() {
+                return Err(StdError::generic_err("The pair has no liquidity"));
+            }
+
+            let rate = decimal_from_ratio(quote_pool, collateral_pool)?;
+            if let Some(intermediate_denom) = intermediate_denom {
+                let (intermediate_rate, last_updated) = query_sei_oracle_rate(
+                    &deps.querier,
+                    &config.base_denom,
+                    intermediate_denom,
+                    None,
+                )?;
+
+                Ok((
+                    decimal_multiplication(rate, intermediate_rate)?,
+                    last_updated,
+                ))
+            } else {
+                Ok((rate, u64::MAX))
+            }
+        }
+        SourceType::Native { native_denom } => {
+            query_sei_oracle_rate(&deps.querier, &config.base_denom, native_denom, None)
+        }
+        SourceType::SeiOracle {
+            denom,
+            twap_lookback_seconds,
+        } => query_sei_oracle_rate(
+            &deps.querier,
+            &config.base_denom,
+            denom,
+            *twap_lookback_seconds,
+        ),
+        SourceType::AnchorMarket { .. } | SourceType::Lunax { .. } => Err(StdError::generic_err(
+            format!("Unsupported price source: {}", price_source),
+        )),
+    }
+}
+
+/// Queries the Sei oracle module for the price of `denom` in `base_denom`,
+/// using the TWAP over `twap_lookback_seconds` when provided. Oracle rates
+/// are quoted in USD, so the base denom rate is only applied when the oracle
+/// also prices it
+fn query_sei_oracle_rate(
+    querier: &QuerierWrapper<SeiQueryWrapper>,
+    base_denom: &str,
+    denom: &str,
+    twap_lookback_seconds: Option<u64>,
+) -> StdResult<(Decimal, u64)> {
+    if denom == base_denom {
+        return Ok((Decimal::one(), u64::MAX));
+    }
+
+    let sei_querier = SeiQuerier::new(querier);
+    let res: ExchangeRatesResponse = sei_querier.query_exchange_rates()?;
+    let find_rate = |denom: &str| -> Option<&OracleExchangeRate> {
+        res.denom_oracle_exchange_rate_pairs
+            .iter()
+            .find(|pair| pair.denom == denom)
+            .map(|pair| &pair.oracle_exchange_rate)
+    };
+
+    let spot_rate = find_rate(denom)
+        .ok_or_else(|| StdError::generic_err(format!("No oracle exchange rate for {}", denom)))?;
+    let base_spot_rate = find_rate(base_denom);
+
+    // the oracle module reports its update times in milliseconds. A TWAP is
+    // as recent as the latest vote on the denom
+    let last_updated = match base_spot_rate {
+        Some(base_spot_rate) => spot_rate
+            .last_update_timestamp
+            .min(base_spot_rate.last_update_timestamp),
+        None => spot_rate.last_update_timestamp,
+    } / 1000;
+
+    let (rate, base_rate): (Decimal, Option<Decimal>) = match twap_lookback_seconds {
+        Some(lookback_seconds) => {
+            let res: OracleTwapsResponse = sei_querier.query_oracle_twaps(lookback_seconds)?;
+            let find_twap = |denom: &str| -> Option<Decimal> {
+                res.oracle_twaps
+                    .iter()
+                    .find(|twap| twap.denom == denom)
+                    .map(|twap| twap.twap)
+            };
+
+            let rate = find_twap(denom)
+                .ok_or_else(|| StdError::generic_err(format!("No oracle twap for {}", denom)))?;
+            let base_rate = match base_spot_rate {
+                Some(_) => Some(find_twap(base_denom).ok_or_else(|| {
+                    StdError::generic_err(format!("No oracle twap for {}", base_denom))
+                })?),
+                None => None,
+            };
+
+            (rate, base_rate)
+        }
+        None => (
+            spot_rate.exchange_rate,
+            base_spot_rate.map(|base_spot_rate| base_spot_rate.exchange_rate),
+        ),
+    };
+
+    match base_rate {
+        Some(base_rate) => Ok((decimal_division(rate, base_rate)?, last_updated)),
+        None => Ok((rate, last_updated)),
+    }
+}
@@ -0,0 +1,65 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdError, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use melange_protocol::collateral_oracle::SourceType;
+
+static PREFIX_COLLATERAL_ASSET_INFO: &[u8] = b"collateral_asset_info";
+static KEY_CONFIG: &[u8] = b"config";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub mint_contract: CanonicalAddr,
+    pub base_denom: String,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralAssetInfo {
+    pub asset: String,
+    pub price_source: SourceType,
+    pub multiplier: Decimal,
+    pub is_revoked: bool,
+}
+
+pub fn store_collateral_info(
+    storage: &mut dyn Storage,
+    collateral: &CollateralAssetInfo,
+) -> StdResult<()> {
+    let mut collaterals_bucket: Bucket<CollateralAssetInfo> =
+        Bucket::new(storage, PREFIX_COLLATERAL_ASSET_INFO);
+    collaterals_bucket.save(collateral.asset.as_bytes(), collateral)
+}
+
+pub fn read_collateral_info(storage: &dyn Storage, asset: &str) -> StdResult<CollateralAssetInfo> {
+    let collaterals_bucket: ReadonlyBucket<CollateralAssetInfo> =
+        ReadonlyBucket::new(storage, PREFIX_COLLATERAL_ASSET_INFO);
+    match collaterals_bucket.load(asset.as_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err(
+            "Collateral asset not found in the whitelist",
+        )),
+    }
+}
+
+pub fn read_collateral_infos(storage: &dyn Storage) -> StdResult<Vec<CollateralAssetInfo>> {
+    let collaterals_bucket: ReadonlyBucket<CollateralAssetInfo> =
+        ReadonlyBucket::new(storage, PREFIX_COLLATERAL_ASSET_INFO);
+
+    collaterals_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
@@ -0,0 +1,513 @@
+use crate::contract::{execute, instantiate, query};
+use crate::testing::mock_querier::{mock_dependencies, native_asset, token_asset};
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{attr, from_binary, Decimal, StdError};
+use melange_protocol::collateral_oracle::{
+    CollateralInfoResponse, CollateralInfosResponse, CollateralPriceResponse, ConfigResponse,
+    ExecuteMsg, InstantiateMsg, QueryMsg, SourceType,
+};
+use terraswap::asset::AssetInfo;
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mint_contract: "mint0000".to_string(),
+        base_denom: "uusd".to_string(),
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            mint_contract: "mint0000".to_string(),
+            base_denom: "uusd".to_string(),
+        }
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        mint_contract: Some("mint0001".to_string()),
+        base_denom: Some("usei".to_string()),
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0001".to_string(),
+            mint_contract: "mint0001".to_string(),
+            base_denom: "usei".to_string(),
+        }
+    );
+}
+
+#[test]
+fn register_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterCollateralAsset {
+        asset: AssetInfo::Token {
+            contract_addr: "masset0000".to_string(),
+        },
+        price_source: SourceType::MelangeOracle {
+            oracle_addr: "oracle0000".to_string(),
+        },
+        multiplier: Decimal::percent(100),
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    // the mint contract registers the collateral when an asset is whitelisted
+    let info = mock_info("mint0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_collateral"),
+            attr("collateral_asset", "masset0000"),
+        ]
+    );
+
+    // cannot register twice
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Collateral was already registered")
+    );
+
+    // multiplier can not be zero
+    let msg = ExecuteMsg::RegisterCollateralAsset {
+        asset: AssetInfo::NativeToken {
+            denom: "usei".to_string(),
+        },
+        price_source: SourceType::Native {
+            native_denom: "usei".to_string(),
+        },
+        multiplier: Decimal::zero(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Multiplier must be bigger than 0")
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralAssetInfo {
+            asset: "masset0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collateral_info: CollateralInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collateral_info,
+        CollateralInfoResponse {
+            asset: "masset0000".to_string(),
+            multiplier: Decimal::percent(100),
+            source_type: "melange_oracle".to_string(),
+            is_revoked: false,
+        }
+    );
+}
+
+#[test]
+fn update_collateral() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[(
+        &"masset0000".to_string(),
+        &Decimal::percent(150),
+        &1000u64,
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterCollateralAsset {
+        asset: AssetInfo::Token {
+            contract_addr: "masset0000".to_string(),
+        },
+        price_source: SourceType::FixedPrice {
+            price: Decimal::percent(100),
+        },
+        multiplier: Decimal::percent(100),
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the owner can update the price source
+    let msg = ExecuteMsg::UpdateCollateralPriceSource {
+        asset: AssetInfo::Token {
+            contract_addr: "masset0000".to_string(),
+        },
+        price_source: SourceType::MelangeOracle {
+            oracle_addr: "oracle0000".to_string(),
+        },
+    };
+    let info = mock_info("mint0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // multiplier can not be zero
+    let msg = ExecuteMsg::UpdateCollateralMultiplier {
+        asset: AssetInfo::Token {
+            contract_addr: "masset0000".to_string(),
+        },
+        multiplier: Decimal::zero(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Multiplier must be bigger than 0")
+    );
+
+    let msg = ExecuteMsg::UpdateCollateralMultiplier {
+        asset: AssetInfo::Token {
+            contract_addr: "masset0000".to_string(),
+        },
+        multiplier: Decimal::percent(120),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_collateral_multiplier"),
+            attr("collateral_asset", "masset0000"),
+            attr("multiplier", "1.2"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralPrice {
+            asset: "masset0000".to_string(),
+            timeframe: None,
+        },
+    )
+    .unwrap();
+    let collateral_price: CollateralPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collateral_price,
+        CollateralPriceResponse {
+            asset: "masset0000".to_string(),
+            rate: Decimal::percent(150),
+            last_updated: 1000u64,
+            multiplier: Decimal::percent(120),
+            is_revoked: false,
+        }
+    );
+}
+
+#[test]
+fn revoke_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterCollateralAsset {
+        asset: AssetInfo::Token {
+            contract_addr: "masset0000".to_string(),
+        },
+        price_source: SourceType::FixedPrice {
+            price: Decimal::percent(100),
+        },
+        multiplier: Decimal::percent(100),
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RevokeCollateralAsset {
+        asset: AssetInfo::Token {
+            contract_addr: "masset0000".to_string(),
+        },
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("mint0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralPrice {
+            asset: "masset0000".to_string(),
+            timeframe: None,
+        },
+    )
+    .unwrap();
+    let collateral_price: CollateralPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collateral_price,
+        CollateralPriceResponse {
+            asset: "masset0000".to_string(),
+            rate: Decimal::percent(100),
+            last_updated: u64::MAX,
+            multiplier: Decimal::percent(100),
+            is_revoked: true,
+        }
+    );
+}
+
+#[test]
+fn query_collateral_prices() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_pool(&[
+        (
+            &"pair0000".to_string(),
+            &[
+                native_asset("uusd", 1000000),
+                token_asset("token0000", 500000),
+            ],
+        ),
+        (
+            &"pair0001".to_string(),
+            &[
+                token_asset("token0001", 1000000),
+                native_asset("usei", 4000000),
+            ],
+        ),
+    ]);
+    deps.querier.with_exchange_rates(&[
+        (&"usei".to_string(), &Decimal::percent(50), &2000000u64),
+        (&"uatom".to_string(), &Decimal::percent(1000), &3000000u64),
+    ]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let collaterals = vec![
+        (
+            AssetInfo::Token {
+                contract_addr: "token0000".to_string(),
+            },
+            SourceType::AmmPair {
+                pair_addr: "pair0000".to_string(),
+                intermediate_denom: None,
+            },
+        ),
+        (
+            AssetInfo::Token {
+                contract_addr: "token0001".to_string(),
+            },
+            SourceType::AmmPair {
+                pair_addr: "pair0001".to_string(),
+                intermediate_denom: Some("usei".to_string()),
+            },
+        ),
+        (
+            AssetInfo::NativeToken {
+                denom: "uatom".to_string(),
+            },
+            SourceType::Native {
+                native_denom: "uatom".to_string(),
+            },
+        ),
+        (
+            AssetInfo::Token {
+                contract_addr: "token0002".to_string(),
+            },
+            SourceType::Lunax {
+                staking_contract_addr: "staking0000".to_string(),
+            },
+        ),
+    ];
+    for (asset, price_source) in collaterals {
+        let msg = ExecuteMsg::RegisterCollateralAsset {
+            asset,
+            price_source,
+            multiplier: Decimal::percent(100),
+        };
+        let info = mock_info("owner0000", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let query_price = |deps: &cosmwasm_std::OwnedDeps<_, _, _, _>, asset: &str| {
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CollateralPrice {
+                asset: asset.to_string(),
+                timeframe: None,
+            },
+        )
+        .map(|res| from_binary::<CollateralPriceResponse>(&res).unwrap())
+    };
+
+    // 1000000uusd / 500000token0000
+    let res = query_price(&deps, "token0000").unwrap();
+    assert_eq!(res.rate, Decimal::percent(200));
+    assert_eq!(res.last_updated, u64::MAX);
+
+    // 4000000usei / 1000000token0001 * 0.5uusd
+    let res = query_price(&deps, "token0001").unwrap();
+    assert_eq!(res.rate, Decimal::percent(200));
+    assert_eq!(res.last_updated, 2000u64);
+
+    let res = query_price(&deps, "uatom").unwrap();
+    assert_eq!(res.rate, Decimal::percent(1000));
+    assert_eq!(res.last_updated, 3000u64);
+
+    let res = query_price(&deps, "token0002").unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Unsupported price source: lunax")
+    );
+
+    let res = query_price(&deps, "token0003").unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Collateral asset not found in the whitelist")
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::CollateralAssetInfos {}).unwrap();
+    let collateral_infos: CollateralInfosResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collateral_infos
+            .collaterals
+            .iter()
+            .map(|collateral| (collateral.asset.as_str(), collateral.source_type.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("token0000", "amm_pair"),
+            ("token0001", "amm_pair"),
+            ("token0002", "lunax"),
+            ("uatom", "native"),
+        ]
+    );
+}
+
+#[test]
+fn query_sei_oracle_price() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_exchange_rates(&[
+        (&"usei".to_string(), &Decimal::percent(50), &2000000u64),
+        (&"uusdc".to_string(), &Decimal::percent(100), &3000000u64),
+        (&"uatom".to_string(), &Decimal::percent(1000), &1000000u64),
+    ]);
+    deps.querier.with_oracle_twaps(&[
+        (&"usei".to_string(), &Decimal::percent(40)),
+        (&"uusdc".to_string(), &Decimal::percent(80)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mint_contract: "mint0000".to_string(),
+        base_denom: "uusdc".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let collaterals = vec![("usei", Some(3600u64)), ("uatom", None)];
+    for (denom, twap_lookback_seconds) in collaterals {
+        let msg = ExecuteMsg::RegisterCollateralAsset {
+            asset: AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            },
+            price_source: SourceType::SeiOracle {
+                denom: denom.to_string(),
+                twap_lookback_seconds,
+            },
+            multiplier: Decimal::percent(100),
+        };
+        let info = mock_info("owner0000", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // twap of usei in uusdc, 0.4 / 0.8
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralPrice {
+            asset: "usei".to_string(),
+            timeframe: None,
+        },
+    )
+    .unwrap();
+    let collateral_price: CollateralPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collateral_price,
+        CollateralPriceResponse {
+            asset: "usei".to_string(),
+            rate: Decimal::percent(50),
+            last_updated: 2000u64,
+            multiplier: Decimal::percent(100),
+            is_revoked: false,
+        }
+    );
+
+    // spot price of uatom in uusdc, 10 / 1
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralPrice {
+            asset: "uatom".to_string(),
+            timeframe: None,
+        },
+    )
+    .unwrap();
+    let collateral_price: CollateralPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collateral_price,
+        CollateralPriceResponse {
+            asset: "uatom".to_string(),
+            rate: Decimal::percent(1000),
+            last_updated: 1000u64,
+            multiplier: Decimal::percent(100),
+            is_revoked: false,
+        }
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::CollateralAssetInfos {}).unwrap();
+    let collateral_infos: CollateralInfosResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collateral_infos.collaterals[1],
+        CollateralInfoResponse {
+            asset: "usei".to_string(),
+            multiplier: Decimal::percent(100),
+            source_type: "sei_oracle".to_string(),
+            is_revoked: false,
+        }
+    );
+}
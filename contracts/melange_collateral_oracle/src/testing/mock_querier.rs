@@ -0,0 +1,254 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, Uint64, WasmQuery,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use melange_protocol::oracle::PriceResponse;
+use sei_cosmwasm::{
+    DenomOracleExchangeRatePair, ExchangeRatesResponse, OracleExchangeRate, OracleTwap,
+    OracleTwapsResponse, SeiQuery, SeiQueryWrapper,
+};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::PoolResponse;
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier, SeiQueryWrapper> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        api: MockApi::default(),
+        storage: MockStorage::default(),
+        querier: custom_querier,
+        custom_query_type: PhantomData,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<SeiQueryWrapper>,
+    oracle_price_querier: OraclePriceQuerier,
+    exchange_rate_querier: ExchangeRateQuerier,
+    oracle_twap_querier: OracleTwapQuerier,
+    pool_querier: PoolQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct OraclePriceQuerier {
+    oracle_price: HashMap<String, (Decimal, u64)>,
+}
+
+impl OraclePriceQuerier {
+    pub fn new(oracle_price: &[(&String, &Decimal, &u64)]) -> Self {
+        let mut oracle_price_map: HashMap<String, (Decimal, u64)> = HashMap::new();
+        for (asset, price, last_updated) in oracle_price.iter() {
+            oracle_price_map.insert((*asset).clone(), (**price, **last_updated));
+        }
+
+        OraclePriceQuerier {
+            oracle_price: oracle_price_map,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ExchangeRateQuerier {
+    exchange_rates: Vec<DenomOracleExchangeRatePair>,
+}
+
+impl ExchangeRateQuerier {
+    pub fn new(exchange_rates: &[(&String, &Decimal, &u64)]) -> Self {
+        ExchangeRateQuerier {
+            exchange_rates: exchange_rates
+                .iter()
+                .map(
+                    |(denom, rate, last_update_timestamp)| DenomOracleExchangeRatePair {
+                        denom: (*denom).clone(),
+                        oracle_exchange_rate: OracleExchangeRate {
+                            exchange_rate: **rate,
+                            last_update: Uint64::zero(),
+                            last_update_timestamp: **last_update_timestamp,
+                        },
+                    },
+                )
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct OracleTwapQuerier {
+    oracle_twaps: Vec<OracleTwap>,
+}
+
+impl OracleTwapQuerier {
+    pub fn new(oracle_twaps: &[(&String, &Decimal)]) -> Self {
+        OracleTwapQuerier {
+            oracle_twaps: oracle_twaps
+                .iter()
+                .map(|(denom, twap)| OracleTwap {
+                    denom: (*denom).clone(),
+                    twap: **twap,
+                    lookback_seconds: 0,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PoolQuerier {
+    pools: HashMap<String, [Asset; 2]>,
+}
+
+impl PoolQuerier {
+    pub fn new(pools: &[(&String, &[Asset; 2])]) -> Self {
+        let mut pools_map: HashMap<String, [Asset; 2]> = HashMap::new();
+        for (pair_addr, assets) in pools.iter() {
+            pools_map.insert((*pair_addr).clone(), (*assets).clone());
+        }
+
+        PoolQuerier { pools: pools_map }
+    }
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<SeiQueryWrapper> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {:?}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MockQueryMsg {
+    Price {
+        asset_token: String,
+        timeframe: Option<u64>,
+    },
+    Pool {},
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<SeiQueryWrapper>) -> QuerierResult {
+        match &request {
+            QueryRequest::Custom(SeiQueryWrapper {
+                route: _,
+                query_data: SeiQuery::ExchangeRates {},
+            }) => SystemResult::Ok(ContractResult::from(to_binary(&ExchangeRatesResponse {
+                denom_oracle_exchange_rate_pairs: self.exchange_rate_querier.exchange_rates.clone(),
+            }))),
+            QueryRequest::Custom(SeiQueryWrapper {
+                route: _,
+                query_data: SeiQuery::OracleTwaps { lookback_seconds },
+            }) => SystemResult::Ok(ContractResult::from(to_binary(&OracleTwapsResponse {
+                oracle_twaps: self
+                    .oracle_twap_querier
+                    .oracle_twaps
+                    .iter()
+                    .map(|twap| OracleTwap {
+                        lookback_seconds: *lookback_seconds,
+                        ..twap.clone()
+                    })
+                    .collect(),
+            }))),
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    MockQueryMsg::Price {
+                        asset_token,
+                        timeframe: _,
+                    } => match self.oracle_price_querier.oracle_price.get(&asset_token) {
+                        Some((rate, last_updated)) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                                rate: *rate,
+                                last_updated: *last_updated,
+                            })))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No oracle price exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
+                    MockQueryMsg::Pool {} => match self.pool_querier.pools.get(contract_addr) {
+                        Some(assets) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(&PoolResponse {
+                                assets: assets.clone(),
+                                total_share: Uint128::zero(),
+                            })))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No pair exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<SeiQueryWrapper>) -> Self {
+        WasmMockQuerier {
+            base,
+            oracle_price_querier: OraclePriceQuerier::default(),
+            exchange_rate_querier: ExchangeRateQuerier::default(),
+            oracle_twap_querier: OracleTwapQuerier::default(),
+            pool_querier: PoolQuerier::default(),
+        }
+    }
+
+    // configure the melange oracle price mock querier
+    pub fn with_oracle_price(&mut self, oracle_price: &[(&String, &Decimal, &u64)]) {
+        self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
+    }
+
+    // configure the native oracle exchange rates mock querier
+    pub fn with_exchange_rates(&mut self, exchange_rates: &[(&String, &Decimal, &u64)]) {
+        self.exchange_rate_querier = ExchangeRateQuerier::new(exchange_rates);
+    }
+
+    // configure the native oracle twaps mock querier
+    pub fn with_oracle_twaps(&mut self, oracle_twaps: &[(&String, &Decimal)]) {
+        self.oracle_twap_querier = OracleTwapQuerier::new(oracle_twaps);
+    }
+
+    // configure the terraswap pair pool mock querier
+    pub fn with_pool(&mut self, pools: &[(&String, &[Asset; 2])]) {
+        self.pool_querier = PoolQuerier::new(pools);
+    }
+}
+
+pub fn native_asset(denom: &str, amount: u128) -> Asset {
+    Asset {
+        info: AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        },
+        amount: Uint128::from(amount),
+    }
+}
+
+pub fn token_asset(contract_addr: &str, amount: u128) -> Asset {
+    Asset {
+        info: AssetInfo::Token {
+            contract_addr: contract_addr.to_string(),
+        },
+        amount: Uint128::from(amount),
+    }
+}
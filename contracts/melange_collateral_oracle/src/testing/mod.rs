@@ -0,0 +1,2 @@
+mod contract_test;
+mod mock_querier;
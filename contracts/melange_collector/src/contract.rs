@@ -0,0 +1,246 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+};
+
+use crate::state::{
+    read_config, read_conversion_route, read_conversion_routes, remove_conversion_route,
+    store_config, store_conversion_route, Config, ConversionRoute,
+};
+use crate::swap::{convert, distribute, swap_hook};
+
+use melange_protocol::collector::{
+    ConfigResponse, ConversionRouteResponse, ConversionRoutesResponse, ConversionStep, ExecuteMsg,
+    InstantiateMsg, QueryMsg,
+};
+use terraswap::asset::AssetInfo;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let mln_base_pair = msg
+        .mln_base_pair
+        .map(|pair| deps.api.addr_canonicalize(&pair))
+        .transpose()?;
+
+    store_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_canonicalize(&msg.owner)?,
+            distribution_contract: deps.api.addr_canonicalize(&msg.distribution_contract)?,
+            tswap_factory: deps.api.addr_canonicalize(&msg.tswap_factory)?,
+            melange_token: deps.api.addr_canonicalize(&msg.melange_token)?,
+            base_denom: msg.base_denom,
+            mln_base_pair,
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::UpdateConfig {
+            owner,
+            distribution_contract,
+            tswap_factory,
+            melange_token,
+            base_denom,
+            mln_base_pair,
+        } => update_config(
+            deps,
+            info,
+            owner,
+            distribution_contract,
+            tswap_factory,
+            melange_token,
+            base_denom,
+            mln_base_pair,
+        ),
+        ExecuteMsg::UpdateConversionRoute { asset_info, route } => {
+            update_conversion_route(deps, info, asset_info, route)
+        }
+        ExecuteMsg::Convert { asset_token } => convert(deps, env, asset_token),
+        ExecuteMsg::Distribute {} => distribute(deps, env),
+        ExecuteMsg::SwapHook {
+            offer_asset_info,
+            pair_contract,
+        } => swap_hook(deps, env, info, offer_asset_info, pair_contract),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    distribution_contract: Option<String>,
+    tswap_factory: Option<String>,
+    melange_token: Option<String>,
+    base_denom: Option<String>,
+    mln_base_pair: Option<String>,
+) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    if let Some(distribution_contract) = distribution_contract {
+        config.distribution_contract = deps.api.addr_canonicalize(&distribution_contract)?;
+    }
+
+    if let Some(tswap_factory) = tswap_factory {
+        config.tswap_factory = deps.api.addr_canonicalize(&tswap_factory)?;
+    }
+
+    if let Some(melange_token) = melange_token {
+        config.melange_token = deps.api.addr_canonicalize(&melange_token)?;
+    }
+
+    if let Some(base_denom) = base_denom {
+        config.base_denom = base_denom;
+    }
+
+    if let Some(mln_base_pair) = mln_base_pair {
+        config.mln_base_pair = Some(deps.api.addr_canonicalize(&mln_base_pair)?);
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+/// Sets or removes the conversion route of a collected asset.
+/// A route must end with the base denom
+pub fn update_conversion_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    route: Option<Vec<ConversionStep>>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let base_asset_info = AssetInfo::NativeToken {
+        denom: config.base_denom,
+    };
+    if asset_info.equal(&base_asset_info) {
+        return Err(StdError::generic_err(
+            "The base denom cannot have a conversion route",
+        ));
+    }
+
+    let asset_key = asset_info.to_string();
+    let route = match route {
+        Some(route) => route,
+        None => {
+            remove_conversion_route(deps.storage, &asset_key);
+            return Ok(Response::new().add_attributes(vec![
+                attr("action", "remove_conversion_route"),
+                attr("asset", asset_key),
+            ]));
+        }
+    };
+
+    match route.last() {
+        Some(step) if step.ask_asset_info.equal(&base_asset_info) => (),
+        _ => {
+            return Err(StdError::generic_err(
+                "The conversion route must end with the base denom",
+            ))
+        }
+    }
+
+    validate_asset_info(deps.as_ref(), &asset_info)?;
+    for step in route.iter() {
+        deps.api.addr_validate(&step.pair_contract)?;
+        validate_asset_info(deps.as_ref(), &step.ask_asset_info)?;
+    }
+
+    store_conversion_route(
+        deps.storage,
+        &ConversionRoute {
+            asset_info,
+            route: route.clone(),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_conversion_route"),
+        attr("asset", asset_key),
+        attr("steps", route.len().to_string()),
+    ]))
+}
+
+fn validate_asset_info(deps: Deps, asset_info: &AssetInfo) -> StdResult<()> {
+    if let AssetInfo::Token { contract_addr } = asset_info {
+        deps.api.addr_validate(contract_addr)?;
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::ConversionRoute { asset_token } => {
+            to_binary(&query_conversion_route(deps, asset_token)?)
+        }
+        QueryMsg::ConversionRoutes {} => to_binary(&query_conversion_routes(deps)?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = read_config(deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.addr_humanize(&state.owner)?.to_string(),
+        distribution_contract: deps
+            .api
+            .addr_humanize(&state.distribution_contract)?
+            .to_string(),
+        tswap_factory: deps.api.addr_humanize(&state.tswap_factory)?.to_string(),
+        melange_token: deps.api.addr_humanize(&state.melange_token)?.to_string(),
+        base_denom: state.base_denom,
+        mln_base_pair: state
+            .mln_base_pair
+            .map(|pair| deps.api.addr_humanize(&pair).map(|addr| addr.to_string()))
+            .transpose()?,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_conversion_route(
+    deps: Deps,
+    asset_token: String,
+) -> StdResult<ConversionRouteResponse> {
+    let conversion_route = read_conversion_route(deps.storage, &asset_token)?;
+    Ok(ConversionRouteResponse {
+        asset_info: conversion_route.asset_info,
+        route: conversion_route.route,
+    })
+}
+
+pub fn query_conversion_routes(deps: Deps) -> StdResult<ConversionRoutesResponse> {
+    let routes = read_conversion_routes(deps.storage)?
+        .into_iter()
+        .map(|conversion_route| ConversionRouteResponse {
+            asset_info: conversion_route.asset_info,
+            route: conversion_route.route,
+        })
+        .collect();
+
+    Ok(ConversionRoutesResponse { routes })
+}
@@ -0,0 +1,6 @@
+pub mod contract;
+mod state;
+mod swap;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,68 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{CanonicalAddr, Order, StdError, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use melange_protocol::collector::ConversionStep;
+use terraswap::asset::AssetInfo;
+
+static KEY_CONFIG: &[u8] = b"config";
+
+static PREFIX_CONVERSION_ROUTE: &[u8] = b"conversion_route";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub distribution_contract: CanonicalAddr, // collected rewards receiver
+    pub tswap_factory: CanonicalAddr,
+    pub melange_token: CanonicalAddr,
+    pub base_denom: String,
+    pub mln_base_pair: Option<CanonicalAddr>,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+/// Swaps converting a collected asset to the base denom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConversionRoute {
+    pub asset_info: AssetInfo,
+    pub route: Vec<ConversionStep>,
+}
+
+/// Routes are keyed by the token address or the native denom
+pub fn store_conversion_route(
+    storage: &mut dyn Storage,
+    conversion_route: &ConversionRoute,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_CONVERSION_ROUTE).save(
+        conversion_route.asset_info.to_string().as_bytes(),
+        conversion_route,
+    )
+}
+
+pub fn read_conversion_route(storage: &dyn Storage, asset_key: &str) -> StdResult<ConversionRoute> {
+    match ReadonlyBucket::new(storage, PREFIX_CONVERSION_ROUTE).load(asset_key.as_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err("No conversion route stored")),
+    }
+}
+
+pub fn remove_conversion_route(storage: &mut dyn Storage, asset_key: &str) {
+    Bucket::<ConversionRoute>::new(storage, PREFIX_CONVERSION_ROUTE).remove(asset_key.as_bytes())
+}
+
+pub fn read_conversion_routes(storage: &dyn Storage) -> StdResult<Vec<ConversionRoute>> {
+    ReadonlyBucket::new(storage, PREFIX_CONVERSION_ROUTE)
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
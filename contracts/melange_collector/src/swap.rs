@@ -0,0 +1,210 @@
+use cosmwasm_std::{
+    attr, to_binary, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    WasmMsg,
+};
+
+use crate::state::{read_config, read_conversion_route, Config};
+
+use cw20::Cw20ExecuteMsg;
+use melange_protocol::collector::{ConversionStep, ExecuteMsg};
+use melange_protocol::gov::Cw20HookMsg as GovCw20HookMsg;
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg};
+use terraswap::querier::{query_pair_info, query_token_balance};
+
+/// Convert
+/// Anyone can execute convert function to swap
+/// asset token => base denom through the asset conversion route
+/// base denom => melange token when the melange token is given
+pub fn convert(deps: DepsMut, env: Env, asset_token: String) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let tswap_factory = deps.api.addr_humanize(&config.tswap_factory)?;
+    let melange_token = deps.api.addr_humanize(&config.melange_token)?;
+    let base_asset_info = AssetInfo::NativeToken {
+        denom: config.base_denom.clone(),
+    };
+
+    let (asset_info, route) = if asset_token == melange_token.as_str() {
+        let pair_contract = match config.mln_base_pair {
+            Some(pair) => deps.api.addr_humanize(&pair)?.to_string(),
+            None => {
+                query_pair_info(
+                    &deps.querier,
+                    tswap_factory,
+                    &[
+                        base_asset_info.clone(),
+                        AssetInfo::Token {
+                            contract_addr: melange_token.to_string(),
+                        },
+                    ],
+                )?
+                .contract_addr
+            }
+        };
+
+        (
+            base_asset_info,
+            vec![ConversionStep {
+                pair_contract,
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: melange_token.to_string(),
+                },
+            }],
+        )
+    } else {
+        match read_conversion_route(deps.storage, &asset_token) {
+            Ok(conversion_route) => (conversion_route.asset_info, conversion_route.route),
+            Err(_) => {
+                // assets without route are swapped through their base denom pair
+                let asset_info = AssetInfo::Token {
+                    contract_addr: deps.api.addr_validate(&asset_token)?.to_string(),
+                };
+                let pair_info = query_pair_info(
+                    &deps.querier,
+                    tswap_factory,
+                    &[base_asset_info.clone(), asset_info.clone()],
+                )?;
+
+                (
+                    asset_info,
+                    vec![ConversionStep {
+                        pair_contract: pair_info.contract_addr,
+                        ask_asset_info: base_asset_info,
+                    }],
+                )
+            }
+        }
+    };
+
+    let amount = asset_info.query_pool(&deps.querier, deps.api, env.contract.address.clone())?;
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to convert"));
+    }
+
+    // the first swap offers the collected amount, the next ones
+    // swap the whole balance received from the previous step
+    let mut messages: Vec<CosmosMsg> = vec![swap_msg(
+        Asset {
+            info: asset_info,
+            amount,
+        },
+        route[0].pair_contract.clone(),
+    )?];
+    for (prev_step, step) in route.iter().zip(route.iter().skip(1)) {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::SwapHook {
+                offer_asset_info: prev_step.ask_asset_info.clone(),
+                pair_contract: step.pair_contract.clone(),
+            })?,
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "convert"),
+        attr("asset_token", asset_token),
+        attr("amount", amount.to_string()),
+        attr("steps", route.len().to_string()),
+    ]))
+}
+
+/// SwapHook
+/// Swaps the whole balance of an intermediate asset of a conversion route.
+/// Can only be executed by the contract itself
+pub fn swap_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    offer_asset_info: AssetInfo,
+    pair_contract: String,
+) -> StdResult<Response> {
+    if info.sender != env.contract.address {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let amount = offer_asset_info.query_pool(&deps.querier, deps.api, env.contract.address)?;
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to swap"));
+    }
+
+    Ok(Response::new()
+        .add_message(swap_msg(
+            Asset {
+                info: offer_asset_info.clone(),
+                amount,
+            },
+            pair_contract,
+        )?)
+        .add_attributes(vec![
+            attr("action", "swap_hook"),
+            attr("offer_asset", offer_asset_info.to_string()),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
+fn swap_msg(offer_asset: Asset, pair_contract: String) -> StdResult<CosmosMsg> {
+    Ok(match offer_asset.info.clone() {
+        AssetInfo::NativeToken { denom } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_contract,
+            funds: vec![Coin {
+                denom,
+                amount: offer_asset.amount,
+            }],
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset,
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+            })?,
+        }),
+        AssetInfo::Token { contract_addr } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: pair_contract,
+                amount: offer_asset.amount,
+                msg: to_binary(&PairCw20HookMsg::Swap {
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                    deadline: None,
+                })?,
+            })?,
+        }),
+    })
+}
+
+// Anyone can execute distribute function to send
+// the collected melange token to the distribution contract
+pub fn distribute(deps: DepsMut, env: Env) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let amount = query_token_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.melange_token)?,
+        env.contract.address,
+    )?;
+
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to distribute"));
+    }
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.melange_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: deps
+                    .api
+                    .addr_humanize(&config.distribution_contract)?
+                    .to_string(),
+                amount,
+                msg: to_binary(&GovCw20HookMsg::DepositReward {})?,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "distribute"),
+            attr("amount", amount.to_string()),
+        ]))
+}
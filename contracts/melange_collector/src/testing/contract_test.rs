@@ -0,0 +1,402 @@
+use crate::contract::{execute, instantiate, query};
+use crate::testing::mock_querier::mock_dependencies;
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{from_binary, to_binary, Coin, CosmosMsg, StdError, SubMsg, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use melange_protocol::collector::{
+    ConfigResponse, ConversionRouteResponse, ConversionRoutesResponse, ConversionStep, ExecuteMsg,
+    InstantiateMsg, QueryMsg,
+};
+use melange_protocol::gov::Cw20HookMsg as GovCw20HookMsg;
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg};
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: "owner0000".to_string(),
+        distribution_contract: "gov0000".to_string(),
+        tswap_factory: "tswapfactory".to_string(),
+        melange_token: "mln0000".to_string(),
+        base_denom: "usei".to_string(),
+        mln_base_pair: None,
+    }
+}
+
+fn native_swap_msg(pair: &str, denom: &str, amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair.to_string(),
+        funds: vec![Coin {
+            denom: denom.to_string(),
+            amount: Uint128::from(amount),
+        }],
+        msg: to_binary(&PairExecuteMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: denom.to_string(),
+                },
+                amount: Uint128::from(amount),
+            },
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    }))
+}
+
+fn token_swap_msg(pair: &str, token: &str, amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: pair.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&PairCw20HookMsg::Swap {
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+            })
+            .unwrap(),
+        })
+        .unwrap(),
+    }))
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            distribution_contract: "gov0000".to_string(),
+            tswap_factory: "tswapfactory".to_string(),
+            melange_token: "mln0000".to_string(),
+            base_denom: "usei".to_string(),
+            mln_base_pair: None,
+        }
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        distribution_contract: None,
+        tswap_factory: None,
+        melange_token: None,
+        base_denom: None,
+        mln_base_pair: Some("mlnpair0000".to_string()),
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, "owner0001".to_string());
+    assert_eq!(config.mln_base_pair, Some("mlnpair0000".to_string()));
+}
+
+#[test]
+fn test_update_conversion_route() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let asset_info = AssetInfo::NativeToken {
+        denom: "uatom".to_string(),
+    };
+    let route = vec![
+        ConversionStep {
+            pair_contract: "pair0000".to_string(),
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: "bridge0000".to_string(),
+            },
+        },
+        ConversionStep {
+            pair_contract: "pair0001".to_string(),
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "usei".to_string(),
+            },
+        },
+    ];
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateConversionRoute {
+        asset_info: asset_info.clone(),
+        route: Some(route.clone()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    // the route must end with the base denom
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateConversionRoute {
+        asset_info: asset_info.clone(),
+        route: Some(route[..1].to_vec()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("The conversion route must end with the base denom")
+    );
+
+    let msg = ExecuteMsg::UpdateConversionRoute {
+        asset_info: asset_info.clone(),
+        route: Some(vec![]),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("The conversion route must end with the base denom")
+    );
+
+    // the base denom is never converted
+    let msg = ExecuteMsg::UpdateConversionRoute {
+        asset_info: AssetInfo::NativeToken {
+            denom: "usei".to_string(),
+        },
+        route: Some(route.clone()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("The base denom cannot have a conversion route")
+    );
+
+    let msg = ExecuteMsg::UpdateConversionRoute {
+        asset_info: asset_info.clone(),
+        route: Some(route.clone()),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ConversionRoute {
+            asset_token: "uatom".to_string(),
+        },
+    )
+    .unwrap();
+    let conversion_route: ConversionRouteResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        conversion_route,
+        ConversionRouteResponse {
+            asset_info: asset_info.clone(),
+            route,
+        }
+    );
+
+    // remove the route
+    let msg = ExecuteMsg::UpdateConversionRoute {
+        asset_info,
+        route: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::ConversionRoutes {}).unwrap();
+    let conversion_routes: ConversionRoutesResponse = from_binary(&res).unwrap();
+    assert_eq!(conversion_routes.routes, vec![]);
+}
+
+#[test]
+fn test_convert() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "usei".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    deps.querier.with_tswap_pair(
+        &[
+            AssetInfo::NativeToken {
+                denom: "usei".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        "pair0000",
+    );
+    deps.querier.with_tswap_pair(
+        &[
+            AssetInfo::NativeToken {
+                denom: "usei".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "mln0000".to_string(),
+            },
+        ],
+        "mlnpair0000",
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+
+    // assets without route use their base denom pair
+    let msg = ExecuteMsg::Convert {
+        asset_token: "asset0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![token_swap_msg("pair0000", "asset0000", 100u128)]
+    );
+
+    // the base denom is swapped to the melange token
+    let msg = ExecuteMsg::Convert {
+        asset_token: "mln0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![native_swap_msg("mlnpair0000", "usei", 100u128)]
+    );
+
+    // nothing to convert without balance
+    let msg = ExecuteMsg::Convert {
+        asset_token: "asset0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert!(matches!(res, StdError::GenericErr { .. }));
+}
+
+#[test]
+fn test_convert_with_route() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uatom".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let route = vec![
+        ConversionStep {
+            pair_contract: "pair0000".to_string(),
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: "bridge0000".to_string(),
+            },
+        },
+        ConversionStep {
+            pair_contract: "pair0001".to_string(),
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "usei".to_string(),
+            },
+        },
+    ];
+    let msg = ExecuteMsg::UpdateConversionRoute {
+        asset_info: AssetInfo::NativeToken {
+            denom: "uatom".to_string(),
+        },
+        route: Some(route),
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Convert {
+        asset_token: "uatom".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            native_swap_msg("pair0000", "uatom", 100u128),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::SwapHook {
+                    offer_asset_info: AssetInfo::Token {
+                        contract_addr: "bridge0000".to_string(),
+                    },
+                    pair_contract: "pair0001".to_string(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // the hook swaps the received intermediate asset
+    deps.querier.with_token_balances(&[(
+        &"bridge0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(50u128))],
+    )]);
+    let msg = ExecuteMsg::SwapHook {
+        offer_asset_info: AssetInfo::Token {
+            contract_addr: "bridge0000".to_string(),
+        },
+        pair_contract: "pair0001".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![token_swap_msg("pair0001", "bridge0000", 50u128)]
+    );
+}
+
+#[test]
+fn test_distribute() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap_err();
+    assert_eq!(res, StdError::generic_err("Nothing to distribute"));
+
+    deps.querier.with_token_balances(&[(
+        &"mln0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Distribute {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mln0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "gov0000".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&GovCw20HookMsg::DepositReward {}).unwrap(),
+            })
+            .unwrap(),
+        }))]
+    );
+}
@@ -0,0 +1,151 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Empty, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+};
+use cw20::BalanceResponse;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use terraswap::asset::{AssetInfo, PairInfo};
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        api: MockApi::default(),
+        storage: MockStorage::default(),
+        querier: custom_querier,
+        custom_query_type: PhantomData,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    token_querier: TokenQuerier,
+    tswap_pair_querier: TswapPairQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct TokenQuerier {
+    // maps token contract to the balances of each address
+    balances: HashMap<String, HashMap<String, Uint128>>,
+}
+
+impl TokenQuerier {
+    pub fn new(balances: &[(&String, &[(&String, &Uint128)])]) -> Self {
+        let mut balances_map: HashMap<String, HashMap<String, Uint128>> = HashMap::new();
+        for (contract_addr, balances) in balances.iter() {
+            let mut contract_balances_map: HashMap<String, Uint128> = HashMap::new();
+            for (addr, balance) in balances.iter() {
+                contract_balances_map.insert(addr.to_string(), **balance);
+            }
+
+            balances_map.insert(contract_addr.to_string(), contract_balances_map);
+        }
+
+        TokenQuerier {
+            balances: balances_map,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct TswapPairQuerier {
+    // maps the asset infos to the pair contract
+    pairs: HashMap<String, String>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {:?}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MockQueryMsg {
+    Balance { address: String },
+    Pair { asset_infos: [AssetInfo; 2] },
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    MockQueryMsg::Balance { address } => {
+                        let balance = self
+                            .token_querier
+                            .balances
+                            .get(contract_addr)
+                            .and_then(|balances| balances.get(&address))
+                            .cloned()
+                            .unwrap_or_default();
+
+                        SystemResult::Ok(ContractResult::from(to_binary(&BalanceResponse {
+                            balance,
+                        })))
+                    }
+                    MockQueryMsg::Pair { asset_infos } => {
+                        let key = asset_infos[0].to_string() + &asset_infos[1].to_string();
+                        match self.tswap_pair_querier.pairs.get(&key) {
+                            Some(pair) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
+                                    asset_infos,
+                                    contract_addr: pair.to_string(),
+                                    liquidity_token: "liquidity".to_string(),
+                                    asset_decimals: [6u8, 6u8],
+                                })))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No pair info exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            token_querier: TokenQuerier::default(),
+            tswap_pair_querier: TswapPairQuerier::default(),
+        }
+    }
+
+    // configure the cw20 token balances mock querier
+    pub fn with_token_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
+        self.token_querier = TokenQuerier::new(balances);
+    }
+
+    // configure the terraswap pair
+    pub fn with_tswap_pair(&mut self, asset_infos: &[AssetInfo; 2], pair: &str) {
+        self.tswap_pair_querier.pairs.insert(
+            asset_infos[0].to_string() + &asset_infos[1].to_string(),
+            pair.to_string(),
+        );
+    }
+}
@@ -0,0 +1,405 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Storage, Uint128, WasmMsg,
+};
+
+use crate::state::{
+    read_config, read_grant, read_grant_count, read_grants, read_spend_state, store_config,
+    store_grant, store_grant_count, store_spend_state, Config, Grant, SpendState,
+};
+
+use cw20::Cw20ExecuteMsg;
+use melange_protocol::community::{
+    ConfigResponse, ExecuteMsg, GrantResponse, GrantsResponse, InstantiateMsg, QueryMsg,
+    SpendStateResponse,
+};
+use terraswap::querier::query_token_balance;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    if msg.epoch_length == 0 {
+        return Err(StdError::generic_err(
+            "epoch_length must be greater than zero",
+        ));
+    }
+
+    store_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_canonicalize(&msg.owner)?,
+            melange_token: deps.api.addr_canonicalize(&msg.melange_token)?,
+            spend_limit: msg.spend_limit,
+            epoch_spend_limit: msg.epoch_spend_limit,
+            epoch_length: msg.epoch_length,
+        },
+    )?;
+
+    store_spend_state(
+        deps.storage,
+        &SpendState {
+            epoch_start: env.block.time.seconds(),
+            epoch_spent: Uint128::zero(),
+            total_granted: Uint128::zero(),
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::UpdateConfig {
+            owner,
+            spend_limit,
+            epoch_spend_limit,
+            epoch_length,
+        } => update_config(
+            deps,
+            info,
+            owner,
+            spend_limit,
+            epoch_spend_limit,
+            epoch_length,
+        ),
+        ExecuteMsg::Spend { recipient, amount } => spend(deps, env, info, recipient, amount),
+        ExecuteMsg::CreateGrant {
+            recipient,
+            amount,
+            start,
+            end,
+        } => create_grant(deps, env, info, recipient, amount, start, end),
+        ExecuteMsg::ClaimGrant { grant_id } => claim_grant(deps, env, info, grant_id),
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    spend_limit: Option<Uint128>,
+    epoch_spend_limit: Option<Uint128>,
+    epoch_length: Option<u64>,
+) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    if let Some(spend_limit) = spend_limit {
+        config.spend_limit = spend_limit;
+    }
+
+    if let Some(epoch_spend_limit) = epoch_spend_limit {
+        config.epoch_spend_limit = epoch_spend_limit;
+    }
+
+    if let Some(epoch_length) = epoch_length {
+        if epoch_length == 0 {
+            return Err(StdError::generic_err(
+                "epoch_length must be greater than zero",
+            ));
+        }
+
+        config.epoch_length = epoch_length;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+/// Spend
+/// Owner can execute spend operation to send
+/// `amount` of MLN token to `recipient` for community purpose
+pub fn spend(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if config.spend_limit < amount {
+        return Err(StdError::generic_err("Cannot spend more than spend_limit"));
+    }
+
+    let melange_token = deps.api.addr_humanize(&config.melange_token)?;
+    assert_unreserved_balance(deps.as_ref(), &env, amount)?;
+    consume_epoch_spend_limit(deps.storage, &config, env.block.time.seconds(), amount)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: melange_token.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.clone(),
+                amount,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "spend"),
+            attr("recipient", recipient),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
+/// CreateGrant
+/// Owner can reserve `amount` of MLN token for `recipient`,
+/// released linearly from `start` to `end`. The whole grant
+/// amount counts toward the spend limits on creation
+pub fn create_grant(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+    start: u64,
+    end: u64,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if amount.is_zero() {
+        return Err(StdError::generic_err(
+            "Grant amount must be greater than zero",
+        ));
+    }
+
+    if start >= end {
+        return Err(StdError::generic_err("Grant end must be after its start"));
+    }
+
+    if config.spend_limit < amount {
+        return Err(StdError::generic_err("Cannot spend more than spend_limit"));
+    }
+
+    assert_unreserved_balance(deps.as_ref(), &env, amount)?;
+    consume_epoch_spend_limit(deps.storage, &config, env.block.time.seconds(), amount)?;
+
+    let mut spend_state: SpendState = read_spend_state(deps.storage)?;
+    spend_state.total_granted += amount;
+    store_spend_state(deps.storage, &spend_state)?;
+
+    let grant_id = read_grant_count(deps.storage)? + 1;
+    store_grant_count(deps.storage, grant_id)?;
+    store_grant(
+        deps.storage,
+        &Grant {
+            id: grant_id,
+            recipient: deps.api.addr_canonicalize(&recipient)?,
+            amount,
+            claimed: Uint128::zero(),
+            start,
+            end,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "create_grant"),
+        attr("grant_id", grant_id.to_string()),
+        attr("recipient", recipient),
+        attr("amount", amount.to_string()),
+        attr("start", start.to_string()),
+        attr("end", end.to_string()),
+    ]))
+}
+
+/// ClaimGrant
+/// The grant recipient can claim the amount released so far
+pub fn claim_grant(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    grant_id: u64,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let mut grant: Grant = read_grant(deps.storage, grant_id)?;
+    if grant.recipient != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let amount = claimable_amount(&grant, env.block.time.seconds())?;
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to claim"));
+    }
+
+    grant.claimed += amount;
+    store_grant(deps.storage, &grant)?;
+
+    let mut spend_state: SpendState = read_spend_state(deps.storage)?;
+    spend_state.total_granted = spend_state.total_granted.checked_sub(amount)?;
+    store_spend_state(deps.storage, &spend_state)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.melange_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "claim_grant"),
+            attr("grant_id", grant_id.to_string()),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
+/// Returns the released amount of the grant which is not claimed yet
+fn claimable_amount(grant: &Grant, now: u64) -> StdResult<Uint128> {
+    let released = if now <= grant.start {
+        Uint128::zero()
+    } else if now >= grant.end {
+        grant.amount
+    } else {
+        grant
+            .amount
+            .multiply_ratio(now - grant.start, grant.end - grant.start)
+    };
+
+    Ok(released.checked_sub(grant.claimed)?)
+}
+
+/// The amount reserved for the grants cannot be spent
+fn assert_unreserved_balance(deps: Deps, env: &Env, amount: Uint128) -> StdResult<()> {
+    let config: Config = read_config(deps.storage)?;
+    let spend_state: SpendState = read_spend_state(deps.storage)?;
+    let balance = query_token_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.melange_token)?,
+        env.contract.address.clone(),
+    )?;
+
+    if balance.checked_sub(spend_state.total_granted)? < amount {
+        return Err(StdError::generic_err(
+            "Cannot spend the balance reserved for the grants",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Adds the amount to the spending of the current epoch. A new
+/// epoch starts every `epoch_length` seconds from the last one
+fn consume_epoch_spend_limit(
+    storage: &mut dyn Storage,
+    config: &Config,
+    now: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut spend_state: SpendState = read_spend_state(storage)?;
+    roll_epoch(&mut spend_state, config.epoch_length, now);
+
+    let epoch_spent = spend_state.epoch_spent + amount;
+    if epoch_spent > config.epoch_spend_limit {
+        return Err(StdError::generic_err(
+            "Cannot spend more than epoch_spend_limit",
+        ));
+    }
+
+    spend_state.epoch_spent = epoch_spent;
+    store_spend_state(storage, &spend_state)
+}
+
+/// Starts a new epoch when the current one is over
+fn roll_epoch(spend_state: &mut SpendState, epoch_length: u64, now: u64) {
+    if now >= spend_state.epoch_start + epoch_length {
+        spend_state.epoch_start = now - (now - spend_state.epoch_start) % epoch_length;
+        spend_state.epoch_spent = Uint128::zero();
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::SpendState {} => to_binary(&query_spend_state(deps, env)?),
+        QueryMsg::Grant { grant_id } => to_binary(&query_grant(deps, env, grant_id)?),
+        QueryMsg::Grants {
+            recipient,
+            start_after,
+            limit,
+        } => to_binary(&query_grants(deps, env, recipient, start_after, limit)?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = read_config(deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.addr_humanize(&state.owner)?.to_string(),
+        melange_token: deps.api.addr_humanize(&state.melange_token)?.to_string(),
+        spend_limit: state.spend_limit,
+        epoch_spend_limit: state.epoch_spend_limit,
+        epoch_length: state.epoch_length,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_spend_state(deps: Deps, env: Env) -> StdResult<SpendStateResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: SpendState = read_spend_state(deps.storage)?;
+
+    // report the epoch the next spend would be counted in
+    roll_epoch(&mut state, config.epoch_length, env.block.time.seconds());
+
+    Ok(SpendStateResponse {
+        epoch_start: state.epoch_start,
+        epoch_spent: state.epoch_spent,
+        total_granted: state.total_granted,
+    })
+}
+
+pub fn query_grant(deps: Deps, env: Env, grant_id: u64) -> StdResult<GrantResponse> {
+    let grant: Grant = read_grant(deps.storage, grant_id)?;
+    to_grant_response(deps, &env, grant)
+}
+
+pub fn query_grants(
+    deps: Deps,
+    env: Env,
+    recipient: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<GrantsResponse> {
+    let recipient = recipient
+        .map(|addr| deps.api.addr_canonicalize(&addr))
+        .transpose()?;
+
+    let grants = read_grants(deps.storage, recipient, start_after, limit)?
+        .into_iter()
+        .map(|grant| to_grant_response(deps, &env, grant))
+        .collect::<StdResult<Vec<GrantResponse>>>()?;
+
+    Ok(GrantsResponse { grants })
+}
+
+fn to_grant_response(deps: Deps, env: &Env, grant: Grant) -> StdResult<GrantResponse> {
+    Ok(GrantResponse {
+        grant_id: grant.id,
+        recipient: deps.api.addr_humanize(&grant.recipient)?.to_string(),
+        claimable: claimable_amount(&grant, env.block.time.seconds())?,
+        amount: grant.amount,
+        claimed: grant.claimed,
+        start: grant.start,
+        end: grant.end,
+    })
+}
@@ -0,0 +1,5 @@
+pub mod contract;
+mod state;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,110 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{CanonicalAddr, Order, StdError, StdResult, Storage, Uint128};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+
+static KEY_CONFIG: &[u8] = b"config";
+static KEY_SPEND_STATE: &[u8] = b"spend_state";
+static KEY_GRANT_COUNT: &[u8] = b"grant_count";
+
+static PREFIX_GRANT: &[u8] = b"grant";
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub melange_token: CanonicalAddr,
+    pub spend_limit: Uint128,
+    pub epoch_spend_limit: Uint128,
+    pub epoch_length: u64,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendState {
+    pub epoch_start: u64,
+    pub epoch_spent: Uint128,
+    pub total_granted: Uint128, // reserved for the unclaimed grants
+}
+
+pub fn store_spend_state(storage: &mut dyn Storage, spend_state: &SpendState) -> StdResult<()> {
+    singleton(storage, KEY_SPEND_STATE).save(spend_state)
+}
+
+pub fn read_spend_state(storage: &dyn Storage) -> StdResult<SpendState> {
+    singleton_read(storage, KEY_SPEND_STATE).load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Grant {
+    pub id: u64,
+    pub recipient: CanonicalAddr,
+    pub amount: Uint128,
+    pub claimed: Uint128,
+    pub start: u64,
+    pub end: u64,
+}
+
+pub fn store_grant_count(storage: &mut dyn Storage, grant_count: u64) -> StdResult<()> {
+    singleton(storage, KEY_GRANT_COUNT).save(&grant_count)
+}
+
+pub fn read_grant_count(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(singleton_read(storage, KEY_GRANT_COUNT)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_grant(storage: &mut dyn Storage, grant: &Grant) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_GRANT).save(&grant.id.to_be_bytes(), grant)
+}
+
+pub fn read_grant(storage: &dyn Storage, grant_id: u64) -> StdResult<Grant> {
+    match ReadonlyBucket::new(storage, PREFIX_GRANT).load(&grant_id.to_be_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err("Grant does not exist")),
+    }
+}
+
+pub fn read_grants(
+    storage: &dyn Storage,
+    recipient: Option<CanonicalAddr>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Grant>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    let grants: ReadonlyBucket<Grant> = ReadonlyBucket::new(storage, PREFIX_GRANT);
+    grants
+        .range(start.as_deref(), None, Order::Ascending)
+        .filter(|item| match (item, &recipient) {
+            (Ok((_, grant)), Some(recipient)) => grant.recipient == *recipient,
+            _ => true,
+        })
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<u64>) -> Option<Vec<u8>> {
+    start_after.map(|id| {
+        let mut v = id.to_be_bytes().to_vec();
+        v.push(1);
+        v
+    })
+}
@@ -0,0 +1,282 @@
+use crate::contract::{execute, instantiate, query};
+use crate::testing::mock_querier::mock_dependencies;
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{from_binary, to_binary, CosmosMsg, Env, StdError, SubMsg, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use melange_protocol::community::{
+    ConfigResponse, ExecuteMsg, GrantResponse, GrantsResponse, InstantiateMsg, QueryMsg,
+    SpendStateResponse,
+};
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: "owner0000".to_string(),
+        melange_token: "melange0000".to_string(),
+        spend_limit: Uint128::from(1000000u128),
+        epoch_spend_limit: Uint128::from(1500000u128),
+        epoch_length: 100u64,
+    }
+}
+
+fn env_at(seconds: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(seconds);
+    env
+}
+
+fn transfer_msg(recipient: &str, amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "melange0000".to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::from(amount),
+        })
+        .unwrap(),
+    }))
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            melange_token: "melange0000".to_string(),
+            spend_limit: Uint128::from(1000000u128),
+            epoch_spend_limit: Uint128::from(1500000u128),
+            epoch_length: 100u64,
+        }
+    );
+
+    // zero length epochs are rejected
+    let mut msg = instantiate_msg();
+    msg.epoch_length = 0u64;
+    let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("epoch_length must be greater than zero")
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        spend_limit: Some(Uint128::from(500000u128)),
+        epoch_spend_limit: None,
+        epoch_length: Some(200u64),
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0001".to_string(),
+            melange_token: "melange0000".to_string(),
+            spend_limit: Uint128::from(500000u128),
+            epoch_spend_limit: Uint128::from(1500000u128),
+            epoch_length: 200u64,
+        }
+    );
+}
+
+#[test]
+fn test_spend() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"melange0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(10000000u128),
+        )],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::Spend {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    // per request limit
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::Spend {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(2000000u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Cannot spend more than spend_limit")
+    );
+
+    let msg = ExecuteMsg::Spend {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![transfer_msg("addr0000", 1000000u128)]);
+
+    // the epoch cap is reached
+    let res = execute(deps.as_mut(), env_at(99), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Cannot spend more than epoch_spend_limit")
+    );
+
+    // a new epoch starts
+    let _res = execute(deps.as_mut(), env_at(150), info, msg).unwrap();
+    let spend_state: SpendStateResponse =
+        from_binary(&query(deps.as_ref(), env_at(150), QueryMsg::SpendState {}).unwrap()).unwrap();
+    assert_eq!(
+        spend_state,
+        SpendStateResponse {
+            epoch_start: mock_env().block.time.seconds() + 100,
+            epoch_spent: Uint128::from(1000000u128),
+            total_granted: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn test_grant() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"melange0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1500000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let start = mock_env().block.time.seconds() + 100;
+    let end = start + 1000;
+
+    // invalid grant period
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::CreateGrant {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        start: end,
+        end: start,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Grant end must be after its start")
+    );
+
+    let msg = ExecuteMsg::CreateGrant {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        start,
+        end,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    // the granted amount is reserved
+    let msg = ExecuteMsg::Spend {
+        recipient: "addr0001".to_string(),
+        amount: Uint128::from(500001u128),
+    };
+    let res = execute(deps.as_mut(), env_at(200), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Cannot spend the balance reserved for the grants")
+    );
+
+    // nothing released before the start
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::ClaimGrant { grant_id: 1u64 };
+    let res = execute(deps.as_mut(), env_at(100), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("Nothing to claim"));
+
+    // only the recipient can claim
+    let res = execute(
+        deps.as_mut(),
+        env_at(600),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let res = execute(deps.as_mut(), env_at(600), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![transfer_msg("addr0000", 500000u128)]);
+
+    let grant: GrantResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env_at(850),
+            QueryMsg::Grant { grant_id: 1u64 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        grant,
+        GrantResponse {
+            grant_id: 1u64,
+            recipient: "addr0000".to_string(),
+            amount: Uint128::from(1000000u128),
+            claimed: Uint128::from(500000u128),
+            claimable: Uint128::from(250000u128),
+            start,
+            end,
+        }
+    );
+
+    // the rest is released after the end
+    let res = execute(deps.as_mut(), env_at(2000), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![transfer_msg("addr0000", 500000u128)]);
+
+    let res = execute(deps.as_mut(), env_at(2000), info, msg).unwrap_err();
+    assert_eq!(res, StdError::generic_err("Nothing to claim"));
+
+    let spend_state: SpendStateResponse =
+        from_binary(&query(deps.as_ref(), env_at(2000), QueryMsg::SpendState {}).unwrap()).unwrap();
+    assert_eq!(spend_state.total_granted, Uint128::zero());
+
+    let grants: GrantsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env_at(2000),
+            QueryMsg::Grants {
+                recipient: Some("addr0001".to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(grants.grants, vec![]);
+}
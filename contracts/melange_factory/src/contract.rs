@@ -0,0 +1,755 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, to_binary, Binary, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Reply, Response, StdError, StdResult, Storage, SubMsg, SubMsgResult, Uint128, WasmMsg,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::querier::{query_asset_config, query_last_price};
+use crate::state::{
+    decrease_total_weight, increase_total_weight, read_all_weight, read_config,
+    read_last_distributed, read_tmp_asset, read_tmp_whitelist_info, read_total_weight, read_weight,
+    remove_tmp_asset, remove_tmp_whitelist_info, remove_weight, store_config,
+    store_last_distributed, store_tmp_asset, store_tmp_whitelist_info, store_total_weight,
+    store_weight, Config, WhitelistTmpInfo,
+};
+
+use cw20::{Cw20Coin, Cw20ExecuteMsg, MinterResponse};
+use melange_protocol::collateral_oracle::{
+    ExecuteMsg as CollateralOracleExecuteMsg, SourceType,
+};
+use melange_protocol::factory::{
+    ConfigResponse, DistributionInfoResponse, ExecuteMsg, InstantiateMsg, Params, QueryMsg,
+};
+use melange_protocol::mint::ExecuteMsg as MintExecuteMsg;
+use melange_protocol::oracle::ExecuteMsg as OracleExecuteMsg;
+use melange_protocol::staking::{
+    Cw20HookMsg as StakingCw20HookMsg, ExecuteMsg as StakingExecuteMsg,
+};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::factory::ExecuteMsg as TswapFactoryExecuteMsg;
+use terraswap::querier::query_pair_info;
+
+const MELANGE_TOKEN_WEIGHT: u32 = 300u32;
+const DEFAULT_WEIGHT: u32 = 30u32;
+const DISTRIBUTION_INTERVAL: u64 = 60u64;
+
+const TOKEN_CREATION_REPLY_ID: u64 = 1;
+const TSWAP_CREATION_REPLY_ID: u64 = 2;
+
+/// Instantiate message of the cw20 token contract used for the mAssets
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenInstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_balances: Vec<Cw20Coin>,
+    pub mint: Option<MinterResponse>,
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    assert_distribution_schedule(&msg.distribution_schedule)?;
+    store_config(
+        deps.storage,
+        &Config {
+            owner: CanonicalAddr::from(vec![]),
+            melange_token: CanonicalAddr::from(vec![]),
+            mint_contract: CanonicalAddr::from(vec![]),
+            oracle_contract: CanonicalAddr::from(vec![]),
+            collateral_oracle: CanonicalAddr::from(vec![]),
+            tswap_factory: CanonicalAddr::from(vec![]),
+            staking_contract: CanonicalAddr::from(vec![]),
+            commission_collector: CanonicalAddr::from(vec![]),
+            token_code_id: msg.token_code_id,
+            base_denom: msg.base_denom,
+            genesis_time: env.block.time.seconds(),
+            distribution_schedule: msg.distribution_schedule,
+        },
+    )?;
+
+    store_total_weight(deps.storage, 0u32)?;
+    store_last_distributed(deps.storage, env.block.time.seconds())?;
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::PostInitialize {
+            owner,
+            tswap_factory,
+            melange_token,
+            staking_contract,
+            oracle_contract,
+            mint_contract,
+            commission_collector,
+            collateral_oracle,
+        } => post_initialize(
+            deps,
+            env,
+            owner,
+            tswap_factory,
+            melange_token,
+            staking_contract,
+            oracle_contract,
+            mint_contract,
+            commission_collector,
+            collateral_oracle,
+        ),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            token_code_id,
+            distribution_schedule,
+        } => update_config(deps, info, owner, token_code_id, distribution_schedule),
+        ExecuteMsg::UpdateWeight {
+            asset_token,
+            weight,
+        } => update_weight(deps, env, info, asset_token, weight),
+        ExecuteMsg::Whitelist {
+            name,
+            symbol,
+            oracle_proxy,
+            params,
+        } => whitelist(deps, info, name, symbol, oracle_proxy, params),
+        ExecuteMsg::PassCommand { contract_addr, msg } => {
+            pass_command(deps, info, contract_addr, msg)
+        }
+        ExecuteMsg::RevokeAsset { asset_token } => revoke_asset(deps, env, info, asset_token),
+        ExecuteMsg::MigrateAsset {
+            name,
+            symbol,
+            oracle_proxy,
+            from_token,
+        } => migrate_asset(deps, env, info, name, symbol, oracle_proxy, from_token),
+        ExecuteMsg::Distribute {} => distribute(deps, env),
+    }
+}
+
+/// PostInitialize
+/// Set the contract addresses and register the melange token
+/// to the distribution pool. Can only be executed once
+#[allow(clippy::too_many_arguments)]
+pub fn post_initialize(
+    deps: DepsMut,
+    env: Env,
+    owner: String,
+    tswap_factory: String,
+    melange_token: String,
+    staking_contract: String,
+    oracle_contract: String,
+    mint_contract: String,
+    commission_collector: String,
+    collateral_oracle: String,
+) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    if config.owner != CanonicalAddr::from(vec![]) {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    config.owner = deps.api.addr_canonicalize(&owner)?;
+    config.tswap_factory = deps.api.addr_canonicalize(&tswap_factory)?;
+    config.melange_token = deps.api.addr_canonicalize(&melange_token)?;
+    config.staking_contract = deps.api.addr_canonicalize(&staking_contract)?;
+    config.oracle_contract = deps.api.addr_canonicalize(&oracle_contract)?;
+    config.mint_contract = deps.api.addr_canonicalize(&mint_contract)?;
+    config.commission_collector = deps.api.addr_canonicalize(&commission_collector)?;
+    config.collateral_oracle = deps.api.addr_canonicalize(&collateral_oracle)?;
+    store_config(deps.storage, &config)?;
+
+    // the melange token pool receives rewards from the genesis
+    store_weight(deps.storage, &config.melange_token, MELANGE_TOKEN_WEIGHT)?;
+    increase_total_weight(deps.storage, MELANGE_TOKEN_WEIGHT)?;
+    store_last_distributed(deps.storage, env.block.time.seconds())?;
+
+    Ok(Response::new().add_attribute("action", "post_initialize"))
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    token_code_id: Option<u64>,
+    distribution_schedule: Option<Vec<(u64, u64, Uint128)>>,
+) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    if let Some(distribution_schedule) = distribution_schedule {
+        assert_distribution_schedule(&distribution_schedule)?;
+        config.distribution_schedule = distribution_schedule;
+    }
+
+    if let Some(token_code_id) = token_code_id {
+        config.token_code_id = token_code_id;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+/// Every distribution window must end after it starts
+fn assert_distribution_schedule(distribution_schedule: &[(u64, u64, Uint128)]) -> StdResult<()> {
+    if distribution_schedule.iter().any(|s| s.1 <= s.0) {
+        return Err(StdError::generic_err(
+            "Distribution schedule windows must end after they start",
+        ));
+    }
+
+    Ok(())
+}
+
+/// UpdateWeight
+/// The rewards accrued so far are distributed with the previous weights
+/// before the new weight applies
+pub fn update_weight(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_token: String,
+    weight: u32,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    // only assets of the distribution pool, registered to the staking contract
+    let asset_token_raw = deps.api.addr_canonicalize(&asset_token)?;
+    let origin_weight = read_weight(deps.storage, &asset_token_raw)?;
+
+    let (messages, distribution_amount) = distribution_messages(deps.branch(), env, &config)?;
+    store_weight(deps.storage, &asset_token_raw, weight)?;
+
+    let origin_total_weight = read_total_weight(deps.storage)?;
+    store_total_weight(deps.storage, origin_total_weight + weight - origin_weight)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "update_weight"),
+        attr("asset_token", asset_token),
+        attr("weight", weight.to_string()),
+        attr("distribution_amount", distribution_amount.to_string()),
+    ]))
+}
+
+/// Whitelist
+/// Owner can whitelist a new asset. The asset token is instantiated first,
+/// the remaining registrations are done in the reply hooks
+pub fn whitelist(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    symbol: String,
+    oracle_proxy: String,
+    params: Params,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if read_tmp_whitelist_info(deps.storage).is_ok() {
+        return Err(StdError::generic_err("A whitelist process is in progress"));
+    }
+
+    store_tmp_whitelist_info(
+        deps.storage,
+        &WhitelistTmpInfo {
+            params,
+            oracle_proxy: deps.api.addr_canonicalize(&oracle_proxy)?,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(token_creation_msg(
+            deps.as_ref(),
+            &config,
+            name,
+            symbol.clone(),
+        )?)
+        .add_attributes(vec![attr("action", "whitelist"), attr("symbol", symbol)]))
+}
+
+fn token_creation_msg(
+    deps: Deps,
+    config: &Config,
+    name: String,
+    symbol: String,
+) -> StdResult<SubMsg> {
+    Ok(SubMsg::reply_on_success(
+        WasmMsg::Instantiate {
+            admin: None,
+            code_id: config.token_code_id,
+            funds: vec![],
+            label: symbol.clone(),
+            msg: to_binary(&TokenInstantiateMsg {
+                name,
+                symbol,
+                decimals: 6u8,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: deps.api.addr_humanize(&config.mint_contract)?.to_string(),
+                    cap: None,
+                }),
+            })?,
+        },
+        TOKEN_CREATION_REPLY_ID,
+    ))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
+    match msg.id {
+        TOKEN_CREATION_REPLY_ID => {
+            let asset_token = parse_instantiated_contract(msg.result)?;
+            token_creation_hook(deps, env, asset_token)
+        }
+        TSWAP_CREATION_REPLY_ID => tswap_creation_hook(deps),
+        _ => Err(StdError::generic_err("reply id is invalid")),
+    }
+}
+
+fn parse_instantiated_contract(result: SubMsgResult) -> StdResult<String> {
+    let res = result.into_result().map_err(StdError::generic_err)?;
+    res.events
+        .iter()
+        .find(|event| event.ty == "instantiate")
+        .and_then(|event| {
+            event
+                .attributes
+                .iter()
+                .find(|attr| attr.key == "_contract_address")
+        })
+        .map(|attr| attr.value.clone())
+        .ok_or_else(|| StdError::generic_err("Failed to parse the instantiated contract address"))
+}
+
+/// TokenCreationHook
+/// Registers the new asset token to the mint, oracle and collateral oracle
+/// contracts, adds it to the distribution pool and creates its terraswap pair.
+/// The rewards accrued before the asset joins the pool are distributed first
+pub fn token_creation_hook(
+    mut deps: DepsMut,
+    env: Env,
+    asset_token: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let tmp_info: WhitelistTmpInfo = read_tmp_whitelist_info(deps.storage)?;
+    remove_tmp_whitelist_info(deps.storage);
+
+    let (messages, distribution_amount) = distribution_messages(deps.branch(), env, &config)?;
+    let asset_token_raw = deps.api.addr_canonicalize(&asset_token)?;
+    let weight = tmp_info.params.weight.unwrap_or(DEFAULT_WEIGHT);
+    store_weight(deps.storage, &asset_token_raw, weight)?;
+    increase_total_weight(deps.storage, weight)?;
+
+    // the pair creation hook registers the lp token to the staking contract
+    store_tmp_asset(deps.storage, &asset_token_raw)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.mint_contract)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&MintExecuteMsg::RegisterAsset {
+                    asset_token: asset_token.clone(),
+                    auction_discount: tmp_info.params.auction_discount,
+                    min_collateral_ratio: tmp_info.params.min_collateral_ratio,
+                    ipo_params: None,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.oracle_contract)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&OracleExecuteMsg::RegisterProxy {
+                    asset_token: asset_token.clone(),
+                    proxy_addr: deps.api.addr_humanize(&tmp_info.oracle_proxy)?.to_string(),
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.collateral_oracle)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&CollateralOracleExecuteMsg::RegisterCollateralAsset {
+                    asset: AssetInfo::Token {
+                        contract_addr: asset_token.clone(),
+                    },
+                    price_source: SourceType::MelangeOracle {
+                        oracle_addr: deps.api.addr_humanize(&config.oracle_contract)?.to_string(),
+                    },
+                    multiplier: tmp_info
+                        .params
+                        .collateral_multiplier
+                        .unwrap_or_else(Decimal::one),
+                })?,
+            }),
+        ])
+        .add_submessage(SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.tswap_factory)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&TswapFactoryExecuteMsg::CreatePair {
+                    assets: [
+                        Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: config.base_denom,
+                            },
+                            amount: Uint128::zero(),
+                        },
+                        Asset {
+                            info: AssetInfo::Token {
+                                contract_addr: asset_token.clone(),
+                            },
+                            amount: Uint128::zero(),
+                        },
+                    ],
+                })?,
+            },
+            TSWAP_CREATION_REPLY_ID,
+        ))
+        .add_attributes(vec![
+            attr("action", "token_creation_hook"),
+            attr("asset_token", asset_token),
+            attr("weight", weight.to_string()),
+            attr("distribution_amount", distribution_amount.to_string()),
+        ]))
+}
+
+/// TswapCreationHook
+/// Registers the liquidity token of the new pair to the staking contract
+pub fn tswap_creation_hook(deps: DepsMut) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let asset_token = deps.api.addr_humanize(&read_tmp_asset(deps.storage)?)?;
+    remove_tmp_asset(deps.storage);
+
+    let pair_info = query_pair_info(
+        &deps.querier,
+        deps.api.addr_humanize(&config.tswap_factory)?,
+        &[
+            AssetInfo::NativeToken {
+                denom: config.base_denom,
+            },
+            AssetInfo::Token {
+                contract_addr: asset_token.to_string(),
+            },
+        ],
+    )?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.staking_contract)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&StakingExecuteMsg::RegisterAsset {
+                asset_token: asset_token.to_string(),
+                staking_token: pair_info.liquidity_token.clone(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "tswap_creation_hook"),
+            attr("asset_token", asset_token.to_string()),
+            attr("liquidity_token", pair_info.liquidity_token),
+        ]))
+}
+
+pub fn pass_command(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract_addr: String,
+    msg: Binary,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    Ok(
+        Response::new().add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg,
+            funds: vec![],
+        })),
+    )
+}
+
+/// Distribute
+/// Anyone can execute distribute operation to distribute
+/// the melange token inflation rewards on the staking pools
+pub fn distribute(deps: DepsMut, env: Env) -> StdResult<Response> {
+    let last_distributed = read_last_distributed(deps.storage)?;
+    if last_distributed + DISTRIBUTION_INTERVAL > env.block.time.seconds() {
+        return Err(StdError::generic_err(
+            "Cannot distribute melange token before interval",
+        ));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let (messages, distribution_amount) = distribution_messages(deps, env, &config)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "distribute"),
+        attr("distribution_amount", distribution_amount.to_string()),
+    ]))
+}
+
+/// Releases the scheduled tokens since the last distribution, split across
+/// the assets by their current weight, and records the distribution time
+fn distribution_messages(
+    deps: DepsMut,
+    env: Env,
+    config: &Config,
+) -> StdResult<(Vec<CosmosMsg>, Uint128)> {
+    let last_distributed = read_last_distributed(deps.storage)?;
+    let time_elapsed = env.block.time.seconds() - config.genesis_time;
+    let last_time_elapsed = last_distributed - config.genesis_time;
+
+    let mut target_distribution_amount: Uint128 = Uint128::zero();
+    for s in config.distribution_schedule.iter() {
+        if s.0 > time_elapsed || s.1 < last_time_elapsed {
+            continue;
+        }
+
+        // min(s.1, time_elapsed) - max(s.0, last_time_elapsed)
+        let time_duration =
+            std::cmp::min(s.1, time_elapsed) - std::cmp::max(s.0, last_time_elapsed);
+
+        let time_slot = s.1 - s.0;
+        let distribution_amount_per_sec: Decimal = Decimal::from_ratio(s.2, time_slot);
+        target_distribution_amount += distribution_amount_per_sec * Uint128::from(time_duration);
+    }
+
+    let staking_contract = deps.api.addr_humanize(&config.staking_contract)?;
+    let melange_token = deps.api.addr_humanize(&config.melange_token)?;
+
+    let total_weight: u32 = read_total_weight(deps.storage)?;
+    let mut distribution_amount: Uint128 = Uint128::zero();
+    let weights: Vec<(CanonicalAddr, u32)> = read_all_weight(deps.storage)?;
+    let mut rewards: Vec<(String, Uint128)> = vec![];
+
+    // without any weight, the released tokens stay in the factory
+    let weights = if total_weight == 0 { vec![] } else { weights };
+    for (asset_token, weight) in weights.iter() {
+        let amount = target_distribution_amount * Decimal::from_ratio(*weight, total_weight);
+        if amount.is_zero() {
+            continue;
+        }
+
+        distribution_amount += amount;
+        rewards.push((deps.api.addr_humanize(asset_token)?.to_string(), amount));
+    }
+
+    // store last distributed
+    store_last_distributed(deps.storage, env.block.time.seconds())?;
+
+    // send token rewards to the staking contract, 10 pools per message
+    const SPLIT_UNIT: usize = 10;
+    let messages: Vec<CosmosMsg> = rewards
+        .chunks(SPLIT_UNIT)
+        .map(|chunk| {
+            let amount = chunk.iter().map(|(_, amount)| *amount).sum();
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: melange_token.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: staking_contract.to_string(),
+                    amount,
+                    msg: to_binary(&StakingCw20HookMsg::DepositReward {
+                        rewards: chunk.to_vec(),
+                    })?,
+                })?,
+                funds: vec![],
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    Ok((messages, distribution_amount))
+}
+
+/// RevokeAsset
+/// Owner can revoke an asset from the distribution pool once the rewards
+/// accrued so far are distributed.
+/// The mint contract freezes the asset at the last oracle price
+pub fn revoke_asset(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_token: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let end_price = query_last_price(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        asset_token.clone(),
+    )?;
+
+    let (messages, distribution_amount) = distribution_messages(deps.branch(), env, &config)?;
+    remove_distribution_info(deps.storage, &deps.api.addr_canonicalize(&asset_token)?)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.mint_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&MintExecuteMsg::RegisterMigration {
+                asset_token: asset_token.clone(),
+                end_price,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "revoke_asset"),
+            attr("asset_token", asset_token),
+            attr("end_price", end_price.to_string()),
+            attr("distribution_amount", distribution_amount.to_string()),
+        ]))
+}
+
+/// MigrateAsset
+/// Owner can migrate an asset to a new asset. The previous asset
+/// is frozen at the last oracle price and leaves the distribution pool,
+/// the new asset is whitelisted with the same parameters and weight.
+/// The rewards accrued so far are distributed first
+pub fn migrate_asset(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    symbol: String,
+    oracle_proxy: String,
+    from_token: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if read_tmp_whitelist_info(deps.storage).is_ok() {
+        return Err(StdError::generic_err("A whitelist process is in progress"));
+    }
+
+    let asset_token_raw = deps.api.addr_canonicalize(&from_token)?;
+
+    // only assets receiving rewards can be migrated
+    let weight = read_weight(deps.storage, &asset_token_raw)?;
+
+    let end_price = query_last_price(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        from_token.clone(),
+    )?;
+    let asset_config = query_asset_config(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.mint_contract)?,
+        from_token.clone(),
+    )?;
+
+    let (messages, distribution_amount) = distribution_messages(deps.branch(), env, &config)?;
+    remove_distribution_info(deps.storage, &asset_token_raw)?;
+
+    store_tmp_whitelist_info(
+        deps.storage,
+        &WhitelistTmpInfo {
+            params: Params {
+                auction_discount: asset_config.auction_discount,
+                min_collateral_ratio: asset_config.min_collateral_ratio,
+                weight: Some(weight),
+                collateral_multiplier: None,
+            },
+            oracle_proxy: deps.api.addr_canonicalize(&oracle_proxy)?,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.mint_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&MintExecuteMsg::RegisterMigration {
+                asset_token: from_token.clone(),
+                end_price,
+            })?,
+        }))
+        .add_submessage(token_creation_msg(deps.as_ref(), &config, name, symbol)?)
+        .add_attributes(vec![
+            attr("action", "migration"),
+            attr("asset_token", from_token),
+            attr("end_price", end_price.to_string()),
+            attr("distribution_amount", distribution_amount.to_string()),
+        ]))
+}
+
+/// Removes the asset from the distribution pool if it was receiving rewards
+fn remove_distribution_info(
+    storage: &mut dyn Storage,
+    asset_token: &CanonicalAddr,
+) -> StdResult<()> {
+    if let Ok(weight) = read_weight(storage, asset_token) {
+        remove_weight(storage, asset_token);
+        decrease_total_weight(storage, weight)?;
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::DistributionInfo {} => to_binary(&query_distribution_info(deps)?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = read_config(deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.addr_humanize(&state.owner)?.to_string(),
+        melange_token: deps.api.addr_humanize(&state.melange_token)?.to_string(),
+        mint_contract: deps.api.addr_humanize(&state.mint_contract)?.to_string(),
+        oracle_contract: deps.api.addr_humanize(&state.oracle_contract)?.to_string(),
+        collateral_oracle: deps.api.addr_humanize(&state.collateral_oracle)?.to_string(),
+        tswap_factory: deps.api.addr_humanize(&state.tswap_factory)?.to_string(),
+        staking_contract: deps.api.addr_humanize(&state.staking_contract)?.to_string(),
+        commission_collector: deps
+            .api
+            .addr_humanize(&state.commission_collector)?
+            .to_string(),
+        token_code_id: state.token_code_id,
+        base_denom: state.base_denom,
+        genesis_time: state.genesis_time,
+        distribution_schedule: state.distribution_schedule,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_distribution_info(deps: Deps) -> StdResult<DistributionInfoResponse> {
+    let weights: Vec<(CanonicalAddr, u32)> = read_all_weight(deps.storage)?;
+    let last_distributed = read_last_distributed(deps.storage)?;
+    let resp = DistributionInfoResponse {
+        last_distributed,
+        weights: weights
+            .iter()
+            .map(|w| Ok((deps.api.addr_humanize(&w.0)?.to_string(), w.1)))
+            .collect::<StdResult<Vec<(String, u32)>>>()?,
+    };
+
+    Ok(resp)
+}
@@ -0,0 +1,6 @@
+pub mod contract;
+mod querier;
+mod state;
+
+#[cfg(test)]
+mod testing;
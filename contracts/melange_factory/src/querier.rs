@@ -0,0 +1,28 @@
+use cosmwasm_std::{to_binary, Addr, Decimal, Deps, QueryRequest, StdResult, WasmQuery};
+use melange_protocol::mint::{AssetConfigResponse, QueryMsg as MintQueryMsg};
+use melange_protocol::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
+
+/// Queries the last price of the asset, regardless of its age
+pub fn query_last_price(deps: Deps, oracle: Addr, asset_token: String) -> StdResult<Decimal> {
+    let res: PriceResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: oracle.to_string(),
+        msg: to_binary(&OracleQueryMsg::Price {
+            asset_token,
+            timeframe: None,
+        })?,
+    }))?;
+
+    Ok(res.rate)
+}
+
+/// Queries the mint parameters of the asset
+pub fn query_asset_config(
+    deps: Deps,
+    mint: Addr,
+    asset_token: String,
+) -> StdResult<AssetConfigResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: mint.to_string(),
+        msg: to_binary(&MintQueryMsg::AssetConfig { asset_token })?,
+    }))
+}
@@ -0,0 +1,807 @@
+use crate::contract::{execute, instantiate, query, reply, TokenInstantiateMsg};
+use crate::testing::mock_querier::mock_dependencies;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, CosmosMsg, Decimal, DepsMut, Env, Event, Reply, StdError,
+    SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, MinterResponse};
+use melange_protocol::collateral_oracle::{
+    ExecuteMsg as CollateralOracleExecuteMsg, SourceType,
+};
+use melange_protocol::factory::{
+    ConfigResponse, DistributionInfoResponse, ExecuteMsg, InstantiateMsg, Params, QueryMsg,
+};
+use melange_protocol::mint::ExecuteMsg as MintExecuteMsg;
+use melange_protocol::oracle::ExecuteMsg as OracleExecuteMsg;
+use melange_protocol::staking::{
+    Cw20HookMsg as StakingCw20HookMsg, ExecuteMsg as StakingExecuteMsg,
+};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::factory::ExecuteMsg as TswapFactoryExecuteMsg;
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        token_code_id: 10u64,
+        base_denom: "uusd".to_string(),
+        distribution_schedule: vec![
+            (0, 100, Uint128::from(1000000u128)),
+            (100, 200, Uint128::from(10000000u128)),
+        ],
+    }
+}
+
+fn post_initialize_msg() -> ExecuteMsg {
+    ExecuteMsg::PostInitialize {
+        owner: "owner0000".to_string(),
+        tswap_factory: "tswapfactory".to_string(),
+        melange_token: "melange0000".to_string(),
+        staking_contract: "staking0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        mint_contract: "mint0000".to_string(),
+        commission_collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+    }
+}
+
+fn token_instantiate_reply(asset_token: &str) -> Reply {
+    Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![Event::new("instantiate")
+                .add_attribute("_contract_address", asset_token)
+                .add_attribute("code_id", "10")],
+            data: None,
+        }),
+    }
+}
+
+fn token_instantiate_msg(name: &str, symbol: &str) -> SubMsg {
+    SubMsg::reply_on_success(
+        WasmMsg::Instantiate {
+            admin: None,
+            code_id: 10u64,
+            funds: vec![],
+            label: symbol.to_string(),
+            msg: to_binary(&TokenInstantiateMsg {
+                name: name.to_string(),
+                symbol: symbol.to_string(),
+                decimals: 6u8,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: "mint0000".to_string(),
+                    cap: None,
+                }),
+            })
+            .unwrap(),
+        },
+        1,
+    )
+}
+
+/// whitelists the asset to the distribution pool with the given weight
+fn whitelist_asset(mut deps: DepsMut, env: Env, asset_token: &str, weight: u32) {
+    let msg = ExecuteMsg::Whitelist {
+        name: "apple derivative".to_string(),
+        symbol: "mAPPL".to_string(),
+        oracle_proxy: "proxy0000".to_string(),
+        params: Params {
+            auction_discount: Decimal::percent(5),
+            min_collateral_ratio: Decimal::percent(150),
+            weight: Some(weight),
+            collateral_multiplier: None,
+        },
+    };
+    let _res = execute(deps.branch(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let _res = reply(deps, env, token_instantiate_reply(asset_token)).unwrap();
+}
+
+/// reward message of the distribution to the staking contract
+fn distribution_msg(rewards: Vec<(&str, u128)>) -> SubMsg {
+    let amount: u128 = rewards.iter().map(|(_, amount)| amount).sum();
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "melange0000".to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: "staking0000".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&StakingCw20HookMsg::DepositReward {
+                rewards: rewards
+                    .into_iter()
+                    .map(|(asset_token, amount)| (asset_token.to_string(), Uint128::from(amount)))
+                    .collect(),
+            })
+            .unwrap(),
+        })
+        .unwrap(),
+        funds: vec![],
+    }))
+}
+
+fn query_distribution_info(deps: cosmwasm_std::Deps) -> DistributionInfoResponse {
+    from_binary(&query(deps, mock_env(), QueryMsg::DistributionInfo {}).unwrap()).unwrap()
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg()).unwrap();
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        post_initialize_msg(),
+    )
+    .unwrap();
+
+    // it worked, let's query the state
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            melange_token: "melange0000".to_string(),
+            mint_contract: "mint0000".to_string(),
+            staking_contract: "staking0000".to_string(),
+            commission_collector: "collector0000".to_string(),
+            oracle_contract: "oracle0000".to_string(),
+            collateral_oracle: "collateraloracle0000".to_string(),
+            tswap_factory: "tswapfactory".to_string(),
+            token_code_id: 10u64,
+            base_denom: "uusd".to_string(),
+            genesis_time: env.block.time.seconds(),
+            distribution_schedule: vec![
+                (0, 100, Uint128::from(1000000u128)),
+                (100, 200, Uint128::from(10000000u128)),
+            ],
+        }
+    );
+
+    let msg = InstantiateMsg {
+        distribution_schedule: vec![(100, 0, Uint128::from(1000000u128))],
+        ..instantiate_msg()
+    };
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Distribution schedule windows must end after they start")
+    );
+
+    // the melange token receives rewards from the genesis
+    assert_eq!(
+        query_distribution_info(deps.as_ref()),
+        DistributionInfoResponse {
+            weights: vec![("melange0000".to_string(), 300u32)],
+            last_distributed: env.block.time.seconds(),
+        }
+    );
+
+    // cannot initialize twice
+    let res = execute(deps.as_mut(), env, info, post_initialize_msg()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, post_initialize_msg()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        token_code_id: Some(100u64),
+        distribution_schedule: Some(vec![(1, 2, Uint128::from(123u128))]),
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    // empty or inverted windows are refused
+    let info = mock_info("owner0000", &[]);
+    for window in [(2, 2), (2, 1)] {
+        let invalid_msg = ExecuteMsg::UpdateConfig {
+            owner: None,
+            token_code_id: None,
+            distribution_schedule: Some(vec![(window.0, window.1, Uint128::from(123u128))]),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), invalid_msg).unwrap_err();
+        assert_eq!(
+            res,
+            StdError::generic_err("Distribution schedule windows must end after they start")
+        );
+    }
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, "owner0001".to_string());
+    assert_eq!(config.token_code_id, 100u64);
+    assert_eq!(
+        config.distribution_schedule,
+        vec![(1, 2, Uint128::from(123u128))]
+    );
+}
+
+#[test]
+fn test_update_weight() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, post_initialize_msg()).unwrap();
+    whitelist_asset(deps.as_mut(), mock_env(), "asset0000", 10u32);
+
+    let msg = ExecuteMsg::UpdateWeight {
+        asset_token: "asset0000".to_string(),
+        weight: 30u32,
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    // only assets of the distribution pool are registered to the staking contract
+    let info = mock_info("owner0000", &[]);
+    let unknown_msg = ExecuteMsg::UpdateWeight {
+        asset_token: "asset0001".to_string(),
+        weight: 30u32,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), unknown_msg).unwrap_err();
+    assert_eq!(res, StdError::generic_err("No distribution info stored"));
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_weight"),
+            attr("asset_token", "asset0000"),
+            attr("weight", "30"),
+            attr("distribution_amount", "0"),
+        ]
+    );
+
+    // the first window is released with the previous weights
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let msg = ExecuteMsg::UpdateWeight {
+        asset_token: "melange0000".to_string(),
+        weight: 100u32,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_weight"),
+            attr("asset_token", "melange0000"),
+            attr("weight", "100"),
+            attr("distribution_amount", "999999"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![distribution_msg(vec![
+            ("asset0000", 90909u128),
+            ("melange0000", 909090u128),
+        ])]
+    );
+    assert_eq!(
+        query_distribution_info(deps.as_ref()).last_distributed,
+        env.block.time.seconds()
+    );
+
+    let mut weights = query_distribution_info(deps.as_ref()).weights;
+    weights.sort();
+    assert_eq!(
+        weights,
+        vec![
+            ("asset0000".to_string(), 30u32),
+            ("melange0000".to_string(), 100u32),
+        ]
+    );
+
+    // without any weight, nothing is distributed
+    for asset_token in ["asset0000", "melange0000"] {
+        let msg = ExecuteMsg::UpdateWeight {
+            asset_token: asset_token.to_string(),
+            weight: 0u32,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    env.block.time = env.block.time.plus_seconds(60);
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::Distribute {}).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute"),
+            attr("distribution_amount", "0"),
+        ]
+    );
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn test_distribute() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let mut env = mock_env();
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg()).unwrap();
+    let _res = execute(deps.as_mut(), env.clone(), info, post_initialize_msg()).unwrap();
+    whitelist_asset(deps.as_mut(), env.clone(), "asset0000", 100u32);
+    let info = mock_info("owner0000", &[]);
+
+    // cannot distribute before the interval
+    env.block.time = env.block.time.plus_seconds(30);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Cannot distribute melange token before interval")
+    );
+
+    // the first window is fully released after 100 seconds
+    env.block.time = env.block.time.plus_seconds(70);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute"),
+            attr("distribution_amount", "1000000"),
+        ]
+    );
+
+    let rewards = vec![
+        ("asset0000".to_string(), Uint128::from(250000u128)),
+        ("melange0000".to_string(), Uint128::from(750000u128)),
+    ];
+    let distribution_info = query_distribution_info(deps.as_ref());
+    assert_eq!(distribution_info.last_distributed, env.block.time.seconds());
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "melange0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "staking0000".to_string(),
+                amount: Uint128::from(1000000u128),
+                msg: to_binary(&StakingCw20HookMsg::DepositReward { rewards }).unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // 60% of the second window
+    env.block.time = env.block.time.plus_seconds(60);
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::Distribute {}).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute"),
+            attr("distribution_amount", "6000000"),
+        ]
+    );
+}
+
+#[test]
+fn test_revoke_asset() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_oracle_price(&[(&"asset0000".to_string(), &Decimal::percent(150))]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, post_initialize_msg()).unwrap();
+    whitelist_asset(deps.as_mut(), mock_env(), "asset0000", 100u32);
+
+    let msg = ExecuteMsg::RevokeAsset {
+        asset_token: "asset0000".to_string(),
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    // the asset receives its share of the first window before leaving
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "revoke_asset"),
+            attr("asset_token", "asset0000"),
+            attr("end_price", "1.5"),
+            attr("distribution_amount", "1000000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            distribution_msg(vec![("asset0000", 250000u128), ("melange0000", 750000u128)]),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "mint0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&MintExecuteMsg::RegisterMigration {
+                    asset_token: "asset0000".to_string(),
+                    end_price: Decimal::percent(150),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // the asset left the distribution pool
+    assert_eq!(
+        query_distribution_info(deps.as_ref()).weights,
+        vec![("melange0000".to_string(), 300u32)]
+    );
+}
+
+#[test]
+fn test_whitelist() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, post_initialize_msg()).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "apple derivative".to_string(),
+        symbol: "mAPPL".to_string(),
+        oracle_proxy: "proxy0000".to_string(),
+        params: Params {
+            auction_discount: Decimal::percent(5),
+            min_collateral_ratio: Decimal::percent(150),
+            weight: Some(100u32),
+            collateral_multiplier: Some(Decimal::percent(120)),
+        },
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "whitelist"), attr("symbol", "mAPPL")]
+    );
+    assert_eq!(
+        res.messages,
+        vec![token_instantiate_msg("apple derivative", "mAPPL")]
+    );
+
+    // only one whitelist process at a time
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("A whitelist process is in progress")
+    );
+
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        token_instantiate_reply("asset0000"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "mint0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&MintExecuteMsg::RegisterAsset {
+                    asset_token: "asset0000".to_string(),
+                    auction_discount: Decimal::percent(5),
+                    min_collateral_ratio: Decimal::percent(150),
+                    ipo_params: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "oracle0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&OracleExecuteMsg::RegisterProxy {
+                    asset_token: "asset0000".to_string(),
+                    proxy_addr: "proxy0000".to_string(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "collateraloracle0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&CollateralOracleExecuteMsg::RegisterCollateralAsset {
+                    asset: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    price_source: SourceType::MelangeOracle {
+                        oracle_addr: "oracle0000".to_string(),
+                    },
+                    multiplier: Decimal::percent(120),
+                })
+                .unwrap(),
+            })),
+            SubMsg::reply_on_success(
+                WasmMsg::Execute {
+                    contract_addr: "tswapfactory".to_string(),
+                    funds: vec![],
+                    msg: to_binary(&TswapFactoryExecuteMsg::CreatePair {
+                        assets: [
+                            Asset {
+                                info: AssetInfo::NativeToken {
+                                    denom: "uusd".to_string(),
+                                },
+                                amount: Uint128::zero(),
+                            },
+                            Asset {
+                                info: AssetInfo::Token {
+                                    contract_addr: "asset0000".to_string(),
+                                },
+                                amount: Uint128::zero(),
+                            },
+                        ],
+                    })
+                    .unwrap(),
+                },
+                2,
+            ),
+        ]
+    );
+    assert_eq!(
+        query_distribution_info(deps.as_ref()).weights,
+        vec![
+            ("asset0000".to_string(), 100u32),
+            ("melange0000".to_string(), 300u32)
+        ]
+    );
+
+    deps.querier.with_tswap_pair(
+        &[
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        "pair0000",
+        "lptoken0000",
+    );
+
+    let tswap_reply = Reply {
+        id: 2,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), tswap_reply.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&StakingExecuteMsg::RegisterAsset {
+                asset_token: "asset0000".to_string(),
+                staking_token: "lptoken0000".to_string(),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // the temporary asset is consumed by the hook
+    let _res = reply(deps.as_mut(), mock_env(), tswap_reply).unwrap_err();
+}
+
+#[test]
+fn test_whitelist_default_weight() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, post_initialize_msg()).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "apple derivative".to_string(),
+        symbol: "mAPPL".to_string(),
+        oracle_proxy: "proxy0000".to_string(),
+        params: Params {
+            auction_discount: Decimal::percent(5),
+            min_collateral_ratio: Decimal::percent(150),
+            weight: None,
+            collateral_multiplier: None,
+        },
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        token_instantiate_reply("asset0000"),
+    )
+    .unwrap();
+
+    // the collateral multiplier defaults to 1
+    assert_eq!(
+        res.messages[2],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "collateraloracle0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&CollateralOracleExecuteMsg::RegisterCollateralAsset {
+                asset: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                price_source: SourceType::MelangeOracle {
+                    oracle_addr: "oracle0000".to_string(),
+                },
+                multiplier: Decimal::one(),
+            })
+            .unwrap(),
+        }))
+    );
+
+    assert_eq!(
+        query_distribution_info(deps.as_ref()).weights,
+        vec![
+            ("asset0000".to_string(), 30u32),
+            ("melange0000".to_string(), 300u32)
+        ]
+    );
+
+    let invalid_reply = Reply {
+        id: 3,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), invalid_reply).unwrap_err();
+    assert_eq!(res, StdError::generic_err("reply id is invalid"));
+}
+
+#[test]
+fn test_migrate_asset() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_oracle_price(&[(&"asset0000".to_string(), &Decimal::percent(150))]);
+    deps.querier
+        .with_asset_config("asset0000", Decimal::percent(20), Decimal::percent(150));
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, post_initialize_msg()).unwrap();
+
+    let msg = ExecuteMsg::MigrateAsset {
+        name: "apple migration".to_string(),
+        symbol: "mAPP2".to_string(),
+        oracle_proxy: "proxy0000".to_string(),
+        from_token: "asset0000".to_string(),
+    };
+
+    // the asset is not in the distribution pool
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("No distribution info stored"));
+
+    whitelist_asset(deps.as_mut(), mock_env(), "asset0000", 100u32);
+
+    // the asset receives its share of the first window before leaving
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            distribution_msg(vec![("asset0000", 250000u128), ("melange0000", 750000u128)]),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "mint0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&MintExecuteMsg::RegisterMigration {
+                    asset_token: "asset0000".to_string(),
+                    end_price: Decimal::percent(150),
+                })
+                .unwrap(),
+            })),
+            token_instantiate_msg("apple migration", "mAPP2"),
+        ]
+    );
+    assert_eq!(
+        query_distribution_info(deps.as_ref()).weights,
+        vec![("melange0000".to_string(), 300u32)]
+    );
+
+    // the new asset inherits the mint parameters and the weight,
+    // the second window is released before it joins the pool
+    env.block.time = env.block.time.plus_seconds(100);
+    let res = reply(deps.as_mut(), env, token_instantiate_reply("asset0001")).unwrap();
+    assert_eq!(
+        res.messages[0],
+        distribution_msg(vec![("melange0000", 10000000u128)])
+    );
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mint0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&MintExecuteMsg::RegisterAsset {
+                asset_token: "asset0001".to_string(),
+                auction_discount: Decimal::percent(20),
+                min_collateral_ratio: Decimal::percent(150),
+                ipo_params: None,
+            })
+            .unwrap(),
+        }))
+    );
+    assert_eq!(
+        query_distribution_info(deps.as_ref()).weights,
+        vec![
+            ("asset0001".to_string(), 100u32),
+            ("melange0000".to_string(), 300u32)
+        ]
+    );
+}
+
+#[test]
+fn test_pass_command() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, post_initialize_msg()).unwrap();
+
+    let msg = ExecuteMsg::PassCommand {
+        contract_addr: "mint0000".to_string(),
+        msg: to_binary(&MintExecuteMsg::RegisterMigration {
+            asset_token: "asset0000".to_string(),
+            end_price: Decimal::one(),
+        })
+        .unwrap(),
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mint0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&MintExecuteMsg::RegisterMigration {
+                asset_token: "asset0000".to_string(),
+                end_price: Decimal::one(),
+            })
+            .unwrap(),
+        }))]
+    );
+}
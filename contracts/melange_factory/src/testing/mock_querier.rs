@@ -0,0 +1,194 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, Empty, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, WasmQuery,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use melange_protocol::mint::AssetConfigResponse;
+use melange_protocol::oracle::PriceResponse;
+use terraswap::asset::{AssetInfo, PairInfo};
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        api: MockApi::default(),
+        storage: MockStorage::default(),
+        querier: custom_querier,
+        custom_query_type: PhantomData,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    oracle_price_querier: OraclePriceQuerier,
+    tswap_pair_querier: TswapPairQuerier,
+    asset_config_querier: AssetConfigQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct OraclePriceQuerier {
+    oracle_price: HashMap<String, Decimal>,
+}
+
+impl OraclePriceQuerier {
+    pub fn new(oracle_price: &[(&String, &Decimal)]) -> Self {
+        let mut oracle_price_map: HashMap<String, Decimal> = HashMap::new();
+        for (asset_token, oracle_price) in oracle_price.iter() {
+            oracle_price_map.insert(asset_token.to_string(), **oracle_price);
+        }
+
+        OraclePriceQuerier {
+            oracle_price: oracle_price_map,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct TswapPairQuerier {
+    pairs: HashMap<String, PairInfo>,
+}
+
+#[derive(Clone, Default)]
+pub struct AssetConfigQuerier {
+    asset_configs: HashMap<String, (Decimal, Decimal)>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {:?}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MockQueryMsg {
+    Price {
+        asset_token: String,
+        timeframe: Option<u64>,
+    },
+    Pair {
+        asset_infos: [AssetInfo; 2],
+    },
+    AssetConfig {
+        asset_token: String,
+    },
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { msg, .. }) => match from_binary(msg).unwrap() {
+                MockQueryMsg::Price { asset_token, .. } => {
+                    match self.oracle_price_querier.oracle_price.get(&asset_token) {
+                        Some(rate) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                                rate: *rate,
+                                last_updated: 1000u64,
+                            })))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No oracle price exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
+                MockQueryMsg::Pair { asset_infos } => {
+                    match self
+                        .tswap_pair_querier
+                        .pairs
+                        .get(&(asset_infos[0].to_string() + &asset_infos[1].to_string()))
+                    {
+                        Some(pair_info) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(pair_info)))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No pair info exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
+                MockQueryMsg::AssetConfig { asset_token } => {
+                    match self.asset_config_querier.asset_configs.get(&asset_token) {
+                        Some((auction_discount, min_collateral_ratio)) => SystemResult::Ok(
+                            ContractResult::from(to_binary(&AssetConfigResponse {
+                                token: asset_token,
+                                auction_discount: *auction_discount,
+                                min_collateral_ratio: *min_collateral_ratio,
+                                end_price: None,
+                                stability_fee_rate: Decimal::zero(),
+                                stability_fee_index: Decimal::one(),
+                                ipo_params: None,
+                            })),
+                        ),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No asset config exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
+            },
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            oracle_price_querier: OraclePriceQuerier::default(),
+            tswap_pair_querier: TswapPairQuerier::default(),
+            asset_config_querier: AssetConfigQuerier::default(),
+        }
+    }
+
+    // configure the oracle price mock querier
+    pub fn with_oracle_price(&mut self, oracle_price: &[(&String, &Decimal)]) {
+        self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
+    }
+
+    // configure the terraswap pair
+    pub fn with_tswap_pair(&mut self, asset_infos: &[AssetInfo; 2], pair: &str, lp_token: &str) {
+        self.tswap_pair_querier.pairs.insert(
+            asset_infos[0].to_string() + &asset_infos[1].to_string(),
+            PairInfo {
+                asset_infos: asset_infos.clone(),
+                contract_addr: pair.to_string(),
+                liquidity_token: lp_token.to_string(),
+                asset_decimals: [6u8, 6u8],
+            },
+        );
+    }
+
+    // configure the mint asset config querier
+    pub fn with_asset_config(
+        &mut self,
+        asset_token: &str,
+        auction_discount: Decimal,
+        min_collateral_ratio: Decimal,
+    ) {
+        self.asset_config_querier.asset_configs.insert(
+            asset_token.to_string(),
+            (auction_discount, min_collateral_ratio),
+        );
+    }
+}
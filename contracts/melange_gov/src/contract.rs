@@ -0,0 +1,814 @@
+use crate::staking::{
+    deposit_reward, load_total_balance, query_shares, query_staker, stake_voting_rewards,
+    stake_voting_tokens, withdraw_voting_rewards, withdraw_voting_tokens,
+};
+use crate::state::{
+    read_config, read_poll, read_poll_voter, read_poll_voters, read_polls, read_state,
+    read_tmp_poll_id, read_token_manager, store_config, store_poll, store_poll_indexer,
+    store_poll_voter, store_state, store_tmp_poll_id, store_token_manager, update_poll_status,
+    Config, ExecuteData, Poll, State,
+};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Api, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use melange_protocol::common::OrderBy;
+use melange_protocol::gov::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollAdminAction, PollConfig,
+    PollExecuteMsg, PollResponse, PollStatus, PollsResponse, QueryMsg, StateResponse, VoteOption,
+    VoterInfo, VotersResponse, VotersResponseItem,
+};
+
+const MIN_TITLE_LENGTH: usize = 4;
+const MAX_TITLE_LENGTH: usize = 64;
+const MIN_DESC_LENGTH: usize = 4;
+const MAX_DESC_LENGTH: usize = 1024;
+const MIN_LINK_LENGTH: usize = 12;
+const MAX_LINK_LENGTH: usize = 128;
+
+const POLL_EXECUTE_REPLY_ID: u64 = 1;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    validate_poll_config(&msg.default_poll_config)?;
+    validate_poll_config(&msg.migration_poll_config)?;
+    validate_poll_config(&msg.auth_admin_poll_config)?;
+    validate_voter_weight(msg.voter_weight)?;
+
+    let config = Config {
+        melange_token: deps.api.addr_canonicalize(&msg.melange_token)?,
+        owner: deps.api.addr_canonicalize(info.sender.as_str())?,
+        effective_delay: msg.effective_delay,
+        default_poll_config: msg.default_poll_config,
+        migration_poll_config: msg.migration_poll_config,
+        auth_admin_poll_config: msg.auth_admin_poll_config,
+        voter_weight: msg.voter_weight,
+        snapshot_period: msg.snapshot_period,
+        admin_manager: deps.api.addr_canonicalize(&msg.admin_manager)?,
+        poll_gas_limit: msg.poll_gas_limit,
+    };
+
+    let state = State {
+        contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
+        poll_count: 0,
+        total_share: Uint128::zero(),
+        total_deposit: Uint128::zero(),
+        pending_voting_rewards: Uint128::zero(),
+    };
+
+    store_config(deps.storage, &config)?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            effective_delay,
+            default_poll_config,
+            migration_poll_config,
+            auth_admin_poll_config,
+            voter_weight,
+            snapshot_period,
+            admin_manager,
+            poll_gas_limit,
+        } => {
+            let mut config: Config = read_config(deps.storage)?;
+            if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+
+            update_config(
+                deps.api,
+                &mut config,
+                owner,
+                effective_delay,
+                default_poll_config,
+                migration_poll_config,
+                auth_admin_poll_config,
+                voter_weight,
+                snapshot_period,
+                admin_manager,
+                poll_gas_limit,
+            )?;
+            store_config(deps.storage, &config)?;
+
+            Ok(Response::new().add_attribute("action", "update_config"))
+        }
+        ExecuteMsg::CastVote {
+            poll_id,
+            vote,
+            amount,
+        } => cast_vote(deps, env, info, poll_id, vote, amount),
+        ExecuteMsg::WithdrawVotingTokens { amount } => withdraw_voting_tokens(deps, info, amount),
+        ExecuteMsg::WithdrawVotingRewards { poll_id } => {
+            withdraw_voting_rewards(deps, info, poll_id)
+        }
+        ExecuteMsg::StakeVotingRewards { poll_id } => stake_voting_rewards(deps, info, poll_id),
+        ExecuteMsg::EndPoll { poll_id } => end_poll(deps, env, poll_id),
+        ExecuteMsg::ExecutePoll { poll_id } => execute_poll(deps, env, poll_id),
+        ExecuteMsg::SnapshotPoll { poll_id } => snapshot_poll(deps, env, poll_id),
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> StdResult<Response> {
+    // only melange token contract can execute this message
+    let config: Config = read_config(deps.storage)?;
+    if config.melange_token != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::StakeVotingTokens {}) => {
+            stake_voting_tokens(deps, cw20_msg.sender, cw20_msg.amount)
+        }
+        Ok(Cw20HookMsg::CreatePoll {
+            title,
+            description,
+            link,
+            execute_msg,
+            admin_action,
+        }) => create_poll(
+            deps,
+            env,
+            cw20_msg.sender,
+            cw20_msg.amount,
+            title,
+            description,
+            link,
+            execute_msg,
+            admin_action,
+        ),
+        Ok(Cw20HookMsg::DepositReward {}) => deposit_reward(deps, cw20_msg.amount),
+        Err(_) => Err(StdError::generic_err("data should be given")),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    match msg.id {
+        POLL_EXECUTE_REPLY_ID => {
+            let poll_id: u64 = read_tmp_poll_id(deps.storage)?;
+            failed_poll(deps, poll_id)
+        }
+        _ => Err(StdError::generic_err("reply id is invalid")),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_config(
+    api: &dyn Api,
+    config: &mut Config,
+    owner: Option<String>,
+    effective_delay: Option<u64>,
+    default_poll_config: Option<PollConfig>,
+    migration_poll_config: Option<PollConfig>,
+    auth_admin_poll_config: Option<PollConfig>,
+    voter_weight: Option<Decimal>,
+    snapshot_period: Option<u64>,
+    admin_manager: Option<String>,
+    poll_gas_limit: Option<u64>,
+) -> StdResult<()> {
+    if let Some(owner) = owner {
+        config.owner = api.addr_canonicalize(&owner)?;
+    }
+
+    if let Some(effective_delay) = effective_delay {
+        config.effective_delay = effective_delay;
+    }
+
+    if let Some(default_poll_config) = default_poll_config {
+        validate_poll_config(&default_poll_config)?;
+        config.default_poll_config = default_poll_config;
+    }
+
+    if let Some(migration_poll_config) = migration_poll_config {
+        validate_poll_config(&migration_poll_config)?;
+        config.migration_poll_config = migration_poll_config;
+    }
+
+    if let Some(auth_admin_poll_config) = auth_admin_poll_config {
+        validate_poll_config(&auth_admin_poll_config)?;
+        config.auth_admin_poll_config = auth_admin_poll_config;
+    }
+
+    if let Some(voter_weight) = voter_weight {
+        validate_voter_weight(voter_weight)?;
+        config.voter_weight = voter_weight;
+    }
+
+    if let Some(snapshot_period) = snapshot_period {
+        config.snapshot_period = snapshot_period;
+    }
+
+    if let Some(admin_manager) = admin_manager {
+        config.admin_manager = api.addr_canonicalize(&admin_manager)?;
+    }
+
+    if let Some(poll_gas_limit) = poll_gas_limit {
+        config.poll_gas_limit = poll_gas_limit;
+    }
+
+    Ok(())
+}
+
+/// validate_title returns an error if the title is invalid
+fn validate_title(title: &str) -> StdResult<()> {
+    if title.len() < MIN_TITLE_LENGTH {
+        Err(StdError::generic_err("Title too short"))
+    } else if title.len() > MAX_TITLE_LENGTH {
+        Err(StdError::generic_err("Title too long"))
+    } else {
+        Ok(())
+    }
+}
+
+/// validate_description returns an error if the description is invalid
+fn validate_description(description: &str) -> StdResult<()> {
+    if description.len() < MIN_DESC_LENGTH {
+        Err(StdError::generic_err("Description too short"))
+    } else if description.len() > MAX_DESC_LENGTH {
+        Err(StdError::generic_err("Description too long"))
+    } else {
+        Ok(())
+    }
+}
+
+/// validate_link returns an error if the link is invalid
+fn validate_link(link: &Option<String>) -> StdResult<()> {
+    if let Some(link) = link {
+        if link.len() < MIN_LINK_LENGTH {
+            Err(StdError::generic_err("Link too short"))
+        } else if link.len() > MAX_LINK_LENGTH {
+            Err(StdError::generic_err("Link too long"))
+        } else {
+            Ok(())
+        }
+    } else {
+        Ok(())
+    }
+}
+
+/// validate_poll_config returns an error if the quorum or threshold are not a ratio
+fn validate_poll_config(poll_config: &PollConfig) -> StdResult<()> {
+    if poll_config.quorum > Decimal::one() {
+        return Err(StdError::generic_err("quorum must be 0 to 1"));
+    }
+
+    if poll_config.threshold > Decimal::one() {
+        return Err(StdError::generic_err("threshold must be 0 to 1"));
+    }
+
+    Ok(())
+}
+
+fn validate_voter_weight(voter_weight: Decimal) -> StdResult<()> {
+    if voter_weight >= Decimal::one() {
+        return Err(StdError::generic_err("voter_weight must be smaller than 1"));
+    }
+
+    Ok(())
+}
+
+/// Auth admin polls transfer the admin privileges and every other admin
+/// action is a migration, so each of them has a stricter poll config
+fn poll_config_of(config: &Config, admin_action: &Option<PollAdminAction>) -> PollConfig {
+    match admin_action {
+        Some(PollAdminAction::AuthorizeClaim { .. }) => config.auth_admin_poll_config.clone(),
+        Some(_) => config.migration_poll_config.clone(),
+        None => config.default_poll_config.clone(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+/// create a new poll
+pub fn create_poll(
+    deps: DepsMut,
+    env: Env,
+    proposer: String,
+    deposit_amount: Uint128,
+    title: String,
+    description: String,
+    link: Option<String>,
+    execute_msg: Option<PollExecuteMsg>,
+    admin_action: Option<PollAdminAction>,
+) -> StdResult<Response> {
+    validate_title(&title)?;
+    validate_description(&description)?;
+    validate_link(&link)?;
+
+    if execute_msg.is_some() && admin_action.is_some() {
+        return Err(StdError::generic_err(
+            "Poll can not contain both execute_msg and admin_action",
+        ));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let poll_config: PollConfig = poll_config_of(&config, &admin_action);
+    if deposit_amount < poll_config.proposal_deposit {
+        return Err(StdError::generic_err(format!(
+            "Must deposit more than {} token",
+            poll_config.proposal_deposit
+        )));
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let poll_id = state.poll_count + 1;
+
+    // Increase poll count & total deposit amount
+    state.poll_count += 1;
+    state.total_deposit += deposit_amount;
+
+    let execute_data = match execute_msg {
+        Some(execute_msg) => Some(ExecuteData {
+            contract: deps.api.addr_canonicalize(&execute_msg.contract)?,
+            msg: execute_msg.msg,
+        }),
+        None => None,
+    };
+
+    let new_poll = Poll {
+        id: poll_id,
+        creator: deps.api.addr_canonicalize(&proposer)?,
+        status: PollStatus::InProgress,
+        yes_votes: Uint128::zero(),
+        no_votes: Uint128::zero(),
+        abstain_votes: Uint128::zero(),
+        end_time: env.block.time.seconds() + poll_config.voting_period,
+        title,
+        description,
+        link,
+        execute_data,
+        deposit_amount,
+        total_balance_at_end_poll: None,
+        voters_reward: Uint128::zero(),
+        staked_amount: None,
+        admin_action,
+    };
+
+    store_poll(deps.storage, &new_poll)?;
+    store_poll_indexer(deps.storage, &PollStatus::InProgress, poll_id)?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "create_poll"),
+        attr("creator", proposer),
+        attr("poll_id", poll_id.to_string()),
+        attr("end_time", new_poll.end_time.to_string()),
+    ]))
+}
+
+/*
+ * Ends a poll.
+ */
+pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
+    let mut a_poll: Poll = read_poll(deps.storage, poll_id)?;
+
+    if a_poll.status != PollStatus::InProgress {
+        return Err(StdError::generic_err("Poll is not in progress"));
+    }
+
+    if a_poll.end_time > env.block.time.seconds() {
+        return Err(StdError::generic_err("Voting period has not expired"));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    let poll_config: PollConfig = poll_config_of(&config, &a_poll.admin_action);
+
+    let no = a_poll.no_votes.u128();
+    let yes = a_poll.yes_votes.u128();
+    let tallied_weight = a_poll.total_votes();
+
+    let mut poll_status = PollStatus::Rejected;
+    let mut rejected_reason = "";
+    let mut passed = false;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    let staked_weight = if state.total_share.is_zero() {
+        Uint128::zero()
+    } else if let Some(staked_amount) = a_poll.staked_amount {
+        staked_amount
+    } else {
+        load_total_balance(deps.as_ref(), &config, &state)?
+    };
+
+    let quorum = if staked_weight.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(tallied_weight, staked_weight)
+    };
+
+    if tallied_weight.is_zero() || quorum < poll_config.quorum {
+        // Quorum: More than quorum of the total staked tokens at the end of the voting
+        // period need to have participated in the vote.
+        rejected_reason = "Quorum not reached";
+    } else {
+        if yes != 0u128 && Decimal::from_ratio(yes, yes + no) > poll_config.threshold {
+            // Threshold: More than 50% of the tokens that participated in the vote
+            // (after excluding "Abstain" votes) need to have voted in favor of the proposal ("Yes").
+            poll_status = PollStatus::Passed;
+            passed = true;
+        } else {
+            rejected_reason = "Threshold not reached";
+        }
+
+        // Refunds deposit only when quorum is reached
+        if !a_poll.deposit_amount.is_zero() {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.melange_token)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: deps.api.addr_humanize(&a_poll.creator)?.to_string(),
+                    amount: a_poll.deposit_amount,
+                })?,
+            }))
+        }
+    }
+
+    // Decrease total deposit amount. When the quorum is not reached the
+    // deposit stays in the contract and is distributed to the stakers
+    state.total_deposit = state.total_deposit.checked_sub(a_poll.deposit_amount)?;
+
+    // Nobody can claim the voter rewards of a poll without votes
+    if tallied_weight.is_zero() && !a_poll.voters_reward.is_zero() {
+        state.pending_voting_rewards = state
+            .pending_voting_rewards
+            .checked_sub(a_poll.voters_reward)?;
+        a_poll.voters_reward = Uint128::zero();
+    }
+    store_state(deps.storage, &state)?;
+
+    // Update poll status
+    a_poll.total_balance_at_end_poll = Some(staked_weight);
+    update_poll_status(deps.storage, &mut a_poll, poll_status)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "end_poll"),
+        attr("poll_id", poll_id.to_string()),
+        attr("rejected_reason", rejected_reason),
+        attr("passed", passed.to_string()),
+    ]))
+}
+
+/*
+ * Execute a msg of passed poll.
+ */
+pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    let mut a_poll: Poll = read_poll(deps.storage, poll_id)?;
+
+    if a_poll.status != PollStatus::Passed {
+        return Err(StdError::generic_err("Poll is not in passed status"));
+    }
+
+    if a_poll.end_time + config.effective_delay > env.block.time.seconds() {
+        return Err(StdError::generic_err("Effective delay has not expired"));
+    }
+
+    let execute_msg: Option<WasmMsg> = match a_poll.admin_action.clone() {
+        Some(PollAdminAction::UpdateConfig {
+            owner,
+            effective_delay,
+            default_poll_config,
+            migration_poll_config,
+            auth_admin_poll_config,
+            voter_weight,
+            snapshot_period,
+            admin_manager,
+        }) => {
+            update_config(
+                deps.api,
+                &mut config,
+                owner,
+                effective_delay,
+                default_poll_config,
+                migration_poll_config,
+                auth_admin_poll_config,
+                voter_weight,
+                snapshot_period,
+                admin_manager,
+                None,
+            )?;
+            store_config(deps.storage, &config)?;
+
+            None
+        }
+        // the admin manager accepts the rest of the admin actions as its own messages
+        Some(admin_action) => Some(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.admin_manager)?.to_string(),
+            msg: to_binary(&admin_action)?,
+            funds: vec![],
+        }),
+        None => match a_poll.execute_data.clone() {
+            Some(execute_data) => Some(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&execute_data.contract)?.to_string(),
+                msg: execute_data.msg,
+                funds: vec![],
+            }),
+            None => return Err(StdError::generic_err("The poll does not have execute_data")),
+        },
+    };
+
+    let mut messages: Vec<SubMsg> = vec![];
+    if let Some(execute_msg) = execute_msg {
+        // a failing execution marks the poll as failed instead of reverting
+        messages.push(SubMsg {
+            id: POLL_EXECUTE_REPLY_ID,
+            msg: CosmosMsg::Wasm(execute_msg),
+            gas_limit: Some(config.poll_gas_limit),
+            reply_on: ReplyOn::Error,
+        });
+        store_tmp_poll_id(deps.storage, a_poll.id)?;
+    }
+
+    update_poll_status(deps.storage, &mut a_poll, PollStatus::Executed)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            attr("action", "execute_poll"),
+            attr("poll_id", poll_id.to_string()),
+        ]))
+}
+
+/*
+ * Set the status of a poll to Failed if execute_poll fails
+ */
+pub fn failed_poll(deps: DepsMut, poll_id: u64) -> StdResult<Response> {
+    let mut a_poll: Poll = read_poll(deps.storage, poll_id)?;
+    update_poll_status(deps.storage, &mut a_poll, PollStatus::Failed)?;
+
+    Ok(Response::new().add_attribute("action", "failed_poll"))
+}
+
+pub fn cast_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    vote: VoteOption,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let config: Config = read_config(deps.storage)?;
+    let state: State = read_state(deps.storage)?;
+    if poll_id == 0 || state.poll_count < poll_id {
+        return Err(StdError::generic_err("Poll does not exist"));
+    }
+
+    let mut a_poll: Poll = read_poll(deps.storage, poll_id)?;
+    let current_seconds = env.block.time.seconds();
+    if a_poll.status != PollStatus::InProgress || current_seconds > a_poll.end_time {
+        return Err(StdError::generic_err("Poll is not in progress"));
+    }
+
+    // Check the voter already has a vote on the poll
+    if read_poll_voter(deps.storage, poll_id, &sender_address_raw)?.is_some() {
+        return Err(StdError::generic_err("User has already voted."));
+    }
+
+    let mut token_manager =
+        read_token_manager(deps.storage, &sender_address_raw)?.unwrap_or_default();
+
+    // convert share to amount
+    let total_share = state.total_share;
+    let total_balance = load_total_balance(deps.as_ref(), &config, &state)?;
+
+    if total_share.is_zero()
+        || token_manager
+            .share
+            .multiply_ratio(total_balance, total_share)
+            < amount
+    {
+        return Err(StdError::generic_err(
+            "User does not have enough staked tokens.",
+        ));
+    }
+
+    // update tally info
+    match vote {
+        VoteOption::Yes => a_poll.yes_votes += amount,
+        VoteOption::No => a_poll.no_votes += amount,
+        VoteOption::Abstain => a_poll.abstain_votes += amount,
+    }
+
+    let vote_info = VoterInfo {
+        vote: vote.clone(),
+        balance: amount,
+    };
+    token_manager
+        .locked_balance
+        .push((poll_id, vote_info.clone()));
+    store_token_manager(deps.storage, &sender_address_raw, &token_manager)?;
+
+    // store poll voter && and update poll data
+    store_poll_voter(deps.storage, poll_id, &sender_address_raw, &vote_info)?;
+
+    // processing snapshot
+    let time_to_end = a_poll.end_time - current_seconds;
+    if time_to_end < config.snapshot_period && a_poll.staked_amount.is_none() {
+        a_poll.staked_amount = Some(total_balance);
+    }
+
+    store_poll(deps.storage, &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cast_vote"),
+        attr("poll_id", poll_id.to_string()),
+        attr("amount", amount.to_string()),
+        attr("voter", info.sender.to_string()),
+        attr("vote_option", vote_info.vote.to_string()),
+    ]))
+}
+
+/// SnapshotPoll is used to take a snapshot of the staked amount for quorum calculation
+pub fn snapshot_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let mut a_poll: Poll = read_poll(deps.storage, poll_id)?;
+
+    if a_poll.status != PollStatus::InProgress {
+        return Err(StdError::generic_err("Poll is not in progress"));
+    }
+
+    let time_to_end = a_poll.end_time.saturating_sub(env.block.time.seconds());
+    if time_to_end > config.snapshot_period {
+        return Err(StdError::generic_err("Cannot snapshot at this time"));
+    }
+
+    if a_poll.staked_amount.is_some() {
+        return Err(StdError::generic_err("Snapshot has already occurred"));
+    }
+
+    // store the current staked amount for quorum calculation
+    let state: State = read_state(deps.storage)?;
+    let staked_amount = load_total_balance(deps.as_ref(), &config, &state)?;
+
+    a_poll.staked_amount = Some(staked_amount);
+    store_poll(deps.storage, &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "snapshot_poll"),
+        attr("poll_id", poll_id.to_string()),
+        attr("staked_amount", staked_amount.to_string()),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::Staker { address } => to_binary(&query_staker(deps, address)?),
+        QueryMsg::Poll { poll_id } => to_binary(&query_poll(deps, poll_id)?),
+        QueryMsg::Polls {
+            filter,
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&query_polls(deps, filter, start_after, limit, order_by)?),
+        QueryMsg::Voter { poll_id, address } => to_binary(&query_voter(deps, poll_id, address)?),
+        QueryMsg::Voters {
+            poll_id,
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&query_voters(deps, poll_id, start_after, limit, order_by)?),
+        QueryMsg::Shares {
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&query_shares(deps, start_after, limit, order_by)?),
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        melange_token: deps.api.addr_humanize(&config.melange_token)?.to_string(),
+        effective_delay: config.effective_delay,
+        default_poll_config: config.default_poll_config,
+        migration_poll_config: config.migration_poll_config,
+        auth_admin_poll_config: config.auth_admin_poll_config,
+        voter_weight: config.voter_weight,
+        snapshot_period: config.snapshot_period,
+        admin_manager: deps.api.addr_humanize(&config.admin_manager)?.to_string(),
+        poll_gas_limit: config.poll_gas_limit,
+    })
+}
+
+fn query_state(deps: Deps) -> StdResult<StateResponse> {
+    let state: State = read_state(deps.storage)?;
+    Ok(StateResponse {
+        poll_count: state.poll_count,
+        total_share: state.total_share,
+        total_deposit: state.total_deposit,
+        pending_voting_rewards: state.pending_voting_rewards,
+    })
+}
+
+fn poll_to_response(deps: Deps, poll: Poll) -> StdResult<PollResponse> {
+    Ok(PollResponse {
+        id: poll.id,
+        creator: deps.api.addr_humanize(&poll.creator)?.to_string(),
+        status: poll.status,
+        end_time: poll.end_time,
+        title: poll.title,
+        description: poll.description,
+        link: poll.link,
+        deposit_amount: poll.deposit_amount,
+        execute_data: match poll.execute_data {
+            Some(execute_data) => Some(PollExecuteMsg {
+                contract: deps.api.addr_humanize(&execute_data.contract)?.to_string(),
+                msg: execute_data.msg,
+            }),
+            None => None,
+        },
+        yes_votes: poll.yes_votes,
+        no_votes: poll.no_votes,
+        abstain_votes: poll.abstain_votes,
+        total_balance_at_end_poll: poll.total_balance_at_end_poll,
+        voters_reward: poll.voters_reward,
+        staked_amount: poll.staked_amount,
+        admin_action: poll.admin_action,
+    })
+}
+
+fn query_poll(deps: Deps, poll_id: u64) -> StdResult<PollResponse> {
+    let poll: Poll = read_poll(deps.storage, poll_id)?;
+    poll_to_response(deps, poll)
+}
+
+fn query_polls(
+    deps: Deps,
+    filter: Option<PollStatus>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<PollsResponse> {
+    let polls: Vec<PollResponse> =
+        read_polls(deps.storage, filter, start_after, limit, order_by, false)?
+            .into_iter()
+            .map(|poll| poll_to_response(deps, poll))
+            .collect::<StdResult<Vec<PollResponse>>>()?;
+
+    Ok(PollsResponse { polls })
+}
+
+fn query_voter(deps: Deps, poll_id: u64, address: String) -> StdResult<VotersResponseItem> {
+    let voter = deps.api.addr_canonicalize(&address)?;
+    match read_poll_voter(deps.storage, poll_id, &voter)? {
+        Some(voter_info) => Ok(VotersResponseItem {
+            voter: address,
+            vote: voter_info.vote,
+            balance: voter_info.balance,
+        }),
+        None => Err(StdError::generic_err("Voter not found")),
+    }
+}
+
+fn query_voters(
+    deps: Deps,
+    poll_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<VotersResponse> {
+    let start_after = match start_after {
+        Some(start_after) => Some(deps.api.addr_canonicalize(&start_after)?),
+        None => None,
+    };
+
+    let voters: Vec<VotersResponseItem> =
+        read_poll_voters(deps.storage, poll_id, start_after, limit, order_by)?
+            .into_iter()
+            .map(|(voter, voter_info)| {
+                Ok(VotersResponseItem {
+                    voter: deps.api.addr_humanize(&voter)?.to_string(),
+                    vote: voter_info.vote,
+                    balance: voter_info.balance,
+                })
+            })
+            .collect::<StdResult<Vec<VotersResponseItem>>>()?;
+
+    Ok(VotersResponse { voters })
+}
@@ -0,0 +1,6 @@
+pub mod contract;
+mod staking;
+mod state;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,399 @@
+use crate::state::{
+    read_config, read_poll, read_polls, read_shares, read_state, read_token_manager,
+    remove_poll_voter, store_poll, store_state, store_token_manager, Config, Poll, State,
+    TokenManager,
+};
+use cosmwasm_std::{
+    attr, to_binary, CanonicalAddr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult,
+    Storage, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use melange_protocol::common::OrderBy;
+use melange_protocol::gov::{PollStatus, SharesResponse, SharesResponseItem, StakerResponse};
+use terraswap::querier::query_token_balance;
+
+pub fn stake_voting_tokens(deps: DepsMut, sender: String, amount: Uint128) -> StdResult<Response> {
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Insufficient funds sent"));
+    }
+
+    let sender_address_raw = deps.api.addr_canonicalize(&sender)?;
+    let mut token_manager =
+        read_token_manager(deps.storage, &sender_address_raw)?.unwrap_or_default();
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    // balance already increased, so subtract deposit amount
+    let total_balance = load_total_balance(deps.as_ref(), &config, &state)?.checked_sub(amount)?;
+
+    let share = if total_balance.is_zero() || state.total_share.is_zero() {
+        amount
+    } else {
+        amount.multiply_ratio(state.total_share, total_balance)
+    };
+
+    token_manager.share += share;
+    state.total_share += share;
+
+    store_state(deps.storage, &state)?;
+    store_token_manager(deps.storage, &sender_address_raw, &token_manager)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "staking"),
+        attr("sender", sender),
+        attr("share", share.to_string()),
+        attr("amount", amount.to_string()),
+    ]))
+}
+
+// Withdraw amount if not staked. By default all funds will be withdrawn.
+pub fn withdraw_voting_tokens(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Option<Uint128>,
+) -> StdResult<Response> {
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut token_manager = match read_token_manager(deps.storage, &sender_address_raw)? {
+        Some(token_manager) => token_manager,
+        None => return Err(StdError::generic_err("Nothing staked")),
+    };
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    // Load total share & total balance except proposal deposit amount
+    let total_share = state.total_share;
+    let total_balance = load_total_balance(deps.as_ref(), &config, &state)?;
+    if total_share.is_zero() || total_balance.is_zero() {
+        return Err(StdError::generic_err("Nothing staked"));
+    }
+
+    let locked_balance =
+        compute_locked_balance(deps.storage, &mut token_manager, &sender_address_raw)?;
+    let locked_share = locked_balance.multiply_ratio(total_share, total_balance);
+    let user_share = token_manager.share;
+
+    let withdraw_share = amount
+        .map(|v| v.multiply_ratio(total_share, total_balance))
+        .unwrap_or_else(|| user_share.saturating_sub(locked_share));
+    let withdraw_amount =
+        amount.unwrap_or_else(|| withdraw_share.multiply_ratio(total_balance, total_share));
+
+    if locked_share + withdraw_share > user_share {
+        return Err(StdError::generic_err(
+            "User is trying to withdraw too many tokens.",
+        ));
+    }
+
+    token_manager.share = user_share - withdraw_share;
+    store_token_manager(deps.storage, &sender_address_raw, &token_manager)?;
+
+    state.total_share = total_share.checked_sub(withdraw_share)?;
+    store_state(deps.storage, &state)?;
+
+    send_tokens(
+        deps.as_ref(),
+        &config.melange_token,
+        info.sender.to_string(),
+        withdraw_amount,
+        "withdraw",
+    )
+}
+
+/// Distributes the voter share of the deposited rewards among the polls in
+/// progress. The remaining rewards increase the value of every staked share
+pub fn deposit_reward(deps: DepsMut, amount: Uint128) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+
+    let polls_in_progress: Vec<Poll> = read_polls(
+        deps.storage,
+        Some(PollStatus::InProgress),
+        None,
+        None,
+        None,
+        true,
+    )?;
+
+    let voter_rewards: Uint128 = amount * config.voter_weight;
+    let rewards_per_poll: Uint128 = if polls_in_progress.is_empty() || voter_rewards.is_zero() {
+        Uint128::zero()
+    } else {
+        voter_rewards.multiply_ratio(1u128, polls_in_progress.len() as u128)
+    };
+
+    if !rewards_per_poll.is_zero() {
+        for mut poll in polls_in_progress.iter().cloned() {
+            poll.voters_reward += rewards_per_poll;
+            store_poll(deps.storage, &poll)?;
+        }
+
+        let mut state: State = read_state(deps.storage)?;
+        state.pending_voting_rewards +=
+            rewards_per_poll * Uint128::from(polls_in_progress.len() as u128);
+        store_state(deps.storage, &state)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deposit_reward"),
+        attr("amount", amount.to_string()),
+    ]))
+}
+
+pub fn withdraw_voting_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+    poll_id: Option<u64>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut token_manager = match read_token_manager(deps.storage, &sender_address_raw)? {
+        Some(token_manager) => token_manager,
+        None => return Err(StdError::generic_err("Nothing staked")),
+    };
+
+    let user_reward_amount: Uint128 = withdraw_user_voting_rewards(
+        deps.storage,
+        &sender_address_raw,
+        &mut token_manager,
+        poll_id,
+    )?;
+    store_token_manager(deps.storage, &sender_address_raw, &token_manager)?;
+
+    let mut state: State = read_state(deps.storage)?;
+    state.pending_voting_rewards = state
+        .pending_voting_rewards
+        .checked_sub(user_reward_amount)?;
+    store_state(deps.storage, &state)?;
+
+    send_tokens(
+        deps.as_ref(),
+        &config.melange_token,
+        info.sender.to_string(),
+        user_reward_amount,
+        "withdraw_voting_rewards",
+    )
+}
+
+pub fn stake_voting_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+    poll_id: Option<u64>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut token_manager = match read_token_manager(deps.storage, &sender_address_raw)? {
+        Some(token_manager) => token_manager,
+        None => return Err(StdError::generic_err("Nothing staked")),
+    };
+
+    let user_reward_amount: Uint128 = withdraw_user_voting_rewards(
+        deps.storage,
+        &sender_address_raw,
+        &mut token_manager,
+        poll_id,
+    )?;
+
+    let mut state: State = read_state(deps.storage)?;
+    state.pending_voting_rewards = state
+        .pending_voting_rewards
+        .checked_sub(user_reward_amount)?;
+
+    // the rewards are already in the contract balance, so subtract them
+    let total_balance =
+        load_total_balance(deps.as_ref(), &config, &state)?.checked_sub(user_reward_amount)?;
+
+    let share: Uint128 = if total_balance.is_zero() || state.total_share.is_zero() {
+        user_reward_amount
+    } else {
+        user_reward_amount.multiply_ratio(state.total_share, total_balance)
+    };
+
+    token_manager.share += share;
+    state.total_share += share;
+
+    store_state(deps.storage, &state)?;
+    store_token_manager(deps.storage, &sender_address_raw, &token_manager)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "stake_voting_rewards"),
+        attr("staker", info.sender.to_string()),
+        attr("share", share.to_string()),
+        attr("amount", user_reward_amount.to_string()),
+    ]))
+}
+
+/// Removes the ended polls with rewards from the voter locked balances and
+/// returns the total withdrawn rewards
+fn withdraw_user_voting_rewards(
+    storage: &mut dyn Storage,
+    user_address: &CanonicalAddr,
+    token_manager: &mut TokenManager,
+    poll_id: Option<u64>,
+) -> StdResult<Uint128> {
+    let w_polls: Vec<(Poll, Uint128)> = withdrawable_polls(storage, token_manager, poll_id)?;
+    if w_polls.is_empty() {
+        return Err(StdError::generic_err("Nothing to withdraw"));
+    }
+
+    let mut user_reward_amount = Uint128::zero();
+    for (poll, reward) in w_polls.iter() {
+        user_reward_amount += *reward;
+        remove_poll_voter(storage, poll.id, user_address);
+        token_manager
+            .locked_balance
+            .retain(|(locked_poll_id, _)| *locked_poll_id != poll.id);
+    }
+
+    Ok(user_reward_amount)
+}
+
+/// Returns the ended polls the user voted on, along with the voting
+/// rewards of the user on each of them
+fn withdrawable_polls(
+    storage: &dyn Storage,
+    token_manager: &TokenManager,
+    poll_id: Option<u64>,
+) -> StdResult<Vec<(Poll, Uint128)>> {
+    let mut w_polls: Vec<(Poll, Uint128)> = vec![];
+    for (locked_poll_id, voter_info) in token_manager.locked_balance.iter() {
+        if poll_id.is_some_and(|poll_id| poll_id != *locked_poll_id) {
+            continue;
+        }
+
+        let poll: Poll = read_poll(storage, *locked_poll_id)?;
+        if poll.status == PollStatus::InProgress || poll.voters_reward.is_zero() {
+            continue;
+        }
+
+        let reward = poll
+            .voters_reward
+            .multiply_ratio(voter_info.balance, poll.total_votes());
+        w_polls.push((poll, reward));
+    }
+
+    Ok(w_polls)
+}
+
+/// Returns the amount of tokens locked by the votes on polls in progress.
+/// Votes on ended polls without pending rewards are cleaned up
+fn compute_locked_balance(
+    storage: &mut dyn Storage,
+    token_manager: &mut TokenManager,
+    voter: &CanonicalAddr,
+) -> StdResult<Uint128> {
+    let mut locked_balance = Uint128::zero();
+    let mut kept_votes = vec![];
+    for (poll_id, voter_info) in token_manager.locked_balance.drain(..) {
+        let poll: Poll = read_poll(storage, poll_id)?;
+        if poll.status == PollStatus::InProgress {
+            locked_balance = locked_balance.max(voter_info.balance);
+        } else if poll.voters_reward.is_zero() {
+            // remove voter info from the poll
+            remove_poll_voter(storage, poll_id, voter);
+            continue;
+        }
+
+        kept_votes.push((poll_id, voter_info));
+    }
+
+    token_manager.locked_balance = kept_votes;
+    Ok(locked_balance)
+}
+
+/// Returns the staked balance, which excludes the poll deposits and the
+/// voting rewards that were not withdrawn yet
+pub fn load_total_balance(deps: Deps, config: &Config, state: &State) -> StdResult<Uint128> {
+    let balance = query_token_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.melange_token)?,
+        deps.api.addr_humanize(&state.contract_addr)?,
+    )?;
+
+    Ok(balance.checked_sub(state.total_deposit + state.pending_voting_rewards)?)
+}
+
+fn send_tokens(
+    deps: Deps,
+    asset_token: &CanonicalAddr,
+    recipient: String,
+    amount: Uint128,
+    action: &str,
+) -> StdResult<Response> {
+    let contract_human = deps.api.addr_humanize(asset_token)?.to_string();
+
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: contract_human,
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.clone(),
+                amount,
+            })?,
+            funds: vec![],
+        })
+        .add_attributes(vec![
+            attr("action", action),
+            attr("recipient", recipient),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
+pub fn query_staker(deps: Deps, address: String) -> StdResult<StakerResponse> {
+    let addr_raw = deps.api.addr_canonicalize(&address)?;
+    let config: Config = read_config(deps.storage)?;
+    let state: State = read_state(deps.storage)?;
+    let mut token_manager = read_token_manager(deps.storage, &addr_raw)?.unwrap_or_default();
+
+    let w_polls: Vec<(Poll, Uint128)> = withdrawable_polls(deps.storage, &token_manager, None)?;
+    let pending_voting_rewards: Uint128 = w_polls.iter().map(|(_, reward)| *reward).sum();
+
+    // filter out not in-progress polls
+    let mut locked_balance = vec![];
+    for (poll_id, voter_info) in token_manager.locked_balance.drain(..) {
+        if read_poll(deps.storage, poll_id)?.status == PollStatus::InProgress {
+            locked_balance.push((poll_id, voter_info));
+        }
+    }
+
+    let total_balance = load_total_balance(deps, &config, &state)?;
+
+    Ok(StakerResponse {
+        balance: if !state.total_share.is_zero() {
+            token_manager
+                .share
+                .multiply_ratio(total_balance, state.total_share)
+        } else {
+            Uint128::zero()
+        },
+        share: token_manager.share,
+        locked_balance,
+        withdrawable_polls: w_polls
+            .into_iter()
+            .map(|(poll, reward)| (poll.id, reward))
+            .collect(),
+        pending_voting_rewards,
+    })
+}
+
+pub fn query_shares(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<SharesResponse> {
+    let start_after = match start_after {
+        Some(start_after) => Some(deps.api.addr_canonicalize(&start_after)?),
+        None => None,
+    };
+
+    let stakers: Vec<SharesResponseItem> = read_shares(deps.storage, start_after, limit, order_by)?
+        .into_iter()
+        .map(|(staker, token_manager)| {
+            Ok(SharesResponseItem {
+                staker: deps.api.addr_humanize(&staker)?.to_string(),
+                share: token_manager.share,
+            })
+        })
+        .collect::<StdResult<Vec<SharesResponseItem>>>()?;
+
+    Ok(SharesResponse { stakers })
+}
@@ -0,0 +1,318 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Binary, CanonicalAddr, Decimal, StdError, StdResult, Storage, Uint128};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use melange_protocol::common::OrderBy;
+use melange_protocol::gov::{PollAdminAction, PollConfig, PollStatus, VoterInfo};
+use std::convert::TryInto;
+
+static KEY_CONFIG: &[u8] = b"config";
+static KEY_STATE: &[u8] = b"state";
+static KEY_TMP_POLL_ID: &[u8] = b"tmp_poll_id";
+
+static PREFIX_POLL_INDEXER: &[u8] = b"poll_indexer";
+static PREFIX_POLL_VOTER: &[u8] = b"poll_voter";
+static PREFIX_POLL: &[u8] = b"poll";
+static PREFIX_BANK: &[u8] = b"bank";
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub melange_token: CanonicalAddr,
+    pub owner: CanonicalAddr,
+    pub effective_delay: u64,
+    pub default_poll_config: PollConfig,
+    pub migration_poll_config: PollConfig,
+    pub auth_admin_poll_config: PollConfig,
+    pub voter_weight: Decimal,
+    pub snapshot_period: u64,
+    pub admin_manager: CanonicalAddr,
+    pub poll_gas_limit: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub contract_addr: CanonicalAddr,
+    pub poll_count: u64,
+    pub total_share: Uint128,
+    pub total_deposit: Uint128,
+    pub pending_voting_rewards: Uint128,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenManager {
+    pub share: Uint128,                        // total staked balance
+    pub locked_balance: Vec<(u64, VoterInfo)>, // maps poll_id to weight voted
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Poll {
+    pub id: u64,
+    pub creator: CanonicalAddr,
+    pub status: PollStatus,
+    pub yes_votes: Uint128,
+    pub no_votes: Uint128,
+    pub abstain_votes: Uint128,
+    pub end_time: u64,
+    pub title: String,
+    pub description: String,
+    pub link: Option<String>,
+    pub execute_data: Option<ExecuteData>,
+    pub deposit_amount: Uint128,
+    /// Total balance at the end poll
+    pub total_balance_at_end_poll: Option<Uint128>,
+    pub voters_reward: Uint128,
+    pub staked_amount: Option<Uint128>,
+    pub admin_action: Option<PollAdminAction>,
+}
+
+impl Poll {
+    pub fn total_votes(&self) -> Uint128 {
+        self.yes_votes + self.no_votes + self.abstain_votes
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExecuteData {
+    pub contract: CanonicalAddr,
+    pub msg: Binary,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+pub fn store_state(storage: &mut dyn Storage, state: &State) -> StdResult<()> {
+    singleton(storage, KEY_STATE).save(state)
+}
+
+pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
+    singleton_read(storage, KEY_STATE).load()
+}
+
+pub fn store_tmp_poll_id(storage: &mut dyn Storage, tmp_poll_id: u64) -> StdResult<()> {
+    singleton(storage, KEY_TMP_POLL_ID).save(&tmp_poll_id)
+}
+
+pub fn read_tmp_poll_id(storage: &dyn Storage) -> StdResult<u64> {
+    singleton_read(storage, KEY_TMP_POLL_ID).load()
+}
+
+pub fn store_poll(storage: &mut dyn Storage, poll: &Poll) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_POLL).save(&poll.id.to_be_bytes(), poll)
+}
+
+pub fn read_poll(storage: &dyn Storage, poll_id: u64) -> StdResult<Poll> {
+    match ReadonlyBucket::new(storage, PREFIX_POLL).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => Ok(poll),
+        None => Err(StdError::generic_err("Poll does not exist")),
+    }
+}
+
+pub fn store_poll_indexer(
+    storage: &mut dyn Storage,
+    status: &PollStatus,
+    poll_id: u64,
+) -> StdResult<()> {
+    Bucket::multilevel(
+        storage,
+        &[PREFIX_POLL_INDEXER, status.to_string().as_bytes()],
+    )
+    .save(&poll_id.to_be_bytes(), &true)
+}
+
+pub fn remove_poll_indexer(storage: &mut dyn Storage, status: &PollStatus, poll_id: u64) {
+    Bucket::<bool>::multilevel(
+        storage,
+        &[PREFIX_POLL_INDEXER, status.to_string().as_bytes()],
+    )
+    .remove(&poll_id.to_be_bytes())
+}
+
+/// Moves the poll to its new status, keeping the status index in sync
+pub fn update_poll_status(
+    storage: &mut dyn Storage,
+    poll: &mut Poll,
+    status: PollStatus,
+) -> StdResult<()> {
+    remove_poll_indexer(storage, &poll.status, poll.id);
+    store_poll_indexer(storage, &status, poll.id)?;
+    poll.status = status;
+    store_poll(storage, poll)
+}
+
+pub fn store_poll_voter(
+    storage: &mut dyn Storage,
+    poll_id: u64,
+    voter: &CanonicalAddr,
+    voter_info: &VoterInfo,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()])
+        .save(voter.as_slice(), voter_info)
+}
+
+pub fn read_poll_voter(
+    storage: &dyn Storage,
+    poll_id: u64,
+    voter: &CanonicalAddr,
+) -> StdResult<Option<VoterInfo>> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()])
+        .may_load(voter.as_slice())
+}
+
+pub fn remove_poll_voter(storage: &mut dyn Storage, poll_id: u64, voter: &CanonicalAddr) {
+    Bucket::<VoterInfo>::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()])
+        .remove(voter.as_slice())
+}
+
+pub fn store_token_manager(
+    storage: &mut dyn Storage,
+    staker: &CanonicalAddr,
+    token_manager: &TokenManager,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_BANK).save(staker.as_slice(), token_manager)
+}
+
+pub fn read_token_manager(
+    storage: &dyn Storage,
+    staker: &CanonicalAddr,
+) -> StdResult<Option<TokenManager>> {
+    ReadonlyBucket::new(storage, PREFIX_BANK).may_load(staker.as_slice())
+}
+
+pub fn read_poll_voters(
+    storage: &dyn Storage,
+    poll_id: u64,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<Vec<(CanonicalAddr, VoterInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (start, end, order_by) = match order_by {
+        Some(OrderBy::Asc) => (calc_range_start_addr(start_after), None, OrderBy::Asc),
+        _ => (None, calc_range_end_addr(start_after), OrderBy::Desc),
+    };
+
+    let voters: ReadonlyBucket<VoterInfo> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()]);
+    voters
+        .range(start.as_deref(), end.as_deref(), order_by.into())
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+/// Reads the polls, optionally filtered by status. `remove_hard_cap` lifts
+/// the query limit for internal iterations over every in progress poll
+pub fn read_polls(
+    storage: &dyn Storage,
+    filter: Option<PollStatus>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+    remove_hard_cap: bool,
+) -> StdResult<Vec<Poll>> {
+    let mut limit: usize = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    if remove_hard_cap {
+        limit = usize::MAX;
+    }
+
+    let (start, end, order_by) = match order_by {
+        Some(OrderBy::Asc) => (calc_range_start(start_after), None, OrderBy::Asc),
+        _ => (None, calc_range_end(start_after), OrderBy::Desc),
+    };
+
+    if let Some(status) = filter {
+        let poll_indexer: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+            storage,
+            &[PREFIX_POLL_INDEXER, status.to_string().as_bytes()],
+        );
+        poll_indexer
+            .range(start.as_deref(), end.as_deref(), order_by.into())
+            .take(limit)
+            .map(|item| {
+                let (k, _) = item?;
+                read_poll(storage, bytes_to_u64(&k)?)
+            })
+            .collect()
+    } else {
+        let polls: ReadonlyBucket<Poll> = ReadonlyBucket::new(storage, PREFIX_POLL);
+        polls
+            .range(start.as_deref(), end.as_deref(), order_by.into())
+            .take(limit)
+            .map(|item| {
+                let (_, v) = item?;
+                Ok(v)
+            })
+            .collect()
+    }
+}
+
+pub fn read_shares(
+    storage: &dyn Storage,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<Vec<(CanonicalAddr, TokenManager)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (start, end, order_by) = match order_by {
+        Some(OrderBy::Asc) => (calc_range_start_addr(start_after), None, OrderBy::Asc),
+        _ => (None, calc_range_end_addr(start_after), OrderBy::Desc),
+    };
+
+    let bank: ReadonlyBucket<TokenManager> = ReadonlyBucket::new(storage, PREFIX_BANK);
+    bank.range(start.as_deref(), end.as_deref(), order_by.into())
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+fn bytes_to_u64(data: &[u8]) -> StdResult<u64> {
+    match data[0..8].try_into() {
+        Ok(bytes) => Ok(u64::from_be_bytes(bytes)),
+        Err(_) => Err(StdError::generic_err(
+            "Corrupted data found. 8 byte expected.",
+        )),
+    }
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<u64>) -> Option<Vec<u8>> {
+    start_after.map(|id| {
+        let mut v = id.to_be_bytes().to_vec();
+        v.push(1);
+        v
+    })
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_end(start_after: Option<u64>) -> Option<Vec<u8>> {
+    start_after.map(|id| id.to_be_bytes().to_vec())
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start_addr(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| {
+        let mut v = addr.as_slice().to_vec();
+        v.push(1);
+        v
+    })
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_end_addr(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| addr.as_slice().to_vec())
+}
@@ -0,0 +1,1206 @@
+use crate::contract::{execute, instantiate, query, reply};
+use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Binary, CosmosMsg, Decimal, Env, OwnedDeps, Reply, ReplyOn,
+    StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use melange_protocol::gov::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollAdminAction, PollConfig,
+    PollExecuteMsg, PollResponse, PollStatus, QueryMsg, StakerResponse, StateResponse, VoteOption,
+    VoterInfo,
+};
+
+const VOTING_TOKEN: &str = "voting_token";
+const TEST_CREATOR: &str = "creator";
+const TEST_VOTER: &str = "voter1";
+const TEST_VOTER_2: &str = "voter2";
+const ADMIN_MANAGER: &str = "admin_manager";
+const DEFAULT_PROPOSAL_DEPOSIT: u128 = 100u128;
+const DEFAULT_VOTING_PERIOD: u64 = 20000u64;
+const DEFAULT_EFFECTIVE_DELAY: u64 = 10000u64;
+const DEFAULT_SNAPSHOT_PERIOD: u64 = 10u64;
+const DEFAULT_POLL_GAS_LIMIT: u64 = 1_000_000u64;
+
+fn default_poll_config() -> PollConfig {
+    PollConfig {
+        proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+        voting_period: DEFAULT_VOTING_PERIOD,
+        quorum: Decimal::percent(30),
+        threshold: Decimal::percent(50),
+    }
+}
+
+fn migration_poll_config() -> PollConfig {
+    PollConfig {
+        proposal_deposit: Uint128::from(200u128),
+        voting_period: 10000u64,
+        quorum: Decimal::percent(40),
+        threshold: Decimal::percent(60),
+    }
+}
+
+fn auth_admin_poll_config() -> PollConfig {
+    PollConfig {
+        proposal_deposit: Uint128::from(300u128),
+        voting_period: 30000u64,
+        quorum: Decimal::percent(50),
+        threshold: Decimal::percent(70),
+    }
+}
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        melange_token: VOTING_TOKEN.to_string(),
+        effective_delay: DEFAULT_EFFECTIVE_DELAY,
+        default_poll_config: default_poll_config(),
+        migration_poll_config: migration_poll_config(),
+        auth_admin_poll_config: auth_admin_poll_config(),
+        voter_weight: Decimal::percent(50),
+        snapshot_period: DEFAULT_SNAPSHOT_PERIOD,
+        admin_manager: ADMIN_MANAGER.to_string(),
+        poll_gas_limit: DEFAULT_POLL_GAS_LIMIT,
+    }
+}
+
+fn mock_instantiate(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) {
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg())
+        .expect("contract successfully handles InstantiateMsg");
+}
+
+fn mock_env_after(seconds: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(seconds);
+    env
+}
+
+fn set_contract_balance(
+    deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    balance: u128,
+) {
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(balance))],
+    )]);
+}
+
+fn create_poll_msg(
+    deposit: u128,
+    execute_msg: Option<PollExecuteMsg>,
+    admin_action: Option<PollAdminAction>,
+) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(deposit),
+        msg: to_binary(&Cw20HookMsg::CreatePoll {
+            title: "test".to_string(),
+            description: "test".to_string(),
+            link: None,
+            execute_msg,
+            admin_action,
+        })
+        .unwrap(),
+    })
+}
+
+fn stake_msg(staker: &str, amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: staker.to_string(),
+        amount: Uint128::from(amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    })
+}
+
+fn vote_msg(poll_id: u64, vote: VoteOption, amount: u128) -> ExecuteMsg {
+    ExecuteMsg::CastVote {
+        poll_id,
+        vote,
+        amount: Uint128::from(amount),
+    }
+}
+
+fn query_poll(
+    deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    poll_id: u64,
+) -> PollResponse {
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id }).unwrap();
+    from_binary(&res).unwrap()
+}
+
+fn query_state(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> StateResponse {
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    from_binary(&res).unwrap()
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: TEST_CREATOR.to_string(),
+            melange_token: VOTING_TOKEN.to_string(),
+            effective_delay: DEFAULT_EFFECTIVE_DELAY,
+            default_poll_config: default_poll_config(),
+            migration_poll_config: migration_poll_config(),
+            auth_admin_poll_config: auth_admin_poll_config(),
+            voter_weight: Decimal::percent(50),
+            snapshot_period: DEFAULT_SNAPSHOT_PERIOD,
+            admin_manager: ADMIN_MANAGER.to_string(),
+            poll_gas_limit: DEFAULT_POLL_GAS_LIMIT,
+        }
+    );
+
+    assert_eq!(
+        query_state(&deps),
+        StateResponse {
+            poll_count: 0,
+            total_share: Uint128::zero(),
+            total_deposit: Uint128::zero(),
+            pending_voting_rewards: Uint128::zero(),
+        }
+    );
+
+    // invalid poll config
+    let mut msg = instantiate_msg();
+    msg.default_poll_config.quorum = Decimal::percent(101);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg);
+    assert_eq!(res, Err(StdError::generic_err("quorum must be 0 to 1")));
+
+    let mut msg = instantiate_msg();
+    msg.voter_weight = Decimal::one();
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg);
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("voter_weight must be smaller than 1"))
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("addr0001".to_string()),
+        effective_delay: Some(20000u64),
+        default_poll_config: None,
+        migration_poll_config: None,
+        auth_admin_poll_config: None,
+        voter_weight: Some(Decimal::percent(10)),
+        snapshot_period: None,
+        admin_manager: None,
+        poll_gas_limit: Some(2_000_000u64),
+    };
+
+    // unauthorized attempt
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(StdError::generic_err("unauthorized")));
+
+    let _res = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, "addr0001".to_string());
+    assert_eq!(config.effective_delay, 20000u64);
+    assert_eq!(config.voter_weight, Decimal::percent(10));
+    assert_eq!(config.poll_gas_limit, 2_000_000u64);
+}
+
+#[test]
+fn create_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    // only the voting token can call the hook
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        create_poll_msg(DEFAULT_PROPOSAL_DEPOSIT, None, None),
+    );
+    assert_eq!(res, Err(StdError::generic_err("unauthorized")));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(DEFAULT_PROPOSAL_DEPOSIT - 1, None, None),
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Must deposit more than 100 token"))
+    );
+
+    // admin actions require the deposit of their own poll config
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(
+            DEFAULT_PROPOSAL_DEPOSIT,
+            None,
+            Some(PollAdminAction::AuthorizeClaim {
+                authorized_addr: "addr0000".to_string(),
+            }),
+        ),
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Must deposit more than 300 token"))
+    );
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: to_binary(&Cw20HookMsg::CreatePoll {
+            title: "a".to_string(),
+            description: "test".to_string(),
+            link: None,
+            execute_msg: None,
+            admin_action: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg);
+    assert_eq!(res, Err(StdError::generic_err("Title too short")));
+
+    let execute_msg = PollExecuteMsg {
+        contract: "contract0000".to_string(),
+        msg: Binary::from(b"{}".to_vec()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(DEFAULT_PROPOSAL_DEPOSIT, Some(execute_msg.clone()), None),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_poll"),
+            attr("creator", TEST_CREATOR),
+            attr("poll_id", "1"),
+            attr(
+                "end_time",
+                (mock_env().block.time.seconds() + DEFAULT_VOTING_PERIOD).to_string()
+            ),
+        ]
+    );
+
+    let poll = query_poll(&deps, 1u64);
+    assert_eq!(poll.status, PollStatus::InProgress);
+    assert_eq!(poll.creator, TEST_CREATOR.to_string());
+    assert_eq!(poll.execute_data, Some(execute_msg));
+    assert_eq!(poll.deposit_amount, Uint128::from(DEFAULT_PROPOSAL_DEPOSIT));
+
+    let state = query_state(&deps);
+    assert_eq!(state.poll_count, 1u64);
+    assert_eq!(state.total_deposit, Uint128::from(DEFAULT_PROPOSAL_DEPOSIT));
+}
+
+#[test]
+fn happy_days_end_and_execute_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    let execute_msg = PollExecuteMsg {
+        contract: "contract0000".to_string(),
+        msg: Binary::from(b"{}".to_vec()),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(DEFAULT_PROPOSAL_DEPOSIT, Some(execute_msg), None),
+    )
+    .unwrap();
+
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 1000u128);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        stake_msg(TEST_VOTER, 1000u128),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "staking"),
+            attr("sender", TEST_VOTER),
+            attr("share", "1000"),
+            attr("amount", "1000"),
+        ]
+    );
+
+    // cannot vote with more than the staked amount
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        vote_msg(1u64, VoteOption::Yes, 1001u128),
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err(
+            "User does not have enough staked tokens."
+        ))
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        vote_msg(1u64, VoteOption::Yes, 1000u128),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "1000"),
+            attr("voter", TEST_VOTER),
+            attr("vote_option", "yes"),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        vote_msg(1u64, VoteOption::Yes, 1000u128),
+    );
+    assert_eq!(res, Err(StdError::generic_err("User has already voted.")));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Voting period has not expired"))
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+    // the deposit is refunded
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+        }))]
+    );
+    set_contract_balance(&mut deps, 1000u128);
+
+    let poll = query_poll(&deps, 1u64);
+    assert_eq!(poll.status, PollStatus::Passed);
+    assert_eq!(
+        poll.total_balance_at_end_poll,
+        Some(Uint128::from(1000u128))
+    );
+    assert_eq!(query_state(&deps).total_deposit, Uint128::zero());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD + DEFAULT_EFFECTIVE_DELAY - 1),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1 },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Effective delay has not expired"))
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD + DEFAULT_EFFECTIVE_DELAY),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 1u64,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "contract0000".to_string(),
+                msg: Binary::from(b"{}".to_vec()),
+                funds: vec![],
+            }),
+            gas_limit: Some(DEFAULT_POLL_GAS_LIMIT),
+            reply_on: ReplyOn::Error,
+        }]
+    );
+    assert_eq!(query_poll(&deps, 1u64).status, PollStatus::Executed);
+
+    // a failing execution marks the poll as failed
+    let _res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1u64,
+            result: SubMsgResult::Err("error".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(query_poll(&deps, 1u64).status, PollStatus::Failed);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD + DEFAULT_EFFECTIVE_DELAY),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1 },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Poll is not in passed status"))
+    );
+}
+
+#[test]
+fn end_poll_rejected() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    for _ in 0..2 {
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(VOTING_TOKEN, &[]),
+            create_poll_msg(DEFAULT_PROPOSAL_DEPOSIT, None, None),
+        )
+        .unwrap();
+    }
+
+    set_contract_balance(&mut deps, 2 * DEFAULT_PROPOSAL_DEPOSIT + 1000u128);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        stake_msg(TEST_VOTER, 1000u128),
+    )
+    .unwrap();
+
+    // 10% participation does not reach the 30% quorum
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        vote_msg(1u64, VoteOption::Yes, 100u128),
+    )
+    .unwrap();
+    // quorum reached, but the yes votes do not reach the 50% threshold
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        vote_msg(2u64, VoteOption::No, 500u128),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Quorum not reached"),
+            attr("passed", "false"),
+        ]
+    );
+    // the deposit is kept for the stakers
+    assert_eq!(res.messages, vec![]);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 2 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "2"),
+            attr("rejected_reason", "Threshold not reached"),
+            attr("passed", "false"),
+        ]
+    );
+    assert_eq!(res.messages.len(), 1);
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 1000u128);
+
+    assert_eq!(query_poll(&deps, 1u64).status, PollStatus::Rejected);
+    assert_eq!(query_poll(&deps, 2u64).status, PollStatus::Rejected);
+
+    // the forfeited deposit is added to the staked balance
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let staker: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(staker.balance, Uint128::from(1100u128));
+    assert_eq!(staker.locked_balance, vec![]);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD + DEFAULT_EFFECTIVE_DELAY),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1 },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Poll is not in passed status"))
+    );
+}
+
+#[test]
+fn snapshot_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(DEFAULT_PROPOSAL_DEPOSIT, None, None),
+    )
+    .unwrap();
+
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 1000u128);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        stake_msg(TEST_VOTER, 1000u128),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Cannot snapshot at this time"))
+    );
+
+    let env = mock_env_after(DEFAULT_VOTING_PERIOD - DEFAULT_SNAPSHOT_PERIOD);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "snapshot_poll"),
+            attr("poll_id", "1"),
+            attr("staked_amount", "1000"),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::SnapshotPoll { poll_id: 1 },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Snapshot has already occurred"))
+    );
+
+    // stakes after the snapshot do not count for the quorum
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 11000u128);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        stake_msg(TEST_VOTER_2, 10000u128),
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_VOTER, &[]),
+        vote_msg(1u64, VoteOption::Yes, 1000u128),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("passed", "true"));
+    assert_eq!(
+        query_poll(&deps, 1u64).total_balance_at_end_poll,
+        Some(Uint128::from(1000u128))
+    );
+}
+
+#[test]
+fn withdraw_voting_tokens() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(DEFAULT_PROPOSAL_DEPOSIT, None, None),
+    )
+    .unwrap();
+
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 1000u128);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        stake_msg(TEST_VOTER, 1000u128),
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        vote_msg(1u64, VoteOption::Yes, 600u128),
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let staker: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        staker,
+        StakerResponse {
+            balance: Uint128::from(1000u128),
+            share: Uint128::from(1000u128),
+            locked_balance: vec![(
+                1u64,
+                VoterInfo {
+                    vote: VoteOption::Yes,
+                    balance: Uint128::from(600u128),
+                }
+            )],
+            withdrawable_polls: vec![],
+            pending_voting_rewards: Uint128::zero(),
+        }
+    );
+
+    // the voted amount is locked until the poll ends
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::WithdrawVotingTokens {
+            amount: Some(Uint128::from(401u128)),
+        },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err(
+            "User is trying to withdraw too many tokens."
+        ))
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::WithdrawVotingTokens { amount: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(400u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 600u128);
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    )
+    .unwrap();
+    set_contract_balance(&mut deps, 600u128);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::WithdrawVotingTokens { amount: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw"),
+            attr("recipient", TEST_VOTER),
+            attr("amount", "600"),
+        ]
+    );
+    assert_eq!(query_state(&deps).total_share, Uint128::zero());
+
+    // the emptied staker has nothing left to withdraw
+    set_contract_balance(&mut deps, 0u128);
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::WithdrawVotingTokens {
+            amount: Some(Uint128::from(1u128)),
+        },
+    );
+    assert_eq!(res, Err(StdError::generic_err("Nothing staked")));
+}
+
+#[test]
+fn voting_rewards() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(DEFAULT_PROPOSAL_DEPOSIT, None, None),
+    )
+    .unwrap();
+
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 2000u128);
+    for voter in [TEST_VOTER, TEST_VOTER_2] {
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(VOTING_TOKEN, &[]),
+            stake_msg(voter, 1000u128),
+        )
+        .unwrap();
+    }
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        vote_msg(1u64, VoteOption::Yes, 1000u128),
+    )
+    .unwrap();
+
+    // half of the reward goes to the voters of the poll in progress
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 2200u128);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: TEST_CREATOR.to_string(),
+            amount: Uint128::from(200u128),
+            msg: to_binary(&Cw20HookMsg::DepositReward {}).unwrap(),
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "deposit_reward"), attr("amount", "200")]
+    );
+    assert_eq!(
+        query_poll(&deps, 1u64).voters_reward,
+        Uint128::from(100u128)
+    );
+    assert_eq!(
+        query_state(&deps).pending_voting_rewards,
+        Uint128::from(100u128)
+    );
+
+    // rewards can only be withdrawn once the poll ends
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::WithdrawVotingRewards { poll_id: None },
+    );
+    assert_eq!(res, Err(StdError::generic_err("Nothing to withdraw")));
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    )
+    .unwrap();
+    set_contract_balance(&mut deps, 2200u128);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let staker: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(staker.balance, Uint128::from(1050u128));
+    assert_eq!(
+        staker.withdrawable_polls,
+        vec![(1u64, Uint128::from(100u128))]
+    );
+    assert_eq!(staker.pending_voting_rewards, Uint128::from(100u128));
+
+    // voter2 did not vote
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::StakeVotingRewards { poll_id: None },
+    );
+    assert_eq!(res, Err(StdError::generic_err("Nothing to withdraw")));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::StakeVotingRewards {
+            poll_id: Some(1u64),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "stake_voting_rewards"),
+            attr("staker", TEST_VOTER),
+            attr("share", "95"),
+            attr("amount", "100"),
+        ]
+    );
+
+    let state = query_state(&deps);
+    assert_eq!(state.pending_voting_rewards, Uint128::zero());
+    assert_eq!(state.total_share, Uint128::from(2095u128));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::WithdrawVotingRewards { poll_id: None },
+    );
+    assert_eq!(res, Err(StdError::generic_err("Nothing to withdraw")));
+}
+
+#[test]
+fn withdraw_voting_rewards() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(DEFAULT_PROPOSAL_DEPOSIT, None, None),
+    )
+    .unwrap();
+
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 1000u128);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        stake_msg(TEST_VOTER, 1000u128),
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        vote_msg(1u64, VoteOption::Abstain, 1000u128),
+    )
+    .unwrap();
+
+    set_contract_balance(&mut deps, DEFAULT_PROPOSAL_DEPOSIT + 1100u128);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: TEST_CREATOR.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::DepositReward {}).unwrap(),
+        }),
+    )
+    .unwrap();
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env_after(DEFAULT_VOTING_PERIOD),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::EndPoll { poll_id: 1 },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::WithdrawVotingRewards {
+            poll_id: Some(1u64),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(query_state(&deps).pending_voting_rewards, Uint128::zero());
+
+    // the vote is cleared once the rewards are withdrawn
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Voter {
+            poll_id: 1u64,
+            address: TEST_VOTER.to_string(),
+        },
+    );
+    assert_eq!(res, Err(StdError::generic_err("Voter not found")));
+}
+
+#[test]
+fn admin_action_polls() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(&mut deps);
+
+    let migrations = vec![(
+        "contract0000".to_string(),
+        12u64,
+        Binary::from(b"{}".to_vec()),
+    )];
+    let migration_action = PollAdminAction::ExecuteMigrations {
+        migrations: migrations.clone(),
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(
+            200u128,
+            Some(PollExecuteMsg {
+                contract: "contract0000".to_string(),
+                msg: Binary::from(b"{}".to_vec()),
+            }),
+            Some(migration_action.clone()),
+        ),
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err(
+            "Poll can not contain both execute_msg and admin_action"
+        ))
+    );
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(200u128, None, Some(migration_action.clone())),
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg(
+            200u128,
+            None,
+            Some(PollAdminAction::UpdateConfig {
+                owner: None,
+                effective_delay: Some(5000u64),
+                default_poll_config: None,
+                migration_poll_config: None,
+                auth_admin_poll_config: None,
+                voter_weight: None,
+                snapshot_period: None,
+                admin_manager: None,
+            }),
+        ),
+    )
+    .unwrap();
+
+    // migration polls use the migration voting period
+    let poll = query_poll(&deps, 1u64);
+    assert_eq!(poll.end_time, mock_env().block.time.seconds() + 10000u64);
+
+    set_contract_balance(&mut deps, 400u128 + 1000u128);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(VOTING_TOKEN, &[]),
+        stake_msg(TEST_VOTER, 1000u128),
+    )
+    .unwrap();
+    for poll_id in [1u64, 2u64] {
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(TEST_VOTER, &[]),
+            vote_msg(poll_id, VoteOption::Yes, 1000u128),
+        )
+        .unwrap();
+
+        let _res = execute(
+            deps.as_mut(),
+            mock_env_after(10000u64),
+            mock_info(TEST_VOTER, &[]),
+            ExecuteMsg::EndPoll { poll_id },
+        )
+        .unwrap();
+    }
+
+    // migrations wait for the effective delay like every poll
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(10000u64),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1 },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Effective delay has not expired"))
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(10000u64 + DEFAULT_EFFECTIVE_DELAY),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 1u64,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: ADMIN_MANAGER.to_string(),
+                msg: to_binary(&migration_action).unwrap(),
+                funds: vec![],
+            }),
+            gas_limit: Some(DEFAULT_POLL_GAS_LIMIT),
+            reply_on: ReplyOn::Error,
+        }]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(10000u64),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 2 },
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("Effective delay has not expired"))
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(10000u64 + DEFAULT_EFFECTIVE_DELAY),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 2 },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.effective_delay, 5000u64);
+    assert_eq!(query_poll(&deps, 2u64).status, PollStatus::Executed);
+
+    // successful replies are not expected
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 2u64,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    );
+    assert_eq!(res, Err(StdError::generic_err("reply id is invalid")));
+}
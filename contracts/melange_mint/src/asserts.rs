@@ -2,78 +2,197 @@ use std::str::FromStr;
 
 use crate::{
     contract::MIN_CR_ALLOWED,
-    state::{AssetConfig, Position},
+    error::ContractError,
+    state::{
+        read_deposit_utilization, read_mint_utilization, read_pause_info, read_position_approval,
+        AssetConfig, Position,
+    },
 };
-use cosmwasm_std::{Decimal, Deps, Env, StdError, StdResult};
-use terraswap::asset::{Asset, AssetInfo};
+use cosmwasm_std::{Addr, BlockInfo, Decimal, Deps, Storage, Uint128};
+use melange_protocol::mint::PauseOperation;
 use sei_cosmwasm::SeiQueryWrapper;
+use terraswap::asset::{Asset, AssetInfoRaw};
 
-// Check zero balance & same collateral with position
+// Check zero balance & the collateral is held by the position
 pub fn assert_collateral(
     deps: Deps<SeiQueryWrapper>,
     position: &Position,
-    collateral: &Asset
-) -> StdResult<()> {
-    if !collateral
-        .info
-        .equal(&position.collateral.info.to_normal(deps.api)?)
+    collateral: &Asset,
+) -> Result<(), ContractError> {
+    let collateral_info_raw = collateral.info.to_raw(deps.api)?;
+    if !std::iter::once(&position.collateral)
+        .chain(position.other_collaterals.iter())
+        .any(|position_collateral| position_collateral.info == collateral_info_raw)
         || collateral.amount.is_zero()
     {
-        return Err(StdError::generic_err("Wrong collateral"));
+        return Err(ContractError::WrongCollateral {});
     }
 
     Ok(())
 }
 
 // Check zero balance & same asset with position
-pub fn assert_asset(deps: Deps<SeiQueryWrapper>, position: &Position, asset: &Asset) -> StdResult<()> {
+pub fn assert_asset(
+    deps: Deps<SeiQueryWrapper>,
+    position: &Position,
+    asset: &Asset,
+) -> Result<(), ContractError> {
     if !asset.info.equal(&position.asset.info.to_normal(deps.api)?) || asset.amount.is_zero() {
-        return Err(StdError::generic_err("Wrong asset"));
+        return Err(ContractError::WrongAsset {});
     }
 
     Ok(())
 }
 
-pub fn assert_migrated_asset(asset_config: &AssetConfig) -> StdResult<()> {
+// Check the sender owns the position or holds an unexpired approval of its owner
+pub fn assert_position_operator(
+    deps: Deps<SeiQueryWrapper>,
+    block: &BlockInfo,
+    position: &Position,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let sender_raw = deps.api.addr_canonicalize(sender.as_str())?;
+    if sender_raw == position.owner {
+        return Ok(());
+    }
+
+    match read_position_approval(deps.storage, &position.owner, &sender_raw) {
+        Ok(expires) if !expires.is_expired(block) => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+pub fn assert_migrated_asset(asset_config: &AssetConfig) -> Result<(), ContractError> {
     if asset_config.end_price.is_some() {
-        return Err(StdError::generic_err(
-            "Operation is not allowed for the deprecated asset",
-        ));
+        return Err(ContractError::DeprecatedAsset {});
+    }
+
+    Ok(())
+}
+
+// Check the minting period of a pre-IPO asset has not ended
+pub fn assert_mint_period(
+    asset_config: &AssetConfig,
+    block_time: u64,
+) -> Result<(), ContractError> {
+    match &asset_config.ipo_params {
+        Some(ipo_params) if ipo_params.mint_end < block_time => Err(ContractError::MintPeriodEnded {
+            mint_end: ipo_params.mint_end,
+        }),
+        _ => Ok(()),
+    }
+}
+
+// Check the asset is not pre-IPO, its positions cannot be burned or withdrawn until the IPO
+pub fn assert_not_pre_ipo(asset_config: &AssetConfig) -> Result<(), ContractError> {
+    if asset_config.ipo_params.is_some() {
+        return Err(ContractError::PreIpoAsset {});
     }
 
     Ok(())
 }
 
+// Check the positions of a pre-IPO asset only hold the base denom
+pub fn assert_pre_ipo_collateral(
+    base_denom: &str,
+    asset_config: &AssetConfig,
+    collateral_info: &AssetInfoRaw,
+) -> Result<(), ContractError> {
+    if asset_config.ipo_params.is_none() {
+        return Ok(());
+    }
+
+    match collateral_info {
+        AssetInfoRaw::NativeToken { denom } if denom == base_denom => Ok(()),
+        _ => Err(ContractError::PreIpoCollateral {}),
+    }
+}
+
 pub fn assert_revoked_collateral(
     load_collateral_res: (Decimal, Decimal, bool),
-) -> StdResult<(Decimal, Decimal)> {
+) -> Result<(Decimal, Decimal), ContractError> {
     if load_collateral_res.2 {
-        println!("**********************************WoW-1");
-        return Err(StdError::generic_err(
-            "The collateral asset provided is no longer valid",
-        ));
+        return Err(ContractError::RevokedCollateral {});
     }
 
     Ok((load_collateral_res.0, load_collateral_res.1))
 }
 
-pub fn assert_min_collateral_ratio(min_collateral_ratio: Decimal) -> StdResult<()> {
+pub fn assert_auction_discount(auction_discount: Decimal) -> Result<(), ContractError> {
+    if auction_discount > Decimal::one() {
+        Err(ContractError::InvalidAuctionDiscount {})
+    } else {
+        Ok(())
+    }
+}
+
+pub fn assert_min_collateral_ratio(min_collateral_ratio: Decimal) -> Result<(), ContractError> {
     if min_collateral_ratio < Decimal::from_str(MIN_CR_ALLOWED)? {
-        Err(StdError::generic_err(format!(
-            "min_collateral_ratio must be bigger or equal than {}",
-            MIN_CR_ALLOWED
-        )))
+        Err(ContractError::InvalidMinCollateralRatio {
+            min_collateral_ratio: MIN_CR_ALLOWED.to_string(),
+        })
     } else {
         Ok(())
     }
 }
 
-pub fn assert_protocol_fee(protocol_fee_rate: Decimal) -> StdResult<Decimal> {
+pub fn assert_protocol_fee(protocol_fee_rate: Decimal) -> Result<Decimal, ContractError> {
     if protocol_fee_rate >= Decimal::one() {
-        Err(StdError::generic_err(
-            "protocol_fee_rate must be smaller than 1",
-        ))
+        Err(ContractError::InvalidProtocolFeeRate {})
     } else {
         Ok(protocol_fee_rate)
     }
 }
+
+pub fn assert_stability_fee_rate(stability_fee_rate: Decimal) -> Result<(), ContractError> {
+    if stability_fee_rate >= Decimal::one() {
+        Err(ContractError::InvalidStabilityFeeRate {})
+    } else {
+        Ok(())
+    }
+}
+
+// Check the operation is paused neither for the asset nor globally
+pub fn assert_not_paused(
+    storage: &dyn Storage,
+    asset_info: &AssetInfoRaw,
+    operation: PauseOperation,
+) -> Result<(), ContractError> {
+    if read_pause_info(storage, None)?.is_paused(&operation)
+        || read_pause_info(storage, Some(asset_info))?.is_paused(&operation)
+    {
+        return Err(ContractError::OperationPaused {});
+    }
+
+    Ok(())
+}
+
+// Check minting the amount keeps the total minted amount of the asset within its cap
+pub fn assert_mint_cap(
+    storage: &dyn Storage,
+    asset_info: &AssetInfoRaw,
+    mint_amount: Uint128,
+) -> Result<(), ContractError> {
+    let utilization = read_mint_utilization(storage, asset_info)?;
+    match utilization.cap {
+        Some(cap) if utilization.total.checked_add(mint_amount)? > cap => {
+            Err(ContractError::MintCapExceeded {})
+        }
+        _ => Ok(()),
+    }
+}
+
+// Check depositing the amount keeps the total deposited amount of the collateral within its cap
+pub fn assert_deposit_cap(
+    storage: &dyn Storage,
+    collateral_info: &AssetInfoRaw,
+    deposit_amount: Uint128,
+) -> Result<(), ContractError> {
+    let utilization = read_deposit_utilization(storage, collateral_info)?;
+    match utilization.cap {
+        Some(cap) if utilization.total.checked_add(deposit_amount)? > cap => {
+            Err(ContractError::DepositCapExceeded {})
+        }
+        _ => Ok(()),
+    }
+}
@@ -1,39 +1,49 @@
 use crate::{
-    asserts::{assert_min_collateral_ratio, assert_protocol_fee},
-    migration::migrate_asset_configs,
+    asserts::{
+        assert_auction_discount, assert_min_collateral_ratio, assert_protocol_fee,
+        assert_stability_fee_rate,
+    },
+    error::ContractError,
+    migration::{migrate_storage, update_contract_version, CONTRACT_NAME, CONTRACT_VERSION},
     positions::{
-        burn, deposit, mint, open_position, query_next_position_idx, query_position,
-        query_positions, withdraw,
+        accrue_stability_fee, approve_position, auction, burn, close_position, deposit,
+        lock_position_funds_hook, mint, open_position, query_next_position_idx, query_position,
+        query_position_approval, query_position_health, query_position_lock_info, query_positions,
+        query_positions_health, revoke_position, stability_fee_index, transfer_position,
+        unlock_position_funds, withdraw,
+    },
+    simulation::{
+        query_simulate_burn, query_simulate_mint, query_simulate_open_position,
+        query_simulate_withdraw,
     },
+    math::decimal_from_ratio,
     state::{
-        read_asset_config, read_config, store_asset_config, store_config, store_position_idx,
-        AssetConfig, Config,
+        count_position_utilizations, read_asset_config, read_asset_info, read_config,
+        read_deposit_utilization, read_mint_utilization, read_pause_info,
+        read_utilization_cursor, store_asset_config, store_config, store_deposit_utilization,
+        store_mint_utilization, store_pause_info, store_position_idx, AssetConfig, Config,
+        IpoParams, PauseInfo, Utilization,
     },
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut,
-    Env, MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Response, StdResult, Uint128, WasmMsg,
 };
+use cw2::set_contract_version;
 use cw20::Cw20ReceiveMsg;
 use melange_protocol::mint::{
     AssetConfigResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
-    QueryMsg,
+    IpoParams as IpoParamsMsg, PauseInfoResponse, PauseOperation, QueryMsg, UtilizationResponse,
 };
 use melange_protocol::{
     collateral_oracle::{ExecuteMsg as CollateralOracleExecuteMsg, SourceType},
     mint::MigrateMsg,
 };
-use terraswap::asset::{Asset, AssetInfo};
-
-use sei_cosmwasm::{
-    BulkOrderPlacementsResponse, ContractOrderResult, DepositInfo, DexTwapsResponse, EpochResponse,
-    ExchangeRatesResponse, GetLatestPriceResponse, GetOrderByIdResponse, GetOrdersResponse,
-    LiquidationRequest, LiquidationResponse, MsgPlaceOrdersResponse, OracleTwapsResponse, Order,
-    OrderSimulationResponse, OrderType, PositionDirection, SeiMsg, SeiQuerier, SeiQueryWrapper,
-    SettlementEntry, SudoMsg,
-};
+use terraswap::asset::{Asset, AssetInfo, AssetInfoRaw};
+
+use sei_cosmwasm::{SeiMsg, SeiQueryWrapper};
 
 pub const MIN_CR_ALLOWED: &str = "1.1";
 
@@ -43,7 +53,7 @@ pub fn instantiate(
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config = Config {
         owner: deps.api.addr_canonicalize(&msg.owner)?,
         oracle: deps.api.addr_canonicalize(&msg.oracle)?,
@@ -54,8 +64,12 @@ pub fn instantiate(
         base_denom: msg.base_denom,
         token_code_id: msg.token_code_id,
         protocol_fee_rate: assert_protocol_fee(msg.protocol_fee_rate)?,
+        lock_period: msg.lock_period,
+        position_nft: None,
+        guardian: None,
     };
 
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     store_config(deps.storage, &config)?;
     store_position_idx(deps.storage, Uint128::from(1u128))?;
     Ok(Response::default())
@@ -64,12 +78,12 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut<SeiQueryWrapper>,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg
-) -> StdResult<Response> {
+) -> Result<Response<SeiMsg>, ContractError> {
     match msg {
-        ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::UpdateConfig {
             owner,
             oracle,
@@ -79,6 +93,9 @@ pub fn execute(
             token_code_id,
             protocol_fee_rate,
             staking,
+            lock_period,
+            position_nft,
+            guardian,
         } => update_config(
             deps,
             info,
@@ -90,46 +107,86 @@ pub fn execute(
             token_code_id,
             protocol_fee_rate,
             staking,
+            lock_period,
+            position_nft,
+            guardian,
         ),
         ExecuteMsg::UpdateAsset {
             asset_token,
+            auction_discount,
             min_collateral_ratio,
-        } => {
-            let asset_addr = deps.api.addr_validate(asset_token.as_str())?;
-            update_asset(
-                deps,
-                info,
-                asset_addr,
-                min_collateral_ratio,
-            )
-        }
+            stability_fee_rate,
+            ipo_params,
+        } => update_asset(
+            deps,
+            env,
+            info,
+            asset_token,
+            auction_discount,
+            min_collateral_ratio,
+            stability_fee_rate,
+            ipo_params,
+        ),
+        ExecuteMsg::UpdateMintCap {
+            asset_token,
+            mint_cap,
+        } => update_mint_cap(deps, info, asset_token, mint_cap),
+        ExecuteMsg::UpdateDepositCap {
+            collateral,
+            deposit_cap,
+        } => update_deposit_cap(deps, info, collateral, deposit_cap),
         ExecuteMsg::RegisterAsset {
             asset_token,
+            auction_discount,
             min_collateral_ratio,
+            ipo_params,
         } => {
             let asset_addr = deps.api.addr_validate(asset_token.as_str())?;
             register_asset(
                 deps,
                 info,
                 asset_addr,
+                auction_discount,
                 min_collateral_ratio,
+                ipo_params,
             )
         }
+        ExecuteMsg::RegisterNativeAsset {
+            subdenom,
+            auction_discount,
+            min_collateral_ratio,
+            ipo_params,
+        } => register_native_asset(
+            deps,
+            env,
+            info,
+            subdenom,
+            auction_discount,
+            min_collateral_ratio,
+            ipo_params,
+        ),
         ExecuteMsg::RegisterMigration {
             asset_token,
             end_price,
-        } => {
-            let asset_addr = deps.api.addr_validate(asset_token.as_str())?;
-            register_migration(deps, info, asset_addr, end_price)
-        }
+        } => register_migration(deps, env, info, asset_token, end_price),
+        ExecuteMsg::TriggerIpo { asset_token } => trigger_ipo(deps, info, asset_token),
+        ExecuteMsg::Pause {
+            asset_token,
+            operations,
+        } => update_pause_info(deps, info, asset_token, operations, true),
+        ExecuteMsg::Unpause {
+            asset_token,
+            operations,
+        } => update_pause_info(deps, info, asset_token, operations, false),
         ExecuteMsg::OpenPosition {
             collateral,
             asset_info,
             collateral_ratio,
+            short_params,
         } => {
             // only native token can be deposited directly
             if !collateral.is_native_token() {
-                return Err(StdError::generic_err("unauthorized"));
+                return Err(ContractError::Unauthorized {});
             }
 
             // Check the actual deposit happens
@@ -137,10 +194,12 @@ pub fn execute(
 
             open_position(
                 deps,
+                env,
                 info.sender,
                 collateral,
                 asset_info,
                 collateral_ratio,
+                short_params,
             )
         }
         ExecuteMsg::Deposit {
@@ -149,30 +208,106 @@ pub fn execute(
         } => {
             // only native token can be deposited directly
             if !collateral.is_native_token() {
-                return Err(StdError::generic_err("unauthorized"));
+                return Err(ContractError::Unauthorized {});
             }
 
             // Check the actual deposit happens
             collateral.assert_sent_native_token_balance(&info)?;
 
-            deposit(deps, info.sender, position_idx, collateral)
+            deposit(deps, env, info.sender, position_idx, collateral)
         }
         ExecuteMsg::Withdraw {
             position_idx,
             collateral,
-        } => withdraw(deps, info.sender, position_idx, collateral),
+        } => withdraw(deps, env, info.sender, position_idx, collateral),
         ExecuteMsg::Mint {
             position_idx,
             asset,
-        } => mint(deps, info.sender, position_idx, asset),
+            short_params,
+        } => mint(deps, env, info.sender, position_idx, asset, short_params),
+        ExecuteMsg::Burn {
+            position_idx,
+            asset,
+        } => {
+            // cw20 assets are burnt through the receive hook
+            if !asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            // Check the actual deposit happens
+            asset.assert_sent_native_token_balance(&info)?;
+
+            burn(deps, env, info.sender, position_idx, asset)
+        }
+        ExecuteMsg::Auction {
+            position_idx,
+            asset,
+        } => {
+            // cw20 assets are auctioned through the receive hook
+            if !asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            // Check the actual deposit happens
+            asset.assert_sent_native_token_balance(&info)?;
+
+            auction(deps, env, info.sender, position_idx, asset)
+        }
+        ExecuteMsg::ClosePosition {
+            position_idx,
+            asset,
+        } => {
+            // cw20 assets are burnt through the receive hook
+            if !asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            // Check the actual deposit happens
+            asset.assert_sent_native_token_balance(&info)?;
+
+            close_position(deps, env, info.sender, position_idx, asset)
+        }
+        ExecuteMsg::UnlockPositionFunds { positions_idx } => {
+            unlock_position_funds(deps, env, info.sender, positions_idx)
+        }
+        ExecuteMsg::TransferPosition {
+            position_idx,
+            recipient,
+        } => {
+            let recipient = deps.api.addr_validate(recipient.as_str())?;
+            transfer_position(deps, env, info.sender, position_idx, recipient)
+        }
+        ExecuteMsg::ApprovePosition { spender, expires } => {
+            let spender = deps.api.addr_validate(spender.as_str())?;
+            approve_position(deps, env, info.sender, spender, expires)
+        }
+        ExecuteMsg::RevokePosition { spender } => {
+            let spender = deps.api.addr_validate(spender.as_str())?;
+            revoke_position(deps, info.sender, spender)
+        }
+        ExecuteMsg::CountUtilizations { limit } => count_utilizations(deps, limit),
+        ExecuteMsg::LockPositionFundsHook {
+            position_idx,
+            receiver,
+            prev_balance,
+        } => {
+            // only the contract itself can lock the short sale proceeds
+            if info.sender != env.contract.address {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let receiver = deps.api.addr_validate(receiver.as_str())?;
+            lock_position_funds_hook(deps, env, position_idx, receiver, prev_balance)
+        }
     }
 }
 
 pub fn receive_cw20(
     deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response<SeiMsg>, ContractError> {
     let passed_asset: Asset = Asset {
         info: AssetInfo::Token {
             contract_addr: info.sender.to_string(),
@@ -184,25 +319,36 @@ pub fn receive_cw20(
         Ok(Cw20HookMsg::OpenPosition {
                asset_info,
                collateral_ratio,
+               short_params,
            }) => {
             let cw20_sender = deps.api.addr_validate(cw20_msg.sender.as_str())?;
             open_position(
                 deps,
+                env,
                 cw20_sender,
                 passed_asset,
                 asset_info,
                 collateral_ratio,
+                short_params,
             )
         }
         Ok(Cw20HookMsg::Deposit { position_idx }) => {
             let cw20_sender = deps.api.addr_validate(cw20_msg.sender.as_str())?;
-            deposit(deps, cw20_sender, position_idx, passed_asset)
+            deposit(deps, env, cw20_sender, position_idx, passed_asset)
         }
         Ok(Cw20HookMsg::Burn { position_idx }) => {
             let cw20_sender = deps.api.addr_validate(cw20_msg.sender.as_str())?;
-            burn(deps, cw20_sender, position_idx, passed_asset)
+            burn(deps, env, cw20_sender, position_idx, passed_asset)
+        }
+        Ok(Cw20HookMsg::Auction { position_idx }) => {
+            let cw20_sender = deps.api.addr_validate(cw20_msg.sender.as_str())?;
+            auction(deps, env, cw20_sender, position_idx, passed_asset)
+        }
+        Ok(Cw20HookMsg::ClosePosition { position_idx }) => {
+            let cw20_sender = deps.api.addr_validate(cw20_msg.sender.as_str())?;
+            close_position(deps, env, cw20_sender, position_idx, passed_asset)
         }
-        Err(_) => Err(StdError::generic_err("invalid cw20 hook message")),
+        Err(_) => Err(ContractError::InvalidHookMsg {}),
     }
 }
 
@@ -218,11 +364,14 @@ pub fn update_config(
     token_code_id: Option<u64>,
     protocol_fee_rate: Option<Decimal>,
     staking: Option<String>,
-) -> StdResult<Response> {
+    lock_period: Option<u64>,
+    position_nft: Option<String>,
+    guardian: Option<String>,
+) -> Result<Response<SeiMsg>, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
     if let Some(owner) = owner {
@@ -258,22 +407,44 @@ pub fn update_config(
         config.staking = deps.api.addr_canonicalize(&staking)?;
     }
 
+    if let Some(lock_period) = lock_period {
+        config.lock_period = lock_period;
+    }
+
+    if let Some(position_nft) = position_nft {
+        config.position_nft = Some(deps.api.addr_canonicalize(&position_nft)?);
+    }
+
+    if let Some(guardian) = guardian {
+        config.guardian = Some(deps.api.addr_canonicalize(&guardian)?);
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_asset(
     deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
     info: MessageInfo,
-    asset_token: Addr,
+    asset_token: String,
+    auction_discount: Option<Decimal>,
     min_collateral_ratio: Option<Decimal>,
-) -> StdResult<Response> {
+    stability_fee_rate: Option<Decimal>,
+    ipo_params: Option<IpoParamsMsg>,
+) -> Result<Response<SeiMsg>, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let asset_token_raw = deps.api.addr_canonicalize(asset_token.as_str())?;
-    let mut asset: AssetConfig = read_asset_config(deps.storage, &asset_token_raw)?;
+    let asset_info_raw = read_asset_info(deps.storage, deps.api, &asset_token)?;
+    let mut asset: AssetConfig = read_asset_config(deps.storage, &asset_info_raw)?;
 
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(auction_discount) = auction_discount {
+        assert_auction_discount(auction_discount)?;
+        asset.auction_discount = auction_discount;
     }
 
     if let Some(min_collateral_ratio) = min_collateral_ratio {
@@ -281,33 +452,171 @@ pub fn update_asset(
         asset.min_collateral_ratio = min_collateral_ratio;
     }
 
-    store_asset_config(deps.storage, &asset_token_raw, &asset)?;
+    if let Some(stability_fee_rate) = stability_fee_rate {
+        assert_stability_fee_rate(stability_fee_rate)?;
+
+        // fees accrued so far are kept at the previous rate
+        accrue_stability_fee(&mut asset, env.block.time.seconds())?;
+        asset.stability_fee_rate = stability_fee_rate;
+    }
+
+    if let Some(ipo_params) = ipo_params {
+        asset.ipo_params = Some(ipo_params_to_raw(deps.api, ipo_params)?);
+    }
+
+    store_asset_config(deps.storage, &asset_info_raw, &asset)?;
     Ok(Response::new().add_attribute("action", "update_asset"))
 }
 
+pub fn update_mint_cap(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    asset_token: String,
+    mint_cap: Option<Uint128>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // only registered assets can be capped
+    let asset_info_raw = read_asset_info(deps.storage, deps.api, &asset_token)?;
+    read_asset_config(deps.storage, &asset_info_raw)?;
+
+    // lowering the cap below the total only blocks new mints
+    let mut utilization: Utilization = read_mint_utilization(deps.storage, &asset_info_raw)?;
+    utilization.cap = mint_cap;
+    store_mint_utilization(deps.storage, &asset_info_raw, &utilization)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_mint_cap"),
+        attr("asset_token", asset_token),
+        attr("mint_cap", cap_to_string(mint_cap)),
+    ]))
+}
+
+pub fn count_utilizations(
+    deps: DepsMut<SeiQueryWrapper>,
+    limit: Option<u32>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    if read_utilization_cursor(deps.storage)?.is_none() {
+        return Err(ContractError::UtilizationsCounted {});
+    }
+
+    // the totals are complete once the cursor is cleared
+    let cursor = count_position_utilizations(deps.storage, limit)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "count_utilizations"),
+        attr(
+            "last_counted",
+            cursor
+                .map(|cursor| cursor.to_string())
+                .unwrap_or_else(|| "done".to_string()),
+        ),
+    ]))
+}
+
+pub fn update_deposit_cap(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    collateral: AssetInfo,
+    deposit_cap: Option<Uint128>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // lowering the cap below the total only blocks new deposits
+    let collateral_info_raw: AssetInfoRaw = collateral.to_raw(deps.api)?;
+    let mut utilization: Utilization =
+        read_deposit_utilization(deps.storage, &collateral_info_raw)?;
+    utilization.cap = deposit_cap;
+    store_deposit_utilization(deps.storage, &collateral_info_raw, &utilization)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_deposit_cap"),
+        attr("collateral", collateral.to_string()),
+        attr("deposit_cap", cap_to_string(deposit_cap)),
+    ]))
+}
+
+fn cap_to_string(cap: Option<Uint128>) -> String {
+    cap.map(|cap| cap.to_string())
+        .unwrap_or_else(|| "none".to_string())
+}
+
 pub fn register_asset(
     deps: DepsMut<SeiQueryWrapper>,
     info: MessageInfo,
     asset_token: Addr,
+    auction_discount: Decimal,
     min_collateral_ratio: Decimal,
-) -> StdResult<Response> {
+    ipo_params: Option<IpoParamsMsg>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    _register_asset(
+        deps,
+        info,
+        AssetInfo::Token {
+            contract_addr: asset_token.to_string(),
+        },
+        auction_discount,
+        min_collateral_ratio,
+        ipo_params,
+        vec![],
+    )
+}
+
+/// Creates the tokenfactory denom of a native mAsset. This contract
+/// is the denom admin, so it is the only one able to mint and burn it
+pub fn register_native_asset(
+    deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    info: MessageInfo,
+    subdenom: String,
+    auction_discount: Decimal,
+    min_collateral_ratio: Decimal,
+    ipo_params: Option<IpoParamsMsg>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let denom = format!("factory/{}/{}", env.contract.address, subdenom);
+    _register_asset(
+        deps,
+        info,
+        AssetInfo::NativeToken { denom },
+        auction_discount,
+        min_collateral_ratio,
+        ipo_params,
+        vec![CosmosMsg::Custom(SeiMsg::CreateDenom { subdenom })],
+    )
+}
+
+fn _register_asset(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    auction_discount: Decimal,
+    min_collateral_ratio: Decimal,
+    ipo_params: Option<IpoParamsMsg>,
+    mut messages: Vec<CosmosMsg<SeiMsg>>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    assert_auction_discount(auction_discount)?;
     assert_min_collateral_ratio(min_collateral_ratio)?;
+    let ipo_params: Option<IpoParams> = ipo_params
+        .map(|ipo_params| ipo_params_to_raw(deps.api, ipo_params))
+        .transpose()?;
 
     let config: Config = read_config(deps.storage)?;
 
     // permission check
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
-    let asset_token_raw = deps.api.addr_canonicalize(asset_token.as_str())?;
-    if read_asset_config(deps.storage, &asset_token_raw).is_ok() {
-        return Err(StdError::generic_err("Asset was already registered"));
+    let asset_info_raw: AssetInfoRaw = asset_info.to_raw(deps.api)?;
+    if read_asset_config(deps.storage, &asset_info_raw).is_ok() {
+        return Err(ContractError::AssetAlreadyRegistered {});
     }
 
-    let mut messages: Vec<CosmosMsg> = vec![];
-
-
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: deps
             .api
@@ -315,9 +624,7 @@ pub fn register_asset(
             .to_string(),
         funds: vec![],
         msg: to_binary(&CollateralOracleExecuteMsg::RegisterCollateralAsset {
-            asset: AssetInfo::Token {
-                contract_addr: asset_token.to_string(),
-            },
+            asset: asset_info.clone(),
             multiplier: Decimal::one(), // default collateral multiplier for new mAssets
             price_source: SourceType::MelangeOracle {
                 oracle_addr: deps.api.addr_humanize(&config.oracle)?.to_string(),
@@ -328,43 +635,53 @@ pub fn register_asset(
     // Store temp info into base asset store
     store_asset_config(
         deps.storage,
-        &asset_token_raw,
+        &asset_info_raw,
         &AssetConfig {
-            token: deps.api.addr_canonicalize(asset_token.as_str())?,
+            asset_info: asset_info_raw.clone(),
+            auction_discount,
             min_collateral_ratio,
             end_price: None,
+            stability_fee_rate: Decimal::zero(),
+            stability_fee_index: Decimal::one(),
+            stability_fee_last_update: 0,
+            ipo_params,
         },
     )?;
 
     Ok(Response::new()
         .add_attributes(vec![
             attr("action", "register"),
-            attr("asset_token", asset_token),
+            attr("asset_token", asset_info.to_string()),
         ])
         .add_messages(messages))
 }
 
 pub fn register_migration(
     deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
     info: MessageInfo,
-    asset_token: Addr,
+    asset_token: String,
     end_price: Decimal,
-) -> StdResult<Response> {
+) -> Result<Response<SeiMsg>, ContractError> {
     let config = read_config(deps.storage)?;
     if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
-    let asset_token_raw = deps.api.addr_canonicalize(asset_token.as_str())?;
-    let asset_config: AssetConfig = read_asset_config(deps.storage, &asset_token_raw)?;
+    let asset_info_raw = read_asset_info(deps.storage, deps.api, &asset_token)?;
+    let mut asset_config: AssetConfig = read_asset_config(deps.storage, &asset_info_raw)?;
+
+    // deprecated assets stop accruing stability fees
+    accrue_stability_fee(&mut asset_config, env.block.time.seconds())?;
 
     // update asset config
     store_asset_config(
         deps.storage,
-        &asset_token_raw,
+        &asset_info_raw,
         &AssetConfig {
             end_price: Some(end_price),
             min_collateral_ratio: Decimal::percent(100),
+            ipo_params: None,
             ..asset_config
         },
     )?;
@@ -378,9 +695,7 @@ pub fn register_migration(
                 .to_string(),
             funds: vec![],
             msg: to_binary(&CollateralOracleExecuteMsg::RevokeCollateralAsset {
-                asset: AssetInfo::Token {
-                    contract_addr: asset_token.to_string(),
-                },
+                asset: asset_info_raw.to_normal(deps.api)?,
             })?,
         })])
         .add_attributes(vec![
@@ -390,12 +705,130 @@ pub fn register_migration(
         ]))
 }
 
+/// Converts a pre-IPO asset to a regular asset priced by the oracle
+pub fn trigger_ipo(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    asset_token: String,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let asset_info_raw = read_asset_info(deps.storage, deps.api, &asset_token)?;
+    let mut asset_config: AssetConfig = read_asset_config(deps.storage, &asset_info_raw)?;
+
+    let ipo_params: IpoParams = match asset_config.ipo_params {
+        Some(ipo_params) => ipo_params,
+        None => return Err(ContractError::NotPreIpoAsset {}),
+    };
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != ipo_params.trigger_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    asset_config.min_collateral_ratio = ipo_params.min_collateral_ratio_after_ipo;
+    asset_config.ipo_params = None;
+    store_asset_config(deps.storage, &asset_info_raw, &asset_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "trigger_ipo"),
+        attr("asset_token", asset_token),
+    ]))
+}
+
+fn ipo_params_to_raw(api: &dyn Api, ipo_params: IpoParamsMsg) -> Result<IpoParams, ContractError> {
+    assert_min_collateral_ratio(ipo_params.min_collateral_ratio_after_ipo)?;
+    if ipo_params.pre_ipo_price.is_zero() {
+        return Err(ContractError::InvalidPreIpoPrice {});
+    }
+
+    Ok(IpoParams {
+        mint_end: ipo_params.mint_end,
+        pre_ipo_price: ipo_params.pre_ipo_price,
+        min_collateral_ratio_after_ipo: ipo_params.min_collateral_ratio_after_ipo,
+        trigger_addr: api.addr_canonicalize(&ipo_params.trigger_addr)?,
+    })
+}
+
+pub fn update_pause_info(
+    deps: DepsMut<SeiQueryWrapper>,
+    info: MessageInfo,
+    asset_token: Option<String>,
+    operations: Vec<PauseOperation>,
+    paused: bool,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // the guardian can only pause
+    if sender_raw != config.owner && (!paused || config.guardian != Some(sender_raw)) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // only registered assets can be paused one by one
+    let asset_info_raw: Option<AssetInfoRaw> = match &asset_token {
+        Some(asset_token) => {
+            let asset_info_raw = read_asset_info(deps.storage, deps.api, asset_token)?;
+            read_asset_config(deps.storage, &asset_info_raw)?;
+            Some(asset_info_raw)
+        }
+        None => None,
+    };
+
+    let mut pause_info: PauseInfo = read_pause_info(deps.storage, asset_info_raw.as_ref())?;
+    for operation in operations.iter() {
+        pause_info.set(operation, paused);
+    }
+    store_pause_info(deps.storage, asset_info_raw.as_ref(), &pause_info)?;
+
+    let operations: Vec<&str> = operations
+        .iter()
+        .map(|operation| match operation {
+            PauseOperation::OpenPosition => "open_position",
+            PauseOperation::Mint => "mint",
+            PauseOperation::Withdraw => "withdraw",
+            PauseOperation::All => "all",
+        })
+        .collect();
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", if paused { "pause" } else { "unpause" }),
+        attr("asset_token", asset_token.unwrap_or_else(|| "all".to_string())),
+        attr("operations", operations.join(",")),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps<SeiQueryWrapper>, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn migrate(
+    deps: DepsMut<SeiQueryWrapper>,
+    _env: Env,
+    msg: MigrateMsg,
+) -> StdResult<Response> {
+    let stored_version = update_contract_version(deps.storage)?;
+    let from_version = stored_version
+        .as_ref()
+        .map(|version| version.to_string())
+        .unwrap_or_else(|| "unversioned".to_string());
+    migrate_storage(deps.storage, stored_version)?;
+
+    let mut config: Config = read_config(deps.storage)?;
+    config.oracle = deps.api.addr_canonicalize(&msg.melange_oracle_contract)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate"),
+        attr("from_version", from_version),
+        attr("to_version", CONTRACT_VERSION),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<SeiQueryWrapper>, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::AssetConfig { asset_token } => to_binary(&query_asset_config(deps, asset_token)?),
-        QueryMsg::Position { position_idx } => to_binary(&query_position(deps, position_idx)?),
+        QueryMsg::AssetConfig { asset_token } => {
+            to_binary(&query_asset_config(deps, env, asset_token)?)
+        }
+        QueryMsg::Position { position_idx } => {
+            to_binary(&query_position(deps, env, position_idx)?)
+        }
         QueryMsg::Positions {
             owner_addr,
             asset_token,
@@ -404,6 +837,7 @@ pub fn query(deps: Deps<SeiQueryWrapper>, _env: Env, msg: QueryMsg) -> StdResult
             order_by,
         } => to_binary(&query_positions(
             deps,
+            env,
             owner_addr,
             asset_token,
             start_after,
@@ -411,6 +845,64 @@ pub fn query(deps: Deps<SeiQueryWrapper>, _env: Env, msg: QueryMsg) -> StdResult
             order_by,
         )?),
         QueryMsg::NextPositionIdx {} => to_binary(&query_next_position_idx(deps)?),
+        QueryMsg::PositionLockInfo { position_idx } => {
+            to_binary(&query_position_lock_info(deps, position_idx)?)
+        }
+        QueryMsg::PositionApproval { owner, spender } => {
+            to_binary(&query_position_approval(deps, owner, spender)?)
+        }
+        QueryMsg::PositionHealth { position_idx } => {
+            to_binary(&query_position_health(deps, env, position_idx)?)
+        }
+        QueryMsg::PositionsHealth { positions_idx } => {
+            to_binary(&query_positions_health(deps, env, positions_idx)?)
+        }
+        QueryMsg::Utilization { asset_info } => to_binary(&query_utilization(deps, asset_info)?),
+        QueryMsg::PauseInfo { asset_token } => to_binary(&query_pause_info(deps, asset_token)?),
+        QueryMsg::SimulateOpenPosition {
+            sender,
+            collateral,
+            asset_info,
+            collateral_ratio,
+            short_params,
+        } => to_binary(&query_simulate_open_position(
+            deps,
+            env,
+            sender,
+            collateral,
+            asset_info,
+            collateral_ratio,
+            short_params,
+        )?),
+        QueryMsg::SimulateMint {
+            sender,
+            position_idx,
+            asset,
+            short_params,
+        } => to_binary(&query_simulate_mint(
+            deps,
+            env,
+            sender,
+            position_idx,
+            asset,
+            short_params,
+        )?),
+        QueryMsg::SimulateBurn {
+            sender,
+            position_idx,
+            asset,
+        } => to_binary(&query_simulate_burn(deps, env, sender, position_idx, asset)?),
+        QueryMsg::SimulateWithdraw {
+            sender,
+            position_idx,
+            collateral,
+        } => to_binary(&query_simulate_withdraw(
+            deps,
+            env,
+            sender,
+            position_idx,
+            collateral,
+        )?),
     }
 }
 
@@ -432,26 +924,95 @@ pub fn query_config(deps: Deps<SeiQueryWrapper>) -> StdResult<ConfigResponse> {
         base_denom: state.base_denom,
         token_code_id: state.token_code_id,
         protocol_fee_rate: state.protocol_fee_rate,
+        lock_period: state.lock_period,
+        position_nft: state
+            .position_nft
+            .map(|position_nft| deps.api.addr_humanize(&position_nft))
+            .transpose()?
+            .map(|position_nft| position_nft.to_string()),
+        guardian: state
+            .guardian
+            .map(|guardian| deps.api.addr_humanize(&guardian))
+            .transpose()?
+            .map(|guardian| guardian.to_string()),
     };
 
     Ok(resp)
 }
 
-pub fn query_asset_config(deps: Deps<SeiQueryWrapper>, asset_token: String) -> StdResult<AssetConfigResponse> {
-    let asset_config: AssetConfig = read_asset_config(
-        deps.storage,
-        &deps.api.addr_canonicalize(asset_token.as_str())?,
-    )?;
+pub fn query_asset_config(
+    deps: Deps<SeiQueryWrapper>,
+    env: Env,
+    asset_token: String,
+) -> StdResult<AssetConfigResponse> {
+    let asset_info_raw = read_asset_info(deps.storage, deps.api, &asset_token)?;
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &asset_info_raw)?;
 
     let resp = AssetConfigResponse {
-        token: deps
-            .api
-            .addr_humanize(&asset_config.token)
-            .unwrap()
-            .to_string(),
+        token: asset_config.asset_info.to_normal(deps.api)?.to_string(),
+        auction_discount: asset_config.auction_discount,
         min_collateral_ratio: asset_config.min_collateral_ratio,
         end_price: asset_config.end_price,
+        stability_fee_rate: asset_config.stability_fee_rate,
+        stability_fee_index: stability_fee_index(&asset_config, env.block.time.seconds())?,
+        ipo_params: asset_config
+            .ipo_params
+            .map(|ipo_params| -> StdResult<IpoParamsMsg> {
+                Ok(IpoParamsMsg {
+                    mint_end: ipo_params.mint_end,
+                    pre_ipo_price: ipo_params.pre_ipo_price,
+                    min_collateral_ratio_after_ipo: ipo_params.min_collateral_ratio_after_ipo,
+                    trigger_addr: deps.api.addr_humanize(&ipo_params.trigger_addr)?.to_string(),
+                })
+            })
+            .transpose()?,
     };
 
     Ok(resp)
 }
+
+pub fn query_utilization(
+    deps: Deps<SeiQueryWrapper>,
+    asset_info: AssetInfo,
+) -> StdResult<UtilizationResponse> {
+    let asset_info_raw: AssetInfoRaw = asset_info.to_raw(deps.api)?;
+    let mint_utilization: Utilization = read_mint_utilization(deps.storage, &asset_info_raw)?;
+    let deposit_utilization: Utilization =
+        read_deposit_utilization(deps.storage, &asset_info_raw)?;
+
+    let utilization_ratio = |utilization: &Utilization| -> StdResult<Option<Decimal>> {
+        match utilization.cap {
+            Some(cap) if !cap.is_zero() => Ok(Some(decimal_from_ratio(utilization.total, cap)?)),
+            _ => Ok(None),
+        }
+    };
+
+    Ok(UtilizationResponse {
+        asset_info,
+        total_minted: mint_utilization.total,
+        mint_cap: mint_utilization.cap,
+        mint_utilization: utilization_ratio(&mint_utilization)?,
+        total_deposited: deposit_utilization.total,
+        deposit_cap: deposit_utilization.cap,
+        deposit_utilization: utilization_ratio(&deposit_utilization)?,
+    })
+}
+
+pub fn query_pause_info(
+    deps: Deps<SeiQueryWrapper>,
+    asset_token: Option<String>,
+) -> StdResult<PauseInfoResponse> {
+    let asset_info_raw: Option<AssetInfoRaw> = asset_token
+        .as_ref()
+        .map(|asset_token| read_asset_info(deps.storage, deps.api, asset_token))
+        .transpose()?;
+    let pause_info: PauseInfo = read_pause_info(deps.storage, asset_info_raw.as_ref())?;
+
+    Ok(PauseInfoResponse {
+        asset_token,
+        open_position: pause_info.open_position,
+        mint: pause_info.mint,
+        withdraw: pause_info.withdraw,
+        all: pause_info.all,
+    })
+}
@@ -0,0 +1,148 @@
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("unauthorized")]
+    Unauthorized {},
+
+    #[error("invalid cw20 hook message")]
+    InvalidHookMsg {},
+
+    #[error("Asset was already registered")]
+    AssetAlreadyRegistered {},
+
+    #[error("auction_discount must be smaller than 1")]
+    InvalidAuctionDiscount {},
+
+    #[error("min_collateral_ratio must be bigger or equal than {min_collateral_ratio}")]
+    InvalidMinCollateralRatio { min_collateral_ratio: String },
+
+    #[error("protocol_fee_rate must be smaller than 1")]
+    InvalidProtocolFeeRate {},
+
+    #[error("stability_fee_rate must be smaller than 1")]
+    InvalidStabilityFeeRate {},
+
+    #[error("Mint cap exceeded")]
+    MintCapExceeded {},
+
+    #[error("Deposit cap exceeded")]
+    DepositCapExceeded {},
+
+    #[error("Every position is already counted in the totals")]
+    UtilizationsCounted {},
+
+    #[error("pre_ipo_price must be bigger than 0")]
+    InvalidPreIpoPrice {},
+
+    #[error("The minting period of this asset ended at {mint_end}")]
+    MintPeriodEnded { mint_end: u64 },
+
+    #[error("Burning and withdrawing are disabled for pre-IPO assets until the IPO")]
+    PreIpoAsset {},
+
+    #[error("Pre-IPO positions only accept the base denom as collateral")]
+    PreIpoCollateral {},
+
+    #[error("The asset is not a pre-IPO asset")]
+    NotPreIpoAsset {},
+
+    #[error("no asset data stored")]
+    AssetNotRegistered {},
+
+    #[error("Wrong collateral")]
+    WrongCollateral {},
+
+    #[error("Wrong asset")]
+    WrongAsset {},
+
+    #[error("Operation is not allowed for the deprecated asset")]
+    DeprecatedAsset {},
+
+    #[error("The collateral asset provided is no longer valid")]
+    RevokedCollateral {},
+
+    #[error("Price is too old")]
+    StalePrice {},
+
+    #[error("Can not open a position with low collateral ratio than minimum")]
+    OpenPositionBelowMinCollateralRatio {},
+
+    #[error("Cannot mint asset over than min collateral ratio")]
+    MintBelowMinCollateralRatio {},
+
+    #[error("Cannot withdraw collateral over than minimum collateral ratio")]
+    WithdrawBelowMinCollateralRatio {},
+
+    #[error("collateral is too small")]
+    CollateralTooSmall {},
+
+    #[error("Cannot withdraw more than you provide")]
+    WithdrawExceedsCollateral {},
+
+    #[error("Cannot burn asset more than you mint")]
+    BurnExceedsAsset {},
+
+    #[error("The protocol fee exceeds the position collateral")]
+    ProtocolFeeExceedsCollateral {},
+
+    #[error("Must burn the whole position debt to close the position")]
+    CloseAmountMismatch {},
+
+    #[error("Cannot liquidate more than the position amount")]
+    LiquidationExceedsAsset {},
+
+    #[error("Cannot liquidate a safely collateralized position")]
+    SafelyCollateralizedPosition {},
+
+    #[error("Nothing to lock")]
+    NothingToLock {},
+
+    #[error("Lock period has not expired yet. Position {position_idx} unlock time: {unlock_time}")]
+    LockPeriodNotExpired {
+        position_idx: Uint128,
+        unlock_time: u64,
+    },
+
+    #[error("The locked funds of the position belong to another receiver")]
+    LockReceiverMismatch {},
+
+    #[error("There are no unlockable funds for the provided positions")]
+    NothingToUnlock {},
+
+    #[error("There are no locked funds for this position idx")]
+    NoLockedFunds {},
+
+    #[error("Operation is paused")]
+    OperationPaused {},
+
+    #[error("Cannot set approval to own account")]
+    CannotSetOwnAccount {},
+
+    #[error("Invalid expiration value")]
+    InvalidExpiration {},
+
+    #[error("There is no approval for this spender")]
+    NoApproval {},
+
+    #[error("The position is an NFT, transfer the token instead")]
+    NftPosition {},
+}
+
+/// Queries share the price loading of the execute paths
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            ContractError::Overflow(err) => StdError::overflow(err),
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
@@ -1,9 +1,11 @@
 mod asserts;
 pub mod contract;
+pub mod error;
 mod math;
 mod migration;
 mod positions;
 mod querier;
+mod simulation;
 mod state;
 
 #[cfg(test)]
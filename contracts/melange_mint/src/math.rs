@@ -1,27 +1,79 @@
-use cosmwasm_std::{Decimal, Uint128};
+use std::convert::TryFrom;
 
-const DECIMAL_FRACTIONAL: Uint128 = Uint128::new(1_000_000_000u128);
+use cosmwasm_std::{
+    CheckedFromRatioError, Decimal, Decimal256, DivideByZeroError, OverflowError,
+    OverflowOperation, StdError, StdResult, Uint128, Uint256,
+};
 
-pub fn reverse_decimal(decimal: Decimal) -> Decimal {
-    Decimal::from_ratio(DECIMAL_FRACTIONAL, decimal * DECIMAL_FRACTIONAL)
+/// return amount * decimal, rounded down. The product is computed
+/// on 256 bits so only a result exceeding `Uint128` fails
+pub fn decimal_mul(amount: Uint128, decimal: Decimal) -> StdResult<Uint128> {
+    let product = Uint256::from(amount).checked_mul(Uint256::from(decimal.atomics()))?
+        / Uint256::from(Decimal::one().atomics());
+
+    Uint128::try_from(product).map_err(|_| {
+        StdError::overflow(OverflowError::new(
+            OverflowOperation::Mul,
+            amount,
+            decimal,
+        ))
+    })
 }
 
-pub fn decimal_subtraction(a: Decimal, b: Decimal) -> Decimal {
-    Decimal::from_ratio(
-        (DECIMAL_FRACTIONAL * a)
-            .checked_sub(DECIMAL_FRACTIONAL * b)
-            .unwrap(),
-        DECIMAL_FRACTIONAL,
-    )
+/// return numerator / denominator
+pub fn decimal_from_ratio(numerator: Uint128, denominator: Uint128) -> StdResult<Decimal> {
+    Decimal::checked_from_ratio(numerator, denominator).map_err(|err| match err {
+        CheckedFromRatioError::DivideByZero => {
+            StdError::divide_by_zero(DivideByZeroError::new(numerator))
+        }
+        CheckedFromRatioError::Overflow => StdError::overflow(OverflowError::new(
+            OverflowOperation::Mul,
+            numerator,
+            denominator,
+        )),
+    })
 }
 
-/// return a / b
-pub fn decimal_division(a: Decimal, b: Decimal) -> Decimal {
-    Decimal::from_ratio(DECIMAL_FRACTIONAL * a, b * DECIMAL_FRACTIONAL)
+/// return 1 / decimal, rounded down at the 18 decimals of `Decimal`.
+/// It never overflows, the reverse of the smallest decimal is 10^18, but
+/// the reverse of a decimal above 10^18 rounds down to zero
+pub fn reverse_decimal(decimal: Decimal) -> StdResult<Decimal> {
+    decimal_division(Decimal::one(), decimal)
 }
 
-pub fn decimal_multiplication(a: Decimal, b: Decimal) -> Decimal {
-    Decimal::from_ratio(a * DECIMAL_FRACTIONAL * b, DECIMAL_FRACTIONAL)
+/// return a - b
+pub fn decimal_subtraction(a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    Ok(a.checked_sub(b)?)
+}
+
+/// return a / b, rounded down. The quotient is computed with `Decimal256`
+/// so only a result exceeding `Decimal` fails
+pub fn decimal_division(a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    let quotient = Decimal256::from(a)
+        .checked_div(Decimal256::from(b))
+        .map_err(|err| match err {
+            CheckedFromRatioError::DivideByZero => {
+                StdError::divide_by_zero(DivideByZeroError::new(a))
+            }
+            CheckedFromRatioError::Overflow => {
+                StdError::overflow(OverflowError::new(OverflowOperation::Mul, a, b))
+            }
+        })?;
+
+    narrow_decimal(quotient, a, b)
+}
+
+/// return a * b, rounded down. The product is computed with `Decimal256`
+/// so only a result exceeding `Decimal` fails
+pub fn decimal_multiplication(a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    let product = Decimal256::from(a).checked_mul(Decimal256::from(b))?;
+    narrow_decimal(product, a, b)
+}
+
+/// converts the result of an operation on `a` and `b` back to `Decimal`
+fn narrow_decimal(value: Decimal256, a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    Decimal::try_from(value)
+        .map_err(|_| StdError::overflow(OverflowError::new(OverflowOperation::Mul, a, b)))
 }
 
 pub fn decimal_min(a: Decimal, b: Decimal) -> Decimal {
@@ -31,3 +83,170 @@ pub fn decimal_min(a: Decimal, b: Decimal) -> Decimal {
         b
     }
 }
+
+#[cfg(test)]
+mod math_tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const ULP: Decimal = Decimal::raw(1);
+
+    fn any_uint() -> impl Strategy<Value = Uint128> {
+        any::<u128>().prop_map(Uint128::new)
+    }
+
+    fn any_decimal() -> impl Strategy<Value = Decimal> {
+        any::<u128>().prop_map(Decimal::raw)
+    }
+
+    /// prices and ratios handled by the contract, from 10^-18 to 10^9
+    fn price() -> impl Strategy<Value = Decimal> {
+        (1u128..=1_000_000_000_000_000_000_000_000_000u128).prop_map(Decimal::raw)
+    }
+
+    proptest! {
+        #[test]
+        fn decimal_mul_rounds_the_exact_product_down(
+            amount in any_uint(),
+            decimal in any_decimal(),
+        ) {
+            let exact = Uint256::from(amount) * Uint256::from(decimal.atomics())
+                / Uint256::from(Decimal::one().atomics());
+            match decimal_mul(amount, decimal) {
+                Ok(product) => prop_assert_eq!(Uint256::from(product), exact),
+                Err(StdError::Overflow { .. }) => prop_assert!(exact > Uint256::from(Uint128::MAX)),
+                Err(err) => prop_assert!(false, "unexpected error: {}", err),
+            }
+        }
+
+        #[test]
+        fn decimal_multiplication_only_fails_on_overflow(a in any_decimal(), b in any_decimal()) {
+            match decimal_multiplication(a, b) {
+                Ok(product) => prop_assert_eq!(product, a * b),
+                Err(StdError::Overflow { .. }) => {
+                    prop_assert!(a > Decimal::one() && b > Decimal::one())
+                }
+                Err(err) => prop_assert!(false, "unexpected error: {}", err),
+            }
+        }
+
+        #[test]
+        fn decimal_division_only_fails_on_zero_or_overflow(a in any_decimal(), b in any_decimal()) {
+            match decimal_division(a, b) {
+                Ok(quotient) => prop_assert!(decimal_multiplication(quotient, b).unwrap() <= a),
+                Err(StdError::DivideByZero { .. }) => prop_assert!(b.is_zero()),
+                Err(StdError::Overflow { .. }) => prop_assert!(b < Decimal::one()),
+                Err(err) => prop_assert!(false, "unexpected error: {}", err),
+            }
+        }
+
+        #[test]
+        fn decimal_subtraction_only_fails_below_zero(a in any_decimal(), b in any_decimal()) {
+            match decimal_subtraction(a, b) {
+                Ok(difference) => prop_assert_eq!(difference + b, a),
+                Err(StdError::Overflow { .. }) => prop_assert!(a < b),
+                Err(err) => prop_assert!(false, "unexpected error: {}", err),
+            }
+        }
+
+        #[test]
+        fn decimal_from_ratio_only_fails_on_zero_or_overflow(
+            numerator in any_uint(),
+            denominator in any_uint(),
+        ) {
+            match decimal_from_ratio(numerator, denominator) {
+                Ok(ratio) => prop_assert!(decimal_mul(denominator, ratio).unwrap() <= numerator),
+                Err(StdError::DivideByZero { .. }) => prop_assert!(denominator.is_zero()),
+                Err(StdError::Overflow { .. }) => prop_assert!(numerator > denominator),
+                Err(err) => prop_assert!(false, "unexpected error: {}", err),
+            }
+        }
+
+        #[test]
+        fn multiplication_and_division_round_trip(a in price(), b in price()) {
+            // dividing by at least one keeps the rounding of the product within a unit
+            let (small, large) = if a < b { (a, b) } else { (b, a) };
+            let large = large + Decimal::one();
+            let round_trip =
+                decimal_division(decimal_multiplication(small, large).unwrap(), large).unwrap();
+            prop_assert!(round_trip <= small && small - round_trip <= ULP);
+        }
+
+        #[test]
+        fn reverse_decimal_never_fails_on_prices(decimal in price()) {
+            let reverse = reverse_decimal(decimal).unwrap();
+            let product = decimal_multiplication(decimal, reverse).unwrap();
+
+            // the reverse rounds down by less than a unit, scaled back by the price
+            prop_assert!(product <= Decimal::one());
+            prop_assert!(
+                Decimal::one() - product
+                    <= decimal_multiplication(decimal + Decimal::one(), ULP).unwrap() + ULP
+            );
+        }
+
+        #[test]
+        fn helpers_are_monotonic(a in price(), b in price(), c in price()) {
+            let (low, high) = if a < b { (a, b) } else { (b, a) };
+            prop_assert!(
+                decimal_multiplication(low, c).unwrap() <= decimal_multiplication(high, c).unwrap()
+            );
+            prop_assert!(decimal_division(low, c).unwrap() <= decimal_division(high, c).unwrap());
+            prop_assert!(decimal_division(c, low).unwrap() >= decimal_division(c, high).unwrap());
+            prop_assert!(reverse_decimal(low).unwrap() >= reverse_decimal(high).unwrap());
+        }
+    }
+
+    #[test]
+    fn zero_divisors_are_errors() {
+        assert!(matches!(
+            reverse_decimal(Decimal::zero()).unwrap_err(),
+            StdError::DivideByZero { .. }
+        ));
+        assert!(matches!(
+            decimal_division(Decimal::one(), Decimal::zero()).unwrap_err(),
+            StdError::DivideByZero { .. }
+        ));
+        assert!(matches!(
+            decimal_from_ratio(Uint128::new(1), Uint128::zero()).unwrap_err(),
+            StdError::DivideByZero { .. }
+        ));
+    }
+
+    #[test]
+    fn low_prices_keep_full_precision() {
+        assert_eq!(
+            reverse_decimal(Decimal::raw(1)).unwrap(),
+            Decimal::from_ratio(1_000_000_000_000_000_000u128, 1u128)
+        );
+        assert_eq!(
+            reverse_decimal(Decimal::raw(3)).unwrap(),
+            Decimal::from_ratio(1_000_000_000_000_000_000u128, 3u128)
+        );
+        // the reverse of a decimal above 10^18 rounds down to zero
+        assert_eq!(
+            reverse_decimal(Decimal::from_ratio(2_000_000_000_000_000_000u128, 1u128)).unwrap(),
+            Decimal::zero()
+        );
+    }
+
+    #[test]
+    fn helpers_keep_precision() {
+        assert_eq!(
+            reverse_decimal(Decimal::percent(150)).unwrap(),
+            Decimal::from_ratio(2u128, 3u128)
+        );
+        assert_eq!(
+            decimal_subtraction(Decimal::percent(150), Decimal::one()).unwrap(),
+            Decimal::percent(50)
+        );
+        assert_eq!(
+            decimal_division(Decimal::percent(50), Decimal::percent(25)).unwrap(),
+            Decimal::percent(200)
+        );
+        assert_eq!(
+            decimal_multiplication(Decimal::percent(50), Decimal::percent(25)).unwrap(),
+            Decimal::permille(125)
+        );
+    }
+}
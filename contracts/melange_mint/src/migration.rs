@@ -1,10 +1,46 @@
-use cosmwasm_storage::Bucket;
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlyPrefixedStorage};
 use schemars::JsonSchema;
+use semver::Version;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdError, StdResult, Storage};
+use cosmwasm_std::{
+    from_slice, CanonicalAddr, Decimal, Order, StdError, StdResult, Storage, Uint128,
+};
+use cw2::{get_contract_version, set_contract_version};
+
+use crate::state::{
+    store_utilization_cursor, AssetConfig, Config, Utilization, KEY_CONFIG, PREFIX_ASSET_CONFIG,
+    PREFIX_DEPOSIT_UTILIZATION, PREFIX_MINT_UTILIZATION,
+};
+use terraswap::asset::AssetInfoRaw;
+
+pub const CONTRACT_NAME: &str = "crates.io:melange-mint";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// auction discount assigned to assets registered before auctions existed
+const DEFAULT_AUCTION_DISCOUNT: u64 = 20;
+
+/// lock period of the short sale proceeds for contracts instantiated
+/// before they were locked, two weeks
+const DEFAULT_LOCK_PERIOD: u64 = 1_209_600;
+
+/// Config stored before short positions locked their proceeds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub oracle: CanonicalAddr,
+    pub collector: CanonicalAddr,
+    pub collateral_oracle: CanonicalAddr,
+    pub staking: CanonicalAddr,
+    pub tswap_factory: CanonicalAddr,
+    pub base_denom: String,
+    pub token_code_id: u64,
+    pub protocol_fee_rate: Decimal,
+}
+
+/// first version tracking the minted and deposited totals
+const UTILIZATION_VERSION: &str = "0.2.0";
 
-use crate::state::{AssetConfig, PREFIX_ASSET_CONFIG};
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct LegacyAssetConfig {
     pub token: CanonicalAddr,
@@ -12,30 +48,170 @@ pub struct LegacyAssetConfig {
     pub end_price: Option<Decimal>,
 }
 
-pub fn migrate_asset_configs(storage: &mut dyn Storage) -> StdResult<()> {
-    let mut legacy_asset_configs_bucket: Bucket<LegacyAssetConfig> =
-        Bucket::new(storage, PREFIX_ASSET_CONFIG);
+/// Asset config stored before native mAssets were supported
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenAssetConfig {
+    pub token: CanonicalAddr,
+    pub auction_discount: Decimal,
+    pub min_collateral_ratio: Decimal,
+    pub end_price: Option<Decimal>,
+}
+
+/// Checks the stored cw2 info belongs to this contract and is not newer,
+/// then records the current version. Returns the previous version,
+/// `None` for contracts instantiated before the versioning
+pub fn update_contract_version(storage: &mut dyn Storage) -> StdResult<Option<Version>> {
+    let stored_version = match get_contract_version(storage) {
+        Ok(contract_version) => {
+            if contract_version.contract != CONTRACT_NAME {
+                return Err(StdError::generic_err(format!(
+                    "Cannot migrate from a different contract: {}",
+                    contract_version.contract
+                )));
+            }
 
-    let mut asset_configs: Vec<(CanonicalAddr, LegacyAssetConfig)> = vec![];
-    for item in legacy_asset_configs_bucket.range(None, None, Order::Ascending) {
-        let (k, p) = item?;
-        asset_configs.push((CanonicalAddr::from(k), p));
+            let stored_version = parse_version(&contract_version.version)?;
+            if stored_version > parse_version(CONTRACT_VERSION)? {
+                return Err(StdError::generic_err(format!(
+                    "Cannot migrate from a newer version: {}",
+                    contract_version.version
+                )));
+            }
+
+            Some(stored_version)
+        }
+        Err(_) => None,
+    };
+
+    set_contract_version(storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(stored_version)
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version)
+        .map_err(|_| StdError::generic_err(format!("Invalid contract version: {}", version)))
+}
+
+/// Runs the storage migrations required by the stored version
+pub fn migrate_storage(
+    storage: &mut dyn Storage,
+    stored_version: Option<Version>,
+) -> StdResult<()> {
+    // unversioned contracts may hold configs of any previous layout
+    if stored_version.is_none() {
+        migrate_config(storage)?;
+        migrate_asset_configs(storage)?;
+    }
+
+    // the totals are kept up to date from the version tracking them
+    let tracks_utilizations = match &stored_version {
+        Some(version) => *version >= parse_version(UTILIZATION_VERSION)?,
+        None => false,
+    };
+    if !tracks_utilizations {
+        migrate_utilizations(storage)?;
     }
 
-    for (asset, _) in asset_configs.clone().into_iter() {
-        legacy_asset_configs_bucket.remove(asset.as_slice());
+    Ok(())
+}
+
+/// Resets the minted and deposited totals, keeping the caps, and starts
+/// recounting the positions from the first one. Totals are not tracked by
+/// versions before the caps, the positions are counted by `CountUtilizations`
+pub fn migrate_utilizations(storage: &mut dyn Storage) -> StdResult<()> {
+    for prefix in [PREFIX_MINT_UTILIZATION, PREFIX_DEPOSIT_UTILIZATION] {
+        let utilizations: Vec<(Vec<u8>, Utilization)> =
+            ReadonlyBucket::<Utilization>::new(storage, prefix)
+                .range(None, None, Order::Ascending)
+                .collect::<StdResult<Vec<(Vec<u8>, Utilization)>>>()?;
+
+        let mut utilization_bucket: Bucket<Utilization> = Bucket::new(storage, prefix);
+        for (k, utilization) in utilizations.into_iter() {
+            utilization_bucket.save(
+                &k,
+                &Utilization {
+                    total: Uint128::zero(),
+                    ..utilization
+                },
+            )?;
+        }
     }
 
-    let mut new_asset_configs_bucket: Bucket<AssetConfig> =
-        Bucket::new(storage, PREFIX_ASSET_CONFIG);
+    store_utilization_cursor(storage, Some(Uint128::zero()))
+}
+
+/// Converts the stored config to the current layout. A config
+/// already in the current layout is left untouched
+pub fn migrate_config(storage: &mut dyn Storage) -> StdResult<()> {
+    if singleton_read::<Config>(storage, KEY_CONFIG).load().is_ok() {
+        return Ok(());
+    }
 
-    for (asset, asset_config) in asset_configs.into_iter() {
-        let new_asset_config = &AssetConfig {
-            token: asset_config.token,
-            min_collateral_ratio: asset_config.min_collateral_ratio,
-            end_price: asset_config.end_price,
+    let legacy_config: LegacyConfig = singleton_read(storage, KEY_CONFIG).load()?;
+    singleton(storage, KEY_CONFIG).save(&Config {
+        owner: legacy_config.owner,
+        oracle: legacy_config.oracle,
+        collector: legacy_config.collector,
+        collateral_oracle: legacy_config.collateral_oracle,
+        staking: legacy_config.staking,
+        tswap_factory: legacy_config.tswap_factory,
+        base_denom: legacy_config.base_denom,
+        token_code_id: legacy_config.token_code_id,
+        protocol_fee_rate: legacy_config.protocol_fee_rate,
+        lock_period: DEFAULT_LOCK_PERIOD,
+        position_nft: None,
+        guardian: None,
+    })
+}
+
+/// Converts the stored asset configs to the current layout. Configs
+/// already in the current layout are left untouched
+pub fn migrate_asset_configs(storage: &mut dyn Storage) -> StdResult<()> {
+    let mut asset_configs: Vec<(Vec<u8>, AssetConfig)> = vec![];
+    for (k, v) in ReadonlyPrefixedStorage::new(storage, PREFIX_ASSET_CONFIG).range(
+        None,
+        None,
+        Order::Ascending,
+    ) {
+        if from_slice::<AssetConfig>(&v).is_ok() {
+            continue;
+        }
+
+        // the token layout must be tried first, legacy configs ignore the discount
+        let asset_config = if let Ok(asset_config) = from_slice::<TokenAssetConfig>(&v) {
+            AssetConfig {
+                asset_info: AssetInfoRaw::Token {
+                    contract_addr: asset_config.token,
+                },
+                auction_discount: asset_config.auction_discount,
+                min_collateral_ratio: asset_config.min_collateral_ratio,
+                end_price: asset_config.end_price,
+                stability_fee_rate: Decimal::zero(),
+                stability_fee_index: Decimal::one(),
+                stability_fee_last_update: 0,
+                ipo_params: None,
+            }
+        } else {
+            let asset_config: LegacyAssetConfig = from_slice(&v)?;
+            AssetConfig {
+                asset_info: AssetInfoRaw::Token {
+                    contract_addr: asset_config.token,
+                },
+                auction_discount: Decimal::percent(DEFAULT_AUCTION_DISCOUNT),
+                min_collateral_ratio: asset_config.min_collateral_ratio,
+                end_price: asset_config.end_price,
+                stability_fee_rate: Decimal::zero(),
+                stability_fee_index: Decimal::one(),
+                stability_fee_last_update: 0,
+                ipo_params: None,
+            }
         };
-        new_asset_configs_bucket.save(asset.as_slice(), new_asset_config)?;
+        asset_configs.push((k, asset_config));
+    }
+
+    let mut asset_configs_bucket: Bucket<AssetConfig> = Bucket::new(storage, PREFIX_ASSET_CONFIG);
+    for (k, asset_config) in asset_configs.iter() {
+        asset_configs_bucket.save(k, asset_config)?;
     }
 
     Ok(())
@@ -43,12 +219,23 @@ pub fn migrate_asset_configs(storage: &mut dyn Storage) -> StdResult<()> {
 
 #[cfg(test)]
 mod migrate_tests {
-    use crate::state::read_asset_config;
+    use crate::state::{
+        count_position_utilizations, read_asset_config, read_deposit_utilization,
+        read_mint_utilization, read_position, read_utilization_cursor, store_asset_config,
+        store_mint_utilization, store_position, Position, PREFIX_POSITION,
+    };
+    use terraswap::asset::AssetRaw;
 
     use super::*;
     use cosmwasm_std::{testing::mock_dependencies, Api};
 
-    pub fn asset_configs_old_store(storage: &mut dyn Storage) -> Bucket<LegacyAssetConfig> {
+    pub fn asset_configs_old_store(storage: &mut dyn Storage) -> Bucket<'_, LegacyAssetConfig> {
+        Bucket::new(storage, PREFIX_ASSET_CONFIG)
+    }
+
+    pub fn token_asset_configs_old_store(
+        storage: &mut dyn Storage,
+    ) -> Bucket<'_, TokenAssetConfig> {
         Bucket::new(storage, PREFIX_ASSET_CONFIG)
     }
 
@@ -67,17 +254,232 @@ mod migrate_tests {
             .save(asset_config.token.as_slice(), &asset_config)
             .unwrap();
 
+        let token_asset_config = TokenAssetConfig {
+            token: deps.api.addr_canonicalize("mGOOG").unwrap(),
+            auction_discount: Decimal::percent(10),
+            min_collateral_ratio: Decimal::percent(130),
+            end_price: Some(Decimal::percent(50)),
+        };
+        token_asset_configs_old_store(&mut deps.storage)
+            .save(token_asset_config.token.as_slice(), &token_asset_config)
+            .unwrap();
+
+        let native_asset_info = AssetInfoRaw::NativeToken {
+            denom: "factory/mint0000/uapple".to_string(),
+        };
+        let native_asset_config = AssetConfig {
+            asset_info: native_asset_info.clone(),
+            auction_discount: Decimal::percent(20),
+            min_collateral_ratio: Decimal::percent(150),
+            end_price: None,
+            stability_fee_rate: Decimal::zero(),
+            stability_fee_index: Decimal::one(),
+            stability_fee_last_update: 0,
+            ipo_params: None,
+        };
+        store_asset_config(&mut deps.storage, &native_asset_info, &native_asset_config).unwrap();
+
         migrate_asset_configs(deps.as_mut().storage).unwrap();
 
+        let asset_info = AssetInfoRaw::Token {
+            contract_addr: asset_config.token,
+        };
         let new_asset_config: AssetConfig =
-            read_asset_config(deps.as_mut().storage, &asset_config.token).unwrap();
+            read_asset_config(deps.as_mut().storage, &asset_info).unwrap();
 
         assert_eq!(
             new_asset_config,
             AssetConfig {
-                token: asset_config.token,
+                asset_info,
+                auction_discount: Decimal::percent(DEFAULT_AUCTION_DISCOUNT),
                 min_collateral_ratio: asset_config.min_collateral_ratio,
                 end_price: asset_config.end_price,
+                stability_fee_rate: Decimal::zero(),
+                stability_fee_index: Decimal::one(),
+                stability_fee_last_update: 0,
+                ipo_params: None,
+            }
+        );
+
+        let asset_info = AssetInfoRaw::Token {
+            contract_addr: token_asset_config.token,
+        };
+        assert_eq!(
+            read_asset_config(deps.as_mut().storage, &asset_info).unwrap(),
+            AssetConfig {
+                asset_info: asset_info.clone(),
+                auction_discount: token_asset_config.auction_discount,
+                min_collateral_ratio: token_asset_config.min_collateral_ratio,
+                end_price: token_asset_config.end_price,
+                stability_fee_rate: Decimal::zero(),
+                stability_fee_index: Decimal::one(),
+                stability_fee_last_update: 0,
+                ipo_params: None,
+            }
+        );
+
+        assert_eq!(
+            read_asset_config(deps.as_mut().storage, &native_asset_info).unwrap(),
+            native_asset_config
+        );
+    }
+
+    #[test]
+    fn test_update_contract_version() {
+        let mut deps = mock_dependencies();
+
+        // unversioned contract
+        assert_eq!(update_contract_version(&mut deps.storage).unwrap(), None);
+        assert_eq!(
+            update_contract_version(&mut deps.storage).unwrap(),
+            Some(parse_version(CONTRACT_VERSION).unwrap())
+        );
+
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+        assert_eq!(
+            update_contract_version(&mut deps.storage).unwrap_err(),
+            StdError::generic_err("Cannot migrate from a newer version: 99.0.0")
+        );
+
+        set_contract_version(&mut deps.storage, "crates.io:melange-staking", "0.1.0").unwrap();
+        assert_eq!(
+            update_contract_version(&mut deps.storage).unwrap_err(),
+            StdError::generic_err(
+                "Cannot migrate from a different contract: crates.io:melange-staking"
+            )
+        );
+    }
+
+    #[test]
+    fn test_migrate_utilizations() {
+        let mut deps = mock_dependencies();
+
+        let asset_info = AssetInfoRaw::Token {
+            contract_addr: deps.api.addr_canonicalize("asset0000").unwrap(),
+        };
+        let collateral_info = AssetInfoRaw::NativeToken {
+            denom: "uusd".to_string(),
+        };
+
+        // positions stored before the totals were tracked
+        let mut position_bucket: Bucket<Position> = Bucket::new(&mut deps.storage, PREFIX_POSITION);
+        for idx in 1u128..3u128 {
+            position_bucket
+                .save(
+                    &idx.to_be_bytes(),
+                    &Position {
+                        idx: Uint128::from(idx),
+                        owner: deps.api.addr_canonicalize("addr0000").unwrap(),
+                        collateral: AssetRaw {
+                            info: collateral_info.clone(),
+                            amount: Uint128::from(1000u128),
+                        },
+                        asset: AssetRaw {
+                            info: asset_info.clone(),
+                            amount: Uint128::from(500u128),
+                        },
+                        other_collaterals: vec![],
+                        stability_fee_index: None,
+                    },
+                )
+                .unwrap();
+        }
+        store_mint_utilization(
+            &mut deps.storage,
+            &asset_info,
+            &Utilization {
+                total: Uint128::from(100u128),
+                cap: Some(Uint128::from(5000u128)),
+            },
+        )
+        .unwrap();
+
+        migrate_utilizations(&mut deps.storage).unwrap();
+        assert_eq!(
+            read_mint_utilization(&deps.storage, &asset_info).unwrap(),
+            Utilization {
+                total: Uint128::zero(),
+                cap: Some(Uint128::from(5000u128)),
+            }
+        );
+        assert_eq!(
+            read_utilization_cursor(&deps.storage).unwrap(),
+            Some(Uint128::zero())
+        );
+
+        // the first position is counted, the second one is not yet
+        assert_eq!(
+            count_position_utilizations(&mut deps.storage, Some(1u32)).unwrap(),
+            Some(Uint128::from(1u128))
+        );
+        assert_eq!(
+            read_mint_utilization(&deps.storage, &asset_info).unwrap().total,
+            Uint128::from(500u128)
+        );
+
+        // only the counted position moves the totals
+        for idx in 1u128..3u128 {
+            let mut position = read_position(&deps.storage, Uint128::from(idx)).unwrap();
+            position.asset.amount = Uint128::from(300u128);
+            store_position(&mut deps.storage, Uint128::from(idx), &position).unwrap();
+        }
+        assert_eq!(
+            read_mint_utilization(&deps.storage, &asset_info).unwrap().total,
+            Uint128::from(300u128)
+        );
+
+        assert_eq!(
+            count_position_utilizations(&mut deps.storage, Some(2u32)).unwrap(),
+            None
+        );
+        assert_eq!(
+            read_mint_utilization(&deps.storage, &asset_info).unwrap(),
+            Utilization {
+                total: Uint128::from(600u128),
+                cap: Some(Uint128::from(5000u128)),
+            }
+        );
+        assert_eq!(
+            read_deposit_utilization(&deps.storage, &collateral_info).unwrap(),
+            Utilization {
+                total: Uint128::from(2000u128),
+                cap: None,
+            }
+        );
+        assert_eq!(read_utilization_cursor(&deps.storage).unwrap(), None);
+    }
+
+    #[test]
+    fn test_migrate_storage_utilizations() {
+        let mut deps = mock_dependencies();
+
+        let asset_info = AssetInfoRaw::Token {
+            contract_addr: deps.api.addr_canonicalize("asset0000").unwrap(),
+        };
+        let utilization = Utilization {
+            total: Uint128::from(100u128),
+            cap: Some(Uint128::from(5000u128)),
+        };
+        store_mint_utilization(&mut deps.storage, &asset_info, &utilization).unwrap();
+
+        // versions tracking the totals keep them
+        migrate_storage(
+            &mut deps.storage,
+            Some(parse_version(UTILIZATION_VERSION).unwrap()),
+        )
+        .unwrap();
+        assert_eq!(
+            read_mint_utilization(&deps.storage, &asset_info).unwrap(),
+            utilization
+        );
+
+        // older versions recount them from the positions
+        migrate_storage(&mut deps.storage, Some(parse_version("0.1.0").unwrap())).unwrap();
+        assert_eq!(
+            read_mint_utilization(&deps.storage, &asset_info).unwrap(),
+            Utilization {
+                total: Uint128::zero(),
+                cap: Some(Uint128::from(5000u128)),
             }
         );
     }
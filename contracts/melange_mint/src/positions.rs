@@ -1,90 +1,147 @@
 use cosmwasm_std::{
-    attr, to_binary, Addr, Attribute, CosmosMsg, Decimal, Deps, DepsMut, Env, Response, StdError,
-    StdResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, Api, Attribute, BankMsg, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    Empty, Env,
+    QuerierWrapper, Response, StdResult, Storage, Uint128, Uint64, WasmMsg,
 };
 
 use crate::{
     asserts::{
-        assert_asset, assert_collateral, assert_migrated_asset, assert_revoked_collateral,
+        assert_asset, assert_collateral, assert_deposit_cap, assert_migrated_asset, assert_mint_cap,
+        assert_mint_period, assert_not_paused, assert_not_pre_ipo, assert_position_operator,
+        assert_pre_ipo_collateral, assert_revoked_collateral,
     },
+    error::ContractError,
     math::{
-        decimal_division, decimal_min, decimal_multiplication, decimal_subtraction, reverse_decimal,
+        decimal_division, decimal_from_ratio, decimal_min, decimal_mul, decimal_multiplication,
+        decimal_subtraction, reverse_decimal,
     },
-    querier::{load_asset_price, load_collateral_info},
+    querier::{load_asset_price, load_collateral_info, query_nft_owner},
     state::{
-        create_position, read_asset_config, read_config, read_position,
-        read_position_idx, read_positions, read_positions_with_asset_indexer,
-        read_positions_with_user_indexer, remove_position, store_position, store_position_idx,
-        AssetConfig, Config, Position,
+        create_position, is_short_position, read_asset_config, read_asset_info, read_config, read_position,
+        store_asset_config,
+        read_mint_utilization, read_position_approval, read_position_idx, read_position_lock_info,
+        read_position_nft, read_positions,
+        read_positions_with_asset_indexer, read_positions_with_user_indexer, remove_position,
+        remove_position_approval, remove_position_lock_info, remove_position_nft,
+        remove_short_position,
+        store_position, store_position_approval, store_position_idx, store_position_lock_info,
+        store_position_nft, store_short_position, transfer_position as transfer_position_owner, AssetConfig, Config,
+        Position, PositionLockInfo,
     },
 };
 
-use cw20::Cw20ExecuteMsg;
+use cw20::{Cw20ExecuteMsg, Expiration};
 use melange_protocol::{
     common::OrderBy,
-    mint::{NextPositionIdxResponse, PositionResponse, PositionsResponse},
+    cw721::ExecuteMsg as Cw721ExecuteMsg,
+    mint::{
+        NextPositionIdxResponse, PauseOperation, PositionApprovalResponse, PositionHealthResponse,
+        PositionLockInfoResponse, PositionResponse, PositionsHealthResponse, PositionsResponse,
+        ShortParams,
+    },
+    mint::ExecuteMsg,
     staking::ExecuteMsg as StakingExecuteMsg,
 };
 use terraswap::{
     asset::{Asset, AssetInfo, AssetInfoRaw, AssetRaw},
-    pair::Cw20HookMsg as PairCw20HookMsg,
+    pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg},
     querier::query_pair_info,
 };
-use sei_cosmwasm::{
-    SeiMsg, SeiQuerier, SeiQueryWrapper,
-};
+use sei_cosmwasm::{SeiMsg, SeiQueryWrapper};
+
+/// Stability fees are annualized over 365 days
+const SECONDS_PER_YEAR: u64 = 31_536_000;
 
 pub fn open_position(
     deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    sender: Addr,
+    collateral: Asset,
+    asset_info: AssetInfo,
+    collateral_ratio: Decimal,
+    short_params: Option<ShortParams>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    _open_position(
+        deps,
+        env,
+        sender,
+        collateral,
+        asset_info,
+        collateral_ratio,
+        short_params,
+    )
+    .map(|(res, _)| res)
+}
+
+pub(crate) fn _open_position(
+    deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
     sender: Addr,
     collateral: Asset,
     asset_info: AssetInfo,
     collateral_ratio: Decimal,
-) -> StdResult<Response> {
+    short_params: Option<ShortParams>,
+) -> Result<(Response<SeiMsg>, PositionOutcome), ContractError> {
     let config: Config = read_config(deps.storage)?;
     if collateral.amount.is_zero() {
-        return Err(StdError::generic_err("Wrong collateral"));
+        return Err(ContractError::WrongCollateral {});
     }
 
     // assert the collateral is listed and has not been migrated/revoked
     let collateral_info_raw: AssetInfoRaw = collateral.info.to_raw(deps.api)?;
     let collateral_oracle: Addr = deps.api.addr_humanize(&config.collateral_oracle)?;
     let (collateral_price, collateral_multiplier) = assert_revoked_collateral(
-        load_collateral_info(deps.as_ref(), collateral_oracle, &collateral_info_raw, true)?,
+        load_collateral_info(
+            deps.as_ref(),
+            collateral_oracle,
+            &collateral_info_raw,
+            Some(env.block.time.seconds()),
+        )?,
     )?;
 
     // assert asset migrated
     let asset_info_raw: AssetInfoRaw = asset_info.to_raw(deps.api)?;
-    let asset_token_raw = match asset_info_raw.clone() {
-        AssetInfoRaw::Token { contract_addr } => contract_addr,
-        _ => panic!("DO NOT ENTER HERE"),
-    };
-
-    let asset_config: AssetConfig = read_asset_config(deps.storage, &asset_token_raw)?;
+    assert_not_paused(deps.storage, &asset_info_raw, PauseOperation::OpenPosition)?;
+    let mut asset_config: AssetConfig = read_asset_config(deps.storage, &asset_info_raw)?;
     assert_migrated_asset(&asset_config)?;
 
+    // pre-IPO assets are minted against the base denom until the end of their minting period
+    assert_mint_period(&asset_config, env.block.time.seconds())?;
+    assert_pre_ipo_collateral(&config.base_denom, &asset_config, &collateral_info_raw)?;
+
+    // the new debt accrues stability fees from the current index
+    accrue_stability_fee(&mut asset_config, env.block.time.seconds())?;
+    store_asset_config(deps.storage, &asset_info_raw, &asset_config)?;
+
     if collateral_ratio
-        < decimal_multiplication(asset_config.min_collateral_ratio, collateral_multiplier)
+        < decimal_multiplication(asset_config.min_collateral_ratio, collateral_multiplier)?
     {
-        return Err(StdError::generic_err(
-            "Can not open a position with low collateral ratio than minimum",
-        ));
+        return Err(ContractError::OpenPositionBelowMinCollateralRatio {});
     }
 
     let oracle: Addr = deps.api.addr_humanize(&config.oracle)?;
-    let asset_price: Decimal = load_asset_price(deps.as_ref(), oracle, &asset_info_raw, true)?;
+    let asset_price: Decimal = load_asset_price(
+        deps.as_ref(),
+        oracle,
+        &asset_info_raw,
+        Some(env.block.time.seconds()),
+    )?;
 
-    let asset_price_in_collateral_asset = decimal_division(collateral_price, asset_price);
+    let asset_price_in_collateral_asset = decimal_division(collateral_price, asset_price)?;
 
     // Convert collateral to mint amount
-    let mint_amount =
-        collateral.amount * asset_price_in_collateral_asset * reverse_decimal(collateral_ratio);
+    let mint_amount = decimal_mul(
+        decimal_mul(collateral.amount, asset_price_in_collateral_asset)?,
+        reverse_decimal(collateral_ratio)?,
+    )?;
     if mint_amount.is_zero() {
-        return Err(StdError::generic_err("collateral is too small"));
+        return Err(ContractError::CollateralTooSmall {});
     }
 
+    assert_mint_cap(deps.storage, &asset_info_raw, mint_amount)?;
+    assert_deposit_cap(deps.storage, &collateral_info_raw, collateral.amount)?;
+
     let position_idx = read_position_idx(deps.storage)?;
-    let asset_info_raw = asset_info.to_raw(deps.api)?;
 
     create_position(
         deps.storage,
@@ -94,29 +151,51 @@ pub fn open_position(
             owner: deps.api.addr_canonicalize(sender.as_str())?,
             collateral: AssetRaw {
                 amount: collateral.amount,
-                info: collateral_info_raw,
+                info: collateral_info_raw.clone(),
             },
             asset: AssetRaw {
                 amount: mint_amount,
-                info: asset_info_raw,
+                info: asset_info_raw.clone(),
             },
+            other_collaterals: vec![],
+            stability_fee_index: Some(asset_config.stability_fee_index),
         },
     )?;
 
-    let asset_token = deps.api.addr_humanize(&asset_config.token)?.to_string();
-    let messages: Vec<CosmosMsg> = {
-        vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: asset_token,
+    let mut messages: Vec<CosmosMsg<SeiMsg>> = if let Some(short_params) = short_params {
+        // sell the minted asset and lock the proceeds
+        store_short_position(deps.storage, position_idx)?;
+        short_sale_messages(
+            deps.as_ref(),
+            &env,
+            &config,
+            &sender,
+            position_idx,
+            &asset_info,
+            mint_amount,
+            short_params,
+        )?
+    } else {
+        mint_messages(&asset_info, &sender, mint_amount)?
+    };
+
+    // represent the position as a token of the position NFT contract
+    if let Some(position_nft) = config.position_nft {
+        store_position_nft(deps.storage, position_idx, &position_nft)?;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&position_nft)?.to_string(),
             funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: sender.to_string(),
-                amount: mint_amount,
+            msg: to_binary(&Cw721ExecuteMsg::Mint {
+                token_id: position_idx.to_string(),
+                owner: sender.to_string(),
+                token_uri: None,
+                extension: None,
             })?,
-        })]
-    };
+        }));
+    }
 
-    store_position_idx(deps.storage, position_idx + Uint128::from(1u128))?;
-    Ok(Response::new()
+    store_position_idx(deps.storage, position_idx.checked_add(Uint128::from(1u128))?)?;
+    let res = Response::new()
         .add_attributes(vec![
             attr("action", "open_position"),
             attr("position_idx", position_idx.to_string()),
@@ -126,49 +205,74 @@ pub fn open_position(
             ),
             attr("collateral_amount", collateral.to_string()),
         ])
-        .add_messages(messages))
+        .add_messages(messages);
+
+    Ok((
+        res,
+        PositionOutcome {
+            mint_amount,
+            ..PositionOutcome::default()
+        },
+    ))
 }
 
 pub fn deposit(
-    deps: DepsMut<SeiQueryWrapper>,
+    mut deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
     sender: Addr,
     position_idx: Uint128,
     collateral: Asset,
-) -> StdResult<Response> {
+) -> Result<Response<SeiMsg>, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let mut position: Position = read_position(deps.storage, position_idx)?;
-    let position_owner = deps.api.addr_humanize(&position.owner)?;
-    if sender != position_owner {
-        return Err(StdError::generic_err("unauthorized"));
-    }
+    let (mut position, owner_messages) = load_position(deps.branch(), &env, &config, position_idx)?;
+    assert_not_paused(deps.storage, &position.asset.info, PauseOperation::All)?;
+    assert_position_operator(deps.as_ref(), &env.block, &position, &sender)?;
 
-    // Check the given collateral has same asset info
-    // with position's collateral token
-    // also Check the collateral amount is non-zero
-    assert_collateral(deps.as_ref(), &position, &collateral)?;
+    // Check the collateral amount is non-zero
+    if collateral.amount.is_zero() {
+        return Err(ContractError::WrongCollateral {});
+    }
 
     // assert the collateral is listed and has not been migrated/revoked
+    let collateral_info_raw: AssetInfoRaw = collateral.info.to_raw(deps.api)?;
     let collateral_oracle: Addr = deps.api.addr_humanize(&config.collateral_oracle)?;
     assert_revoked_collateral(load_collateral_info(
         deps.as_ref(),
         collateral_oracle,
-        &position.collateral.info,
-        false,
+        &collateral_info_raw,
+        None,
     )?)?;
 
     // assert asset migrated
-    match position.asset.info.clone() {
-        AssetInfoRaw::Token { contract_addr } => {
-            assert_migrated_asset(&read_asset_config(deps.storage, &contract_addr)?)?
-        }
-        _ => panic!("DO NOT ENTER HERE"),
-    };
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &position.asset.info)?;
+    assert_migrated_asset(&asset_config)?;
+    assert_pre_ipo_collateral(&config.base_denom, &asset_config, &collateral_info_raw)?;
+
+    assert_deposit_cap(deps.storage, &collateral_info_raw, collateral.amount)?;
 
-    // Increase collateral amount
-    position.collateral.amount += collateral.amount;
+    // Increase collateral amount, a new collateral asset joins the others
+    if position.collateral.info == collateral_info_raw {
+        position.collateral.amount = position.collateral.amount.checked_add(collateral.amount)?;
+    } else {
+        match position
+            .other_collaterals
+            .iter()
+            .position(|other_collateral| other_collateral.info == collateral_info_raw)
+        {
+            Some(i) => {
+                position.other_collaterals[i].amount = position.other_collaterals[i]
+                    .amount
+                    .checked_add(collateral.amount)?
+            }
+            None => position.other_collaterals.push(AssetRaw {
+                info: collateral_info_raw.clone(),
+                amount: collateral.amount,
+            }),
+        }
+    }
     store_position(deps.storage, position_idx, &position)?;
 
-    Ok(Response::new().add_attributes(vec![
+    Ok(Response::new().add_messages(owner_messages).add_attributes(vec![
         attr("action", "deposit"),
         attr("position_idx", position_idx.to_string()),
         attr("deposit_amount", collateral.to_string()),
@@ -177,90 +281,106 @@ pub fn deposit(
 
 pub fn withdraw(
     deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    sender: Addr,
+    position_idx: Uint128,
+    collateral: Option<Asset>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    _withdraw(deps, env, sender, position_idx, collateral).map(|(res, _)| res)
+}
+
+pub(crate) fn _withdraw(
+    mut deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
     sender: Addr,
     position_idx: Uint128,
     collateral: Option<Asset>,
-) -> StdResult<Response> {
+) -> Result<(Response<SeiMsg>, PositionOutcome), ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let mut position: Position = read_position(deps.storage, position_idx)?;
+    let (mut position, owner_messages) = load_position(deps.branch(), &env, &config, position_idx)?;
+    assert_not_paused(deps.storage, &position.asset.info, PauseOperation::Withdraw)?;
     let position_owner = deps.api.addr_humanize(&position.owner)?;
-    if sender != position_owner {
-        return Err(StdError::generic_err("unauthorized"));
+    assert_position_operator(deps.as_ref(), &env.block, &position, &sender)?;
+
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &position.asset.info)?;
+    assert_not_pre_ipo(&asset_config)?;
+    let oracle: Addr = deps.api.addr_humanize(&config.oracle)?;
+    let asset_price: Decimal = load_asset_price(
+        deps.as_ref(),
+        oracle,
+        &position.asset.info,
+        Some(env.block.time.seconds()),
+    )?;
+
+    // Fetch collateral infos from collateral oracle
+    let mut collaterals: Vec<PositionCollateral> = load_position_collaterals(
+        deps.as_ref(),
+        &config,
+        &position,
+        Some(env.block.time.seconds()),
+    )?;
+
+    // ignore multipliers for de-listed assets
+    if asset_config.end_price.is_some() {
+        for position_collateral in collaterals.iter_mut() {
+            position_collateral.multiplier = Decimal::one();
+        }
     }
 
-    // if collateral is not provided, withdraw all collateral
-    let collateral: Asset = if let Some(collateral) = collateral {
-        // Check the given collateral has same asset info
-        // with position's collateral token
+    // if collateral is not provided, withdraw all collaterals
+    let withdrawn: Vec<PositionCollateral> = if let Some(collateral) = collateral {
+        // Check the given collateral is held by the position
         // also Check the collateral amount is non-zero
         assert_collateral(deps.as_ref(), &position, &collateral)?;
 
-        if position.collateral.amount < collateral.amount {
-            return Err(StdError::generic_err(
-                "Cannot withdraw more than you provide",
-            ));
+        let collateral_info_raw: AssetInfoRaw = collateral.info.to_raw(deps.api)?;
+        let position_collateral = collaterals
+            .iter_mut()
+            .find(|position_collateral| position_collateral.info == collateral_info_raw)
+            .ok_or(ContractError::WrongCollateral {})?;
+        if position_collateral.amount < collateral.amount {
+            return Err(ContractError::WithdrawExceedsCollateral {});
         }
 
-        collateral
+        // Compute new collateral amount
+        position_collateral.amount = position_collateral.amount.checked_sub(collateral.amount)?;
+        vec![PositionCollateral {
+            amount: collateral.amount,
+            ..position_collateral.clone()
+        }]
     } else {
-        position.collateral.to_normal(deps.api)?
-    };
-
-    let asset_token_raw = match position.asset.info.clone() {
-        AssetInfoRaw::Token { contract_addr } => contract_addr,
-        _ => panic!("DO NOT ENTER HERE"),
+        take_collateral(&mut collaterals, Uint128::MAX)?
     };
 
-    let asset_config: AssetConfig = read_asset_config(deps.storage, &asset_token_raw)?;
-    let oracle: Addr = deps.api.addr_humanize(&config.oracle)?;
-    let asset_price: Decimal = load_asset_price(deps.as_ref(), oracle, &position.asset.info, true)?;
-
-    // Fetch collateral info from collateral oracle
-    let collateral_oracle: Addr = deps.api.addr_humanize(&config.collateral_oracle)?;
-    let (collateral_price, mut collateral_multiplier, _collateral_is_revoked) =
-        load_collateral_info(
-            deps.as_ref(),
-            collateral_oracle,
-            &position.collateral.info,
-            true,
-        )?;
-
-    // ignore multiplier for de-listed assets
-    if asset_config.end_price.is_some() {
-        collateral_multiplier = Decimal::one();
-    }
-
-    // Compute new collateral amount
-    let collateral_amount: Uint128 = position.collateral.amount.checked_sub(collateral.amount)?;
-
-    // Convert asset to collateral unit
-    let asset_value_in_collateral_asset: Uint128 =
-        position.asset.amount * decimal_division(asset_price, collateral_price);
+    // Convert asset to main collateral unit
+    let asset_value_in_collateral_asset: Uint128 = decimal_mul(
+        position.asset.amount,
+        decimal_division(asset_price, collaterals[0].price)?,
+    )?;
 
     // Check minimum collateral ratio is satisfied
-    if asset_value_in_collateral_asset * asset_config.min_collateral_ratio * collateral_multiplier
-        > collateral_amount
+    if decimal_mul(
+        decimal_mul(
+            asset_value_in_collateral_asset,
+            asset_config.min_collateral_ratio,
+        )?,
+        collaterals[0].multiplier,
+    )? > weighted_collateral_value(&collaterals)?
     {
-        // println!("\n");
-        // println!("*** IF ***");
-        // println!("asset_price: {}", asset_price);
-        // println!("collateral_price: {}", collateral_price);
-        // println!("collateral_amount: {}", collateral_amount);
-        // println!("asset_value_in_collateral_asset: {}", asset_value_in_collateral_asset);
-        // println!("min_collateral_ratio: {}", asset_config.min_collateral_ratio);
-        // println!("collateral_multiplier: {}", collateral_multiplier);
-        // println!("*** IF ***");
-        // println!("\n");
-        return Err(StdError::generic_err(
-            "Cannot withdraw collateral over than minimum collateral ratio",
-        ));
+        return Err(ContractError::WithdrawBelowMinCollateralRatio {});
     }
 
-    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut messages: Vec<CosmosMsg<SeiMsg>> = vec![];
 
-    position.collateral.amount = collateral_amount;
+    update_position_collaterals(&mut position, &collaterals);
     if position.collateral.amount == Uint128::zero() && position.asset.amount == Uint128::zero() {
         remove_position(deps.storage, position_idx)?;
+        messages.extend(release_position(
+            deps.storage,
+            deps.api,
+            &config.base_denom,
+            position_idx,
+        )?);
     } else {
         store_position(deps.storage, position_idx, &position)?;
     }
@@ -269,10 +389,11 @@ pub fn withdraw(
     // let tax_amount = collateral.compute_tax(&deps.querier)?;
     let tax_amount = Uint128::zero();
 
-    Ok(Response::new()
+    let res = Response::new()
         .add_messages(
-            vec![
-                vec![collateral.clone().into_msg(position_owner)?],
+            [
+                owner_messages,
+                collateral_transfer_messages(deps.api, &withdrawn, &position_owner)?,
                 messages,
             ]
                 .concat(),
@@ -280,141 +401,185 @@ pub fn withdraw(
         .add_attributes(vec![
             attr("action", "withdraw"),
             attr("position_idx", position_idx.to_string()),
-            attr("withdraw_amount", collateral.to_string()),
+            attr("withdraw_amount", collateral_attribute(deps.api, &withdrawn)?),
             attr(
                 "tax_amount",
-                tax_amount.to_string() + &collateral.info.to_string(),
+                tax_amount.to_string() + &withdrawn[0].info.to_normal(deps.api)?.to_string(),
             ),
-        ]))
+        ]);
+
+    Ok((
+        res,
+        PositionOutcome {
+            refund_collateral: collateral_assets(deps.api, &withdrawn)?,
+            ..PositionOutcome::default()
+        },
+    ))
 }
 
 pub fn mint(
     deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
     sender: Addr,
     position_idx: Uint128,
     asset: Asset,
-) -> StdResult<Response> {
+    short_params: Option<ShortParams>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    _mint(deps, env, sender, position_idx, asset, short_params).map(|(res, _)| res)
+}
+
+pub(crate) fn _mint(
+    mut deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    sender: Addr,
+    position_idx: Uint128,
+    asset: Asset,
+    short_params: Option<ShortParams>,
+) -> Result<(Response<SeiMsg>, PositionOutcome), ContractError> {
     let config: Config = read_config(deps.storage)?;
     let mint_amount = asset.amount;
 
-    let mut position: Position = read_position(deps.storage, position_idx)?;
+    let (mut position, owner_messages) = load_position(deps.branch(), &env, &config, position_idx)?;
+    assert_not_paused(deps.storage, &position.asset.info, PauseOperation::Mint)?;
     let position_owner = deps.api.addr_humanize(&position.owner)?;
-    if sender != position_owner {
-        return Err(StdError::generic_err("unauthorized"));
-    }
+    assert_position_operator(deps.as_ref(), &env.block, &position, &sender)?;
 
     assert_asset(deps.as_ref(), &position, &asset)?;
 
-    let asset_token_raw = match position.asset.info.clone() {
-        AssetInfoRaw::Token { contract_addr } => contract_addr,
-        _ => panic!("DO NOT ENTER HERE"),
-    };
-
     // assert the asset migrated
-    let asset_config: AssetConfig = read_asset_config(deps.storage, &asset_token_raw)?;
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &position.asset.info)?;
     assert_migrated_asset(&asset_config)?;
+    assert_mint_period(&asset_config, env.block.time.seconds())?;
 
-    // assert the collateral is listed and has not been migrated/revoked
-    let collateral_oracle: Addr = deps.api.addr_humanize(&config.collateral_oracle)?;
-    let (collateral_price, collateral_multiplier) =
-        assert_revoked_collateral(load_collateral_info(
-            deps.as_ref(),
-            collateral_oracle,
-            &position.collateral.info,
-            true,
-        )?)?;
+    // assert the collaterals are listed and have not been migrated/revoked
+    let collaterals: Vec<PositionCollateral> = load_position_collaterals(
+        deps.as_ref(),
+        &config,
+        &position,
+        Some(env.block.time.seconds()),
+    )?;
+    if collaterals
+        .iter()
+        .any(|position_collateral| position_collateral.is_revoked)
+    {
+        return Err(ContractError::RevokedCollateral {});
+    }
 
     let oracle: Addr = deps.api.addr_humanize(&config.oracle)?;
-    let asset_price: Decimal = load_asset_price(deps.as_ref(), oracle, &position.asset.info, true)?;
+    let asset_price: Decimal = load_asset_price(
+        deps.as_ref(),
+        oracle,
+        &position.asset.info,
+        Some(env.block.time.seconds()),
+    )?;
 
     // Compute new asset amount
-    let asset_amount: Uint128 = mint_amount + position.asset.amount;
+    let asset_amount: Uint128 = mint_amount.checked_add(position.asset.amount)?;
 
-    // Convert asset to collateral unit
-    let asset_value_in_collateral_asset: Uint128 =
-        asset_amount * decimal_division(asset_price, collateral_price);
+    // Convert asset to main collateral unit
+    let asset_value_in_collateral_asset: Uint128 = decimal_mul(
+        asset_amount,
+        decimal_division(asset_price, collaterals[0].price)?,
+    )?;
 
     // Check minimum collateral ratio is satisfied
-    if asset_value_in_collateral_asset * asset_config.min_collateral_ratio * collateral_multiplier
-        > position.collateral.amount
+    if decimal_mul(
+        decimal_mul(
+            asset_value_in_collateral_asset,
+            asset_config.min_collateral_ratio,
+        )?,
+        collaterals[0].multiplier,
+    )? > weighted_collateral_value(&collaterals)?
     {
-        // println!("*** IF ***");
-        // println!("asset_price: {}", asset_price);
-        // println!("collateral_price: {}", collateral_price);
-        // println!("position.collateral.amount: {}", position.collateral.amount);
-        // println!("asset_value_in_collateral_asset: {}", asset_value_in_collateral_asset);
-        // println!("min_collateral_ratio: {}", asset_config.min_collateral_ratio);
-        // println!("collateral_multiplier: {}", collateral_multiplier);
-        // println!("*** IF ***");
-        // println!("\n");
-        return Err(StdError::generic_err(
-            "Cannot mint asset over than min collateral ratio",
-        ));
+        return Err(ContractError::MintBelowMinCollateralRatio {});
     }
 
-    position.asset.amount += mint_amount;
+    assert_mint_cap(deps.storage, &position.asset.info, mint_amount)?;
 
-    let messages: Vec<CosmosMsg> = {
-        vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps.api.addr_humanize(&asset_config.token)?.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                amount: mint_amount,
-                recipient: position_owner.to_string(),
-            })?,
-            funds: vec![],
-        })]
+    position.asset.amount = asset_amount;
+    store_position(deps.storage, position_idx, &position)?;
+
+    let messages: Vec<CosmosMsg<SeiMsg>> = if is_short_position(deps.storage, position_idx)? {
+        // minted assets of a short position are always sold
+        short_sale_messages(
+            deps.as_ref(),
+            &env,
+            &config,
+            &position_owner,
+            position_idx,
+            &asset.info,
+            mint_amount,
+            short_params.unwrap_or(ShortParams {
+                belief_price: None,
+                max_spread: None,
+            }),
+        )?
+    } else {
+        mint_messages(&asset.info, &position_owner, mint_amount)?
     };
-    // println!("xxx, {}", position_idx.to_string());
-    // println!("xxx, {}", asset.to_string());
 
-    Ok(Response::new()
+    let res = Response::new()
+        .add_messages(owner_messages)
         .add_attributes(vec![
             attr("action", "mint"),
             attr("position_idx", position_idx.to_string()),
             attr("mint_amount", asset.to_string()),
         ])
-        .add_messages(messages))
+        .add_messages(messages);
+
+    Ok((
+        res,
+        PositionOutcome {
+            mint_amount,
+            ..PositionOutcome::default()
+        },
+    ))
 }
 
 pub fn burn(
     deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    sender: Addr,
+    position_idx: Uint128,
+    asset: Asset,
+) -> Result<Response<SeiMsg>, ContractError> {
+    _burn(deps, env, sender, position_idx, asset).map(|(res, _)| res)
+}
+
+pub(crate) fn _burn(
+    mut deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
     sender: Addr,
     position_idx: Uint128,
     asset: Asset,
-) -> StdResult<Response> {
+) -> Result<(Response<SeiMsg>, PositionOutcome), ContractError> {
     let burn_amount = asset.amount;
 
     let config: Config = read_config(deps.storage)?;
-    let mut position: Position = read_position(deps.storage, position_idx)?;
+    let (mut position, owner_messages) = load_position(deps.branch(), &env, &config, position_idx)?;
+    assert_not_paused(deps.storage, &position.asset.info, PauseOperation::All)?;
     let position_owner = deps.api.addr_humanize(&position.owner)?;
-    let collateral_info: AssetInfo = position.collateral.info.to_normal(deps.api)?;
 
     // Check the asset has same token with position asset
     // also Check burn amount is non-zero
     assert_asset(deps.as_ref(), &position, &asset)?;
 
-    let asset_token_raw = match position.asset.info.clone() {
-        AssetInfoRaw::Token { contract_addr } => contract_addr,
-        _ => panic!("DO NOT ENTER HERE"),
-    };
-
-    let asset_config: AssetConfig = read_asset_config(deps.storage, &asset_token_raw)?;
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &position.asset.info)?;
+    assert_not_pre_ipo(&asset_config)?;
     if position.asset.amount < burn_amount {
-        return Err(StdError::generic_err(
-            "Cannot burn asset more than you mint",
-        ));
+        return Err(ContractError::BurnExceedsAsset {});
     }
 
-    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut messages: Vec<CosmosMsg<SeiMsg>> = vec![];
     let mut attributes: Vec<Attribute> = vec![];
+    let mut outcome = PositionOutcome::default();
 
-    // fetch collateral info from collateral oracle
-    let collateral_oracle: Addr = deps.api.addr_humanize(&config.collateral_oracle)?;
-    let (collateral_price, _collateral_multiplier, _collateral_is_revoked) = load_collateral_info(
+    // fetch collateral infos from collateral oracle
+    let mut collaterals: Vec<PositionCollateral> = load_position_collaterals(
         deps.as_ref(),
-        collateral_oracle,
-        &position.collateral.info,
-        true,
+        &config,
+        &position,
+        Some(env.block.time.seconds()),
     )?;
 
     // If the collateral is default denom asset and the asset is deprecated,
@@ -424,28 +589,24 @@ pub fn burn(
     if let Some(end_price) = asset_config.end_price {
         let asset_price: Decimal = end_price;
 
-        let collateral_price_in_asset = decimal_division(asset_price, collateral_price);
+        let collateral_price_in_asset = decimal_division(asset_price, collaterals[0].price)?;
 
         // Burn deprecated asset to receive collaterals back
         let conversion_rate =
-            Decimal::from_ratio(position.collateral.amount, position.asset.amount);
-        let mut refund_collateral = Asset {
-            info: collateral_info.clone(),
-            amount: std::cmp::min(
-                burn_amount * collateral_price_in_asset,
-                burn_amount * conversion_rate,
+            decimal_from_ratio(collateral_value(&collaterals)?, position.asset.amount)?;
+        let mut refund_collaterals: Vec<PositionCollateral> = take_collateral(
+            &mut collaterals,
+            std::cmp::min(
+                decimal_mul(burn_amount, collateral_price_in_asset)?,
+                decimal_mul(burn_amount, conversion_rate)?,
             ),
-        };
+        )?;
 
-        position.asset.amount = position.asset.amount.checked_sub(burn_amount).unwrap();
-        position.collateral.amount = position
-            .collateral
-            .amount
-            .checked_sub(refund_collateral.amount)
-            .unwrap();
+        position.asset.amount = position.asset.amount.checked_sub(burn_amount)?;
+        update_position_collaterals(&mut position, &collaterals);
 
         // due to rounding, include 1
-        if position.collateral.amount <= Uint128::from(1u128)
+        if collateral_value(&collaterals)? <= Uint128::from(1u128)
             && position.asset.amount == Uint128::zero()
         {
             close_position = true;
@@ -455,82 +616,104 @@ pub fn burn(
         }
 
         // Subtract protocol fee from refunded collateral
-        let protocol_fee = Asset {
-            info: collateral_info,
-            amount: burn_amount * collateral_price_in_asset * config.protocol_fee_rate,
-        };
-
-        if !protocol_fee.amount.is_zero() {
-            messages.push(
-                protocol_fee
-                    .clone()
-                    .into_msg(deps.api.addr_humanize(&config.collector)?)?,
-            );
-            refund_collateral.amount = refund_collateral
-                .amount
-                .checked_sub(protocol_fee.amount)
-                .unwrap();
+        let protocol_fee_amount = decimal_mul(
+            decimal_mul(burn_amount, collateral_price_in_asset)?,
+            config.protocol_fee_rate,
+        )?;
+        if collateral_value(&refund_collaterals)? < protocol_fee_amount {
+            return Err(ContractError::ProtocolFeeExceedsCollateral {});
         }
-        attributes.push(attr("protocol_fee", protocol_fee.to_string()));
+        let protocol_fee: Vec<PositionCollateral> =
+            take_collateral(&mut refund_collaterals, protocol_fee_amount)?;
+
+        messages.extend(collateral_transfer_messages(
+            deps.api,
+            &protocol_fee,
+            &deps.api.addr_humanize(&config.collector)?,
+        )?);
+        attributes.push(attr("protocol_fee", collateral_attribute(deps.api, &protocol_fee)?));
 
         // Refund collateral msg
-        messages.push(refund_collateral.clone().into_msg(sender)?);
+        messages.extend(collateral_transfer_messages(
+            deps.api,
+            &refund_collaterals,
+            &sender,
+        )?);
 
         attributes.push(attr(
             "refund_collateral_amount",
-            refund_collateral.to_string(),
+            collateral_attribute(deps.api, &refund_collaterals)?,
         ));
+        outcome.protocol_fee = collateral_assets(deps.api, &protocol_fee)?;
+        outcome.refund_collateral = collateral_assets(deps.api, &refund_collaterals)?;
     } else {
-        if sender != position_owner {
-            return Err(StdError::generic_err("unauthorized"));
-        }
+        assert_position_operator(deps.as_ref(), &env.block, &position, &sender)?;
         let oracle = deps.api.addr_humanize(&config.oracle)?;
         let asset_price: Decimal =
-            load_asset_price(deps.as_ref(), oracle, &asset.info.to_raw(deps.api)?, true)?;
-        let collateral_price_in_asset: Decimal = decimal_division(asset_price, collateral_price);
-
-        // Subtract the protocol fee from the position's collateral
-        let protocol_fee = Asset {
-            info: collateral_info,
-            amount: burn_amount * collateral_price_in_asset * config.protocol_fee_rate,
-        };
+            load_asset_price(
+            deps.as_ref(),
+            oracle,
+            &asset.info.to_raw(deps.api)?,
+            Some(env.block.time.seconds()),
+        )?;
+        let collateral_price_in_asset: Decimal =
+            decimal_division(asset_price, collaterals[0].price)?;
 
-        if !protocol_fee.amount.is_zero() {
-            messages.push(
-                protocol_fee
-                    .clone()
-                    .into_msg(deps.api.addr_humanize(&config.collector)?)?,
-            );
-            position.collateral.amount = position
-                .collateral
-                .amount
-                .checked_sub(protocol_fee.amount)?
+        // Subtract the protocol fee from the position's collaterals
+        let protocol_fee_amount = decimal_mul(
+            decimal_mul(burn_amount, collateral_price_in_asset)?,
+            config.protocol_fee_rate,
+        )?;
+        if collateral_value(&collaterals)? < protocol_fee_amount {
+            return Err(ContractError::ProtocolFeeExceedsCollateral {});
         }
-        attributes.push(attr("protocol_fee", protocol_fee.to_string()));
+        let protocol_fee: Vec<PositionCollateral> =
+            take_collateral(&mut collaterals, protocol_fee_amount)?;
+
+        messages.extend(collateral_transfer_messages(
+            deps.api,
+            &protocol_fee,
+            &deps.api.addr_humanize(&config.collector)?,
+        )?);
+        attributes.push(attr("protocol_fee", collateral_attribute(deps.api, &protocol_fee)?));
+        outcome.protocol_fee = collateral_assets(deps.api, &protocol_fee)?;
 
         // Update asset amount
-        position.asset.amount = position.asset.amount.checked_sub(burn_amount).unwrap();
+        position.asset.amount = position.asset.amount.checked_sub(burn_amount)?;
+        update_position_collaterals(&mut position, &collaterals);
         store_position(deps.storage, position_idx, &position)?;
     }
 
-    let asset_token = deps.api.addr_humanize(&asset_config.token)?;
+    if is_short_position(deps.storage, position_idx)? {
+        messages.extend(decrease_short_token_messages(
+            deps.api,
+            &config,
+            &position_owner,
+            &asset.info,
+            burn_amount,
+        )?);
+    }
 
-    Ok(Response::new()
+    if close_position {
+        messages.extend(release_position(
+            deps.storage,
+            deps.api,
+            &config.base_denom,
+            position_idx,
+        )?);
+    }
+
+    let res = Response::new()
         .add_messages(
-            vec![
-                vec![CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: asset_token.to_string(),
-                    msg: to_binary(&Cw20ExecuteMsg::Burn {
-                        amount: burn_amount,
-                    })?,
-                    funds: vec![],
-                })],
+            [
+                owner_messages,
+                vec![burn_message(&asset.info, burn_amount)?],
                 messages,
             ]
                 .concat(),
         )
         .add_attributes(
-            vec![
+            [
                 vec![
                     attr("action", "burn"),
                     attr("position_idx", position_idx.to_string()),
@@ -539,71 +722,1406 @@ pub fn burn(
                 attributes,
             ]
                 .concat(),
-        ))
+        );
+
+    Ok((res, outcome))
 }
 
-pub fn query_position(deps: Deps<SeiQueryWrapper>, position_idx: Uint128) -> StdResult<PositionResponse> {
-    let position: Position = read_position(deps.storage, position_idx)?;
-    let resp = PositionResponse {
-        idx: position.idx,
-        owner: deps.api.addr_humanize(&position.owner)?.to_string(),
-        collateral: position.collateral.to_normal(deps.api)?,
-        asset: position.asset.to_normal(deps.api)?,
-    };
+pub fn close_position(
+    mut deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    sender: Addr,
+    position_idx: Uint128,
+    asset: Asset,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let (position, mut messages) = load_position(deps.branch(), &env, &config, position_idx)?;
+    assert_not_paused(deps.storage, &position.asset.info, PauseOperation::All)?;
+    let position_owner = deps.api.addr_humanize(&position.owner)?;
+    assert_position_operator(deps.as_ref(), &env.block, &position, &sender)?;
 
-    Ok(resp)
-}
+    // Check the asset has same token with position asset
+    // also Check burn amount is non-zero
+    assert_asset(deps.as_ref(), &position, &asset)?;
 
-pub fn query_positions(
-    deps: Deps<SeiQueryWrapper>,
-    owner_addr: Option<String>,
-    asset_token: Option<String>,
-    start_after: Option<Uint128>,
-    limit: Option<u32>,
-    order_by: Option<OrderBy>,
-) -> StdResult<PositionsResponse> {
-    let positions: Vec<Position> = if let Some(owner_addr) = owner_addr {
-        read_positions_with_user_indexer(
-            deps.storage,
-            &deps.api.addr_canonicalize(&owner_addr)?,
-            start_after,
-            limit,
-            order_by,
-        )?
-    } else if let Some(asset_token) = asset_token {
-        read_positions_with_asset_indexer(
-            deps.storage,
-            &deps.api.addr_canonicalize(&asset_token)?,
-            start_after,
-            limit,
-            order_by,
-        )?
+    if asset.amount != position.asset.amount {
+        return Err(ContractError::CloseAmountMismatch {});
+    }
+
+    // deprecated assets are settled at their end price
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &position.asset.info)?;
+    assert_not_pre_ipo(&asset_config)?;
+    let asset_price: Decimal = if let Some(end_price) = asset_config.end_price {
+        end_price
     } else {
-        read_positions(deps.storage, start_after, limit, order_by)?
+        let oracle: Addr = deps.api.addr_humanize(&config.oracle)?;
+        load_asset_price(
+            deps.as_ref(),
+            oracle,
+            &position.asset.info,
+            Some(env.block.time.seconds()),
+        )?
     };
 
-    let position_responses: StdResult<Vec<PositionResponse>> = positions
-        .iter()
-        .map(|position| {
-            Ok(PositionResponse {
-                idx: position.idx,
-                owner: deps.api.addr_humanize(&position.owner)?.to_string(),
-                collateral: position.collateral.to_normal(deps.api)?,
-                asset: position.asset.to_normal(deps.api)?,
-            })
-        })
-        .collect();
+    let mut collaterals: Vec<PositionCollateral> = load_position_collaterals(
+        deps.as_ref(),
+        &config,
+        &position,
+        Some(env.block.time.seconds()),
+    )?;
+    let collateral_price_in_asset: Decimal = decimal_division(asset_price, collaterals[0].price)?;
+
+    // The protocol fee is capped to the collateral of the position,
+    // so under-collateralized positions can still be closed
+    let protocol_fee_amount = std::cmp::min(
+        decimal_mul(
+            decimal_mul(asset.amount, collateral_price_in_asset)?,
+            config.protocol_fee_rate,
+        )?,
+        collateral_value(&collaterals)?,
+    );
+    let protocol_fee: Vec<PositionCollateral> =
+        take_collateral(&mut collaterals, protocol_fee_amount)?;
+    let refund_collaterals: Vec<PositionCollateral> = collaterals;
+
+    messages.push(burn_message(&asset.info, asset.amount)?);
+    messages.extend(collateral_transfer_messages(
+        deps.api,
+        &protocol_fee,
+        &deps.api.addr_humanize(&config.collector)?,
+    )?);
+    messages.extend(collateral_transfer_messages(
+        deps.api,
+        &refund_collaterals,
+        &position_owner,
+    )?);
+
+    if is_short_position(deps.storage, position_idx)? {
+        messages.extend(decrease_short_token_messages(
+            deps.api,
+            &config,
+            &position_owner,
+            &asset.info,
+            asset.amount,
+        )?);
+    }
 
-    Ok(PositionsResponse {
-        positions: position_responses?,
-    })
+    // removes the position with its indexers, burns its token
+    // and releases the locked short proceeds
+    remove_position(deps.storage, position_idx)?;
+    messages.extend(release_position(
+        deps.storage,
+        deps.api,
+        &config.base_denom,
+        position_idx,
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "close_position"),
+        attr("position_idx", position_idx.to_string()),
+        attr("burn_amount", asset.to_string()),
+        attr("protocol_fee", collateral_attribute(deps.api, &protocol_fee)?),
+        attr(
+            "refund_collateral_amount",
+            collateral_attribute(deps.api, &refund_collaterals)?,
+        ),
+    ]))
 }
 
-pub fn query_next_position_idx(deps: Deps<SeiQueryWrapper>) -> StdResult<NextPositionIdxResponse> {
-    let idx = read_position_idx(deps.storage)?;
-    let resp = NextPositionIdxResponse {
-        next_position_idx: idx,
+pub fn auction(
+    mut deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    sender: Addr,
+    position_idx: Uint128,
+    asset: Asset,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let (mut position, owner_messages) = load_position(deps.branch(), &env, &config, position_idx)?;
+    assert_not_paused(deps.storage, &position.asset.info, PauseOperation::All)?;
+    let position_owner = deps.api.addr_humanize(&position.owner)?;
+
+    // Check the asset has same token with position asset
+    // also Check auction amount is non-zero
+    assert_asset(deps.as_ref(), &position, &asset)?;
+
+    // deprecated assets are settled through burn at the end price
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &position.asset.info)?;
+    assert_migrated_asset(&asset_config)?;
+
+    if asset.amount > position.asset.amount {
+        return Err(ContractError::LiquidationExceedsAsset {});
+    }
+
+    let oracle: Addr = deps.api.addr_humanize(&config.oracle)?;
+    let asset_price: Decimal = load_asset_price(
+        deps.as_ref(),
+        oracle,
+        &position.asset.info,
+        Some(env.block.time.seconds()),
+    )?;
+
+    let mut collaterals: Vec<PositionCollateral> = load_position_collaterals(
+        deps.as_ref(),
+        &config,
+        &position,
+        Some(env.block.time.seconds()),
+    )?;
+    let position_collateral_value: Uint128 = collateral_value(&collaterals)?;
+
+    let asset_price_in_collateral_asset: Decimal =
+        decimal_division(asset_price, collaterals[0].price)?;
+
+    // Check the position is in auction state
+    // asset_amount * price_to_collateral * min_collateral_ratio * multiplier > collateral_amount
+    if decimal_mul(
+        decimal_mul(
+            decimal_mul(position.asset.amount, asset_price_in_collateral_asset)?,
+            asset_config.min_collateral_ratio,
+        )?,
+        collaterals[0].multiplier,
+    )? <= weighted_collateral_value(&collaterals)?
+    {
+        return Err(ContractError::SafelyCollateralizedPosition {});
+    }
+
+    // The discount is capped so that an under-water position
+    // does not return more collateral than it holds per unit of debt
+    let cur_collateral_ratio: Decimal = decimal_division(
+        decimal_from_ratio(position_collateral_value, position.asset.amount)?,
+        asset_price_in_collateral_asset,
+    )?;
+    let discount: Decimal = if cur_collateral_ratio > Decimal::one() {
+        decimal_min(
+            asset_config.auction_discount,
+            decimal_division(
+                decimal_subtraction(cur_collateral_ratio, Decimal::one())?,
+                cur_collateral_ratio,
+            )?,
+        )
+    } else {
+        Decimal::zero()
     };
+    let discounted_price: Decimal = decimal_division(
+        asset_price_in_collateral_asset,
+        decimal_subtraction(Decimal::one(), discount)?,
+    )?;
 
-    Ok(resp)
+    // Convert asset value in discounted collateral unit
+    let asset_value_in_collateral_asset: Uint128 = decimal_mul(asset.amount, discounted_price)?;
+
+    let mut messages: Vec<CosmosMsg<SeiMsg>> = owner_messages;
+
+    // Cap return collateral amount to position collateral amount
+    // If the given asset amount exceeds the amount required to liquidate position,
+    // then refund the leftover asset amount
+    let (return_collateral_amount, refund_asset_amount) =
+        if asset_value_in_collateral_asset > position_collateral_value {
+            let refund_asset_amount = decimal_mul(
+                asset_value_in_collateral_asset.checked_sub(position_collateral_value)?,
+                reverse_decimal(discounted_price)?,
+            )?;
+
+            let refund_asset: Asset = Asset {
+                info: asset.info.clone(),
+                amount: refund_asset_amount,
+            };
+            messages.push(transfer_message(refund_asset, sender.clone())?);
+
+            (position_collateral_value, refund_asset_amount)
+        } else {
+            (asset_value_in_collateral_asset, Uint128::zero())
+        };
+
+    let liquidated_asset_amount = asset.amount.checked_sub(refund_asset_amount)?;
+    let left_asset_amount = position.asset.amount.checked_sub(liquidated_asset_amount)?;
+    let mut return_collaterals: Vec<PositionCollateral> =
+        take_collateral(&mut collaterals, return_collateral_amount)?;
+
+    if is_short_position(deps.storage, position_idx)? {
+        messages.extend(decrease_short_token_messages(
+            deps.api,
+            &config,
+            &position_owner,
+            &asset.info,
+            liquidated_asset_amount,
+        )?);
+    }
+
+    // Update position
+    if collaterals
+        .iter()
+        .all(|position_collateral| position_collateral.amount.is_zero())
+    {
+        // all collaterals are sold out
+        remove_position(deps.storage, position_idx)?;
+        messages.extend(release_position(
+            deps.storage,
+            deps.api,
+            &config.base_denom,
+            position_idx,
+        )?);
+    } else if left_asset_amount.is_zero() {
+        // all assets are paid
+        remove_position(deps.storage, position_idx)?;
+        messages.extend(release_position(
+            deps.storage,
+            deps.api,
+            &config.base_denom,
+            position_idx,
+        )?);
+
+        // refunds left collaterals to position owner
+        messages.extend(collateral_transfer_messages(
+            deps.api,
+            &collaterals,
+            &position_owner,
+        )?);
+    } else {
+        update_position_collaterals(&mut position, &collaterals);
+        position.asset.amount = left_asset_amount;
+
+        store_position(deps.storage, position_idx, &position)?;
+    }
+
+    // token burn message
+    messages.push(burn_message(&asset.info, liquidated_asset_amount)?);
+
+    // Deduct protocol fee from the collateral returned to the liquidator
+    let protocol_fee: Vec<PositionCollateral> = take_collateral(
+        &mut return_collaterals,
+        std::cmp::min(
+            decimal_mul(
+                decimal_mul(liquidated_asset_amount, asset_price_in_collateral_asset)?,
+                config.protocol_fee_rate,
+            )?,
+            return_collateral_amount,
+        ),
+    )?;
+
+    // return collateral to liquidation initiator(sender)
+    messages.extend(collateral_transfer_messages(
+        deps.api,
+        &return_collaterals,
+        &sender,
+    )?);
+
+    // protocol fee sent to collector
+    messages.extend(collateral_transfer_messages(
+        deps.api,
+        &protocol_fee,
+        &deps.api.addr_humanize(&config.collector)?,
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "auction"),
+        attr("position_idx", position_idx.to_string()),
+        attr("owner", position_owner.as_str()),
+        attr(
+            "return_collateral_amount",
+            collateral_attribute(deps.api, &return_collaterals)?,
+        ),
+        attr(
+            "liquidated_amount",
+            liquidated_asset_amount.to_string() + &asset.info.to_string(),
+        ),
+        attr("protocol_fee", collateral_attribute(deps.api, &protocol_fee)?),
+    ]))
+}
+
+pub fn lock_position_funds_hook(
+    deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    position_idx: Uint128,
+    receiver: Addr,
+    prev_balance: Uint128,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let current_balance: Uint128 = deps
+        .querier
+        .query_balance(env.contract.address, config.base_denom.clone())?
+        .amount;
+
+    // the sale proceeds are the balance received since the swap was requested
+    let locked_amount: Uint128 = current_balance.checked_sub(prev_balance)?;
+    if locked_amount.is_zero() {
+        return Err(ContractError::NothingToLock {});
+    }
+
+    let unlock_time: u64 = Uint64::from(env.block.time.seconds())
+        .checked_add(Uint64::from(config.lock_period))?
+        .u64();
+    let receiver_raw = deps.api.addr_canonicalize(receiver.as_str())?;
+    let lock_info: PositionLockInfo = match read_position_lock_info(deps.storage, position_idx) {
+        Ok(mut lock_info) => {
+            // the lock follows the position owner, see move_position
+            if lock_info.receiver != receiver_raw {
+                return Err(ContractError::LockReceiverMismatch {});
+            }
+
+            // locking more funds resets the unlock time
+            lock_info.locked_amount = lock_info.locked_amount.checked_add(locked_amount)?;
+            lock_info.unlock_time = unlock_time;
+            lock_info
+        }
+        Err(_) => PositionLockInfo {
+            idx: position_idx,
+            receiver: receiver_raw,
+            locked_amount,
+            unlock_time,
+        },
+    };
+    store_position_lock_info(deps.storage, &lock_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "lock_position_funds_hook"),
+        attr("position_idx", position_idx.to_string()),
+        attr(
+            "locked_amount",
+            locked_amount.to_string() + &config.base_denom,
+        ),
+        attr(
+            "total_locked_amount",
+            lock_info.locked_amount.to_string() + &config.base_denom,
+        ),
+        attr("unlock_time", unlock_time.to_string()),
+    ]))
+}
+
+pub fn unlock_position_funds(
+    deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    sender: Addr,
+    positions_idx: Vec<Uint128>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(sender.as_str())?;
+
+    let mut unlocked_amount = Uint128::zero();
+    for position_idx in positions_idx.iter() {
+        let lock_info: PositionLockInfo = read_position_lock_info(deps.storage, *position_idx)?;
+        if lock_info.receiver != sender_raw {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if env.block.time.seconds() < lock_info.unlock_time {
+            return Err(ContractError::LockPeriodNotExpired {
+                position_idx: *position_idx,
+                unlock_time: lock_info.unlock_time,
+            });
+        }
+
+        unlocked_amount = unlocked_amount.checked_add(lock_info.locked_amount)?;
+        remove_position_lock_info(deps.storage, *position_idx);
+    }
+
+    if unlocked_amount.is_zero() {
+        return Err(ContractError::NothingToUnlock {});
+    }
+
+    let unlocked_funds = Asset {
+        info: AssetInfo::NativeToken {
+            denom: config.base_denom,
+        },
+        amount: unlocked_amount,
+    };
+
+    Ok(Response::new()
+        .add_message(transfer_message(unlocked_funds.clone(), sender)?)
+        .add_attributes(vec![
+            attr("action", "unlock_position_funds"),
+            attr(
+                "positions_idx",
+                positions_idx
+                    .iter()
+                    .map(|idx| idx.to_string())
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ),
+            attr("unlocked_amount", unlocked_funds.to_string()),
+        ]))
+}
+
+pub fn transfer_position(
+    deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    sender: Addr,
+    position_idx: Uint128,
+    recipient: Addr,
+) -> Result<Response<SeiMsg>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let position: Position = read_position(deps.storage, position_idx)?;
+    assert_position_operator(deps.as_ref(), &env.block, &position, &sender)?;
+
+    // the token is transferred instead
+    if read_position_nft(deps.storage, position_idx)?.is_some() {
+        return Err(ContractError::NftPosition {});
+    }
+
+    let position_owner = deps.api.addr_humanize(&position.owner)?;
+    let (_, messages) = move_position(deps, &config, position_idx, &recipient)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "transfer_position"),
+        attr("position_idx", position_idx.to_string()),
+        attr("from", position_owner.as_str()),
+        attr("to", recipient.as_str()),
+    ]))
+}
+
+pub fn approve_position(
+    deps: DepsMut<SeiQueryWrapper>,
+    env: Env,
+    sender: Addr,
+    spender: Addr,
+    expires: Option<Expiration>,
+) -> Result<Response<SeiMsg>, ContractError> {
+    if spender == sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
+    store_position_approval(
+        deps.storage,
+        &deps.api.addr_canonicalize(sender.as_str())?,
+        &deps.api.addr_canonicalize(spender.as_str())?,
+        &expires,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "approve_position"),
+        attr("owner", sender.as_str()),
+        attr("spender", spender.as_str()),
+        attr("expires", expires.to_string()),
+    ]))
+}
+
+pub fn revoke_position(
+    deps: DepsMut<SeiQueryWrapper>,
+    sender: Addr,
+    spender: Addr,
+) -> Result<Response<SeiMsg>, ContractError> {
+    remove_position_approval(
+        deps.storage,
+        &deps.api.addr_canonicalize(sender.as_str())?,
+        &deps.api.addr_canonicalize(spender.as_str())?,
+    );
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_position"),
+        attr("owner", sender.as_str()),
+        attr("spender", spender.as_str()),
+    ]))
+}
+
+/// Loads the position with its current owner and settles its stability fee.
+/// A position minted as an NFT moves to the owner of its token when the
+/// token was transferred
+fn load_position(
+    mut deps: DepsMut<SeiQueryWrapper>,
+    env: &Env,
+    config: &Config,
+    position_idx: Uint128,
+) -> StdResult<(Position, Vec<CosmosMsg<SeiMsg>>)> {
+    let position: Position = read_position(deps.storage, position_idx)?;
+    let position_owner = read_position_owner(deps.as_ref(), &position)?;
+    let (mut position, mut messages) =
+        if deps.api.addr_canonicalize(position_owner.as_str())? == position.owner {
+            (position, vec![])
+        } else {
+            move_position(deps.branch(), config, position_idx, &position_owner)?
+        };
+
+    messages.extend(settle_stability_fee(deps, env, config, &mut position)?);
+    Ok((position, messages))
+}
+
+/// Sends the stability fee accrued by the position, converted to collateral,
+/// to the collector. Fees settle at the last known prices so a stale price
+/// feed does not block deposits
+fn settle_stability_fee(
+    deps: DepsMut<SeiQueryWrapper>,
+    env: &Env,
+    config: &Config,
+    position: &mut Position,
+) -> StdResult<Vec<CosmosMsg<SeiMsg>>> {
+    let mut asset_config: AssetConfig = read_asset_config(deps.storage, &position.asset.info)?;
+    accrue_stability_fee(&mut asset_config, env.block.time.seconds())?;
+    store_asset_config(deps.storage, &position.asset.info, &asset_config)?;
+
+    let mut messages: Vec<CosmosMsg<SeiMsg>> = vec![];
+    let stability_fee_amount = accrued_stability_fee(position, asset_config.stability_fee_index)?;
+    if !stability_fee_amount.is_zero() {
+        let asset_price: Decimal = match asset_config.end_price {
+            Some(end_price) => end_price,
+            None => load_asset_price(
+                deps.as_ref(),
+                deps.api.addr_humanize(&config.oracle)?,
+                &position.asset.info,
+                None,
+            )?,
+        };
+
+        let mut collaterals: Vec<PositionCollateral> =
+            load_position_collaterals(deps.as_ref(), config, position, None)?;
+        let stability_fee_value: Uint128 = decimal_mul(
+            stability_fee_amount,
+            decimal_division(asset_price, collaterals[0].price)?,
+        )?;
+        let stability_fee: Vec<PositionCollateral> =
+            take_collateral(&mut collaterals, stability_fee_value)?;
+        update_position_collaterals(position, &collaterals);
+
+        messages.extend(collateral_transfer_messages(
+            deps.api,
+            &stability_fee,
+            &deps.api.addr_humanize(&config.collector)?,
+        )?);
+    }
+
+    if position.stability_fee_index != Some(asset_config.stability_fee_index) {
+        position.stability_fee_index = Some(asset_config.stability_fee_index);
+        store_position(deps.storage, position.idx, position)?;
+    }
+
+    Ok(messages)
+}
+
+/// Stability fee index of the asset at the block time. Fees accrue linearly
+/// between updates and stop once the asset is deprecated
+pub fn stability_fee_index(asset_config: &AssetConfig, block_time: u64) -> StdResult<Decimal> {
+    if asset_config.stability_fee_rate.is_zero()
+        || asset_config.end_price.is_some()
+        || block_time <= asset_config.stability_fee_last_update
+    {
+        return Ok(asset_config.stability_fee_index);
+    }
+
+    let accrued_rate: Decimal = decimal_multiplication(
+        asset_config.stability_fee_rate,
+        decimal_from_ratio(
+            Uint128::from(block_time - asset_config.stability_fee_last_update),
+            Uint128::from(SECONDS_PER_YEAR),
+        )?,
+    )?;
+    decimal_multiplication(
+        asset_config.stability_fee_index,
+        Decimal::one().checked_add(accrued_rate)?,
+    )
+}
+
+/// Moves the stability fee index of the asset to the block time
+pub fn accrue_stability_fee(asset_config: &mut AssetConfig, block_time: u64) -> StdResult<()> {
+    asset_config.stability_fee_index = stability_fee_index(asset_config, block_time)?;
+    asset_config.stability_fee_last_update =
+        std::cmp::max(asset_config.stability_fee_last_update, block_time);
+    Ok(())
+}
+
+/// Debt accrued by the position since its last settlement, in asset unit
+fn accrued_stability_fee(position: &Position, stability_fee_index: Decimal) -> StdResult<Uint128> {
+    match position.stability_fee_index {
+        Some(position_index) if !position.asset.amount.is_zero() => Ok(decimal_mul(
+            position.asset.amount,
+            decimal_division(stability_fee_index, position_index)?,
+        )?
+            .saturating_sub(position.asset.amount)),
+        _ => Ok(Uint128::zero()),
+    }
+}
+
+/// Positions minted as NFTs belong to the owner of their token
+fn read_position_owner(deps: Deps<SeiQueryWrapper>, position: &Position) -> StdResult<Addr> {
+    match read_position_nft(deps.storage, position.idx)? {
+        Some(position_nft) => query_nft_owner(deps, &position_nft, position.idx),
+        None => deps.api.addr_humanize(&position.owner),
+    }
+}
+
+/// Moves the position to the new owner; the short rewards follow the position
+fn move_position(
+    deps: DepsMut<SeiQueryWrapper>,
+    config: &Config,
+    position_idx: Uint128,
+    new_owner: &Addr,
+) -> StdResult<(Position, Vec<CosmosMsg<SeiMsg>>)> {
+    let previous_owner = deps
+        .api
+        .addr_humanize(&read_position(deps.storage, position_idx)?.owner)?;
+    let position = transfer_position_owner(
+        deps.storage,
+        position_idx,
+        &deps.api.addr_canonicalize(new_owner.as_str())?,
+    )?;
+
+    // locked short sale proceeds move with the position, like its short tokens
+    if let Ok(mut lock_info) = read_position_lock_info(deps.storage, position_idx) {
+        lock_info.receiver = position.owner.clone();
+        store_position_lock_info(deps.storage, &lock_info)?;
+    }
+
+    let mut messages: Vec<CosmosMsg<SeiMsg>> = vec![];
+    if is_short_position(deps.storage, position_idx)? && !position.asset.amount.is_zero() {
+        let asset_info: AssetInfo = position.asset.info.to_normal(deps.api)?;
+        messages.extend(decrease_short_token_messages(
+            deps.api,
+            config,
+            &previous_owner,
+            &asset_info,
+            position.asset.amount,
+        )?);
+        messages.extend(increase_short_token_messages(
+            deps.api,
+            config,
+            new_owner,
+            &asset_info,
+            position.asset.amount,
+        )?);
+    }
+
+    Ok((position, messages))
+}
+
+/// Amounts moved by a position operation, reported by the simulations
+#[derive(Default)]
+pub struct PositionOutcome {
+    pub mint_amount: Uint128,
+    pub protocol_fee: Vec<Asset>,
+    pub refund_collateral: Vec<Asset>,
+}
+
+/// A collateral of a position with its collateral oracle price and multiplier
+#[derive(Clone)]
+struct PositionCollateral {
+    info: AssetInfoRaw,
+    amount: Uint128,
+    price: Decimal,
+    multiplier: Decimal,
+    is_revoked: bool,
+}
+
+/// Loads the collaterals of the position, the main collateral first
+fn load_position_collaterals(
+    deps: Deps<SeiQueryWrapper>,
+    config: &Config,
+    position: &Position,
+    block_time: Option<u64>,
+) -> StdResult<Vec<PositionCollateral>> {
+    let collateral_oracle: Addr = deps.api.addr_humanize(&config.collateral_oracle)?;
+    std::iter::once(&position.collateral)
+        .chain(position.other_collaterals.iter())
+        .map(|collateral| {
+            let (price, multiplier, is_revoked) = load_collateral_info(
+                deps,
+                collateral_oracle.clone(),
+                &collateral.info,
+                block_time,
+            )?;
+
+            Ok(PositionCollateral {
+                info: collateral.info.clone(),
+                amount: collateral.amount,
+                price,
+                multiplier,
+                is_revoked,
+            })
+        })
+        .collect()
+}
+
+/// Value of the collaterals in main collateral unit
+fn collateral_value(collaterals: &[PositionCollateral]) -> StdResult<Uint128> {
+    let main_collateral = &collaterals[0];
+    collaterals
+        .iter()
+        .skip(1)
+        .try_fold(main_collateral.amount, |value, collateral| {
+            let price_in_main_collateral =
+                decimal_division(collateral.price, main_collateral.price)?;
+            Ok(value.checked_add(decimal_mul(collateral.amount, price_in_main_collateral)?)?)
+        })
+}
+
+/// Value of the collaterals in main collateral unit, each weighted by
+/// its multiplier relative to the main collateral multiplier, so the
+/// minimum collateral ratio can be checked against the main multiplier
+fn weighted_collateral_value(collaterals: &[PositionCollateral]) -> StdResult<Uint128> {
+    let main_collateral = &collaterals[0];
+    collaterals
+        .iter()
+        .skip(1)
+        .try_fold(main_collateral.amount, |value, collateral| {
+            let weighted_price_in_main_collateral = decimal_multiplication(
+                decimal_division(collateral.price, main_collateral.price)?,
+                decimal_division(main_collateral.multiplier, collateral.multiplier)?,
+            )?;
+            Ok(value.checked_add(decimal_mul(
+                collateral.amount,
+                weighted_price_in_main_collateral,
+            )?)?)
+        })
+}
+
+/// Takes the amount, in main collateral unit, out of the collaterals,
+/// the main collateral first, and returns the collaterals taken
+fn take_collateral(
+    collaterals: &mut [PositionCollateral],
+    amount: Uint128,
+) -> StdResult<Vec<PositionCollateral>> {
+    let take_all = amount >= collateral_value(collaterals)?;
+    let main_collateral_price = collaterals[0].price;
+
+    let mut left_amount = amount;
+    let mut taken: Vec<PositionCollateral> = vec![];
+    for collateral in collaterals.iter_mut() {
+        let price_in_main_collateral = decimal_division(collateral.price, main_collateral_price)?;
+        let taken_amount = if take_all {
+            collateral.amount
+        } else {
+            std::cmp::min(
+                collateral.amount,
+                decimal_mul(left_amount, reverse_decimal(price_in_main_collateral)?)?,
+            )
+        };
+
+        left_amount =
+            left_amount.saturating_sub(decimal_mul(taken_amount, price_in_main_collateral)?);
+        collateral.amount = collateral.amount.checked_sub(taken_amount)?;
+        taken.push(PositionCollateral {
+            amount: taken_amount,
+            ..collateral.clone()
+        });
+    }
+
+    Ok(taken)
+}
+
+/// Stores the collateral amounts back into the position. Emptied collaterals
+/// are dropped and the next one becomes the main collateral
+fn update_position_collaterals(position: &mut Position, collaterals: &[PositionCollateral]) {
+    let mut position_collaterals = collaterals.iter().map(|collateral| AssetRaw {
+        info: collateral.info.clone(),
+        amount: collateral.amount,
+    });
+
+    if let Some(main_collateral) = position_collaterals.next() {
+        position.collateral = main_collateral;
+    }
+    position.other_collaterals = position_collaterals
+        .filter(|collateral| !collateral.amount.is_zero())
+        .collect();
+    if position.collateral.amount.is_zero() && !position.other_collaterals.is_empty() {
+        position.collateral = position.other_collaterals.remove(0);
+    }
+}
+
+fn collateral_transfer_messages(
+    api: &dyn Api,
+    collaterals: &[PositionCollateral],
+    recipient: &Addr,
+) -> StdResult<Vec<CosmosMsg<SeiMsg>>> {
+    collaterals
+        .iter()
+        .filter(|collateral| !collateral.amount.is_zero())
+        .map(|collateral| {
+            transfer_message(
+                Asset {
+                    info: collateral.info.to_normal(api)?,
+                    amount: collateral.amount,
+                },
+                recipient.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Non-empty collaterals as assets, reported in the operation outcome
+fn collateral_assets(api: &dyn Api, collaterals: &[PositionCollateral]) -> StdResult<Vec<Asset>> {
+    collaterals
+        .iter()
+        .filter(|position_collateral| !position_collateral.amount.is_zero())
+        .map(|position_collateral| {
+            Ok(Asset {
+                info: position_collateral.info.to_normal(api)?,
+                amount: position_collateral.amount,
+            })
+        })
+        .collect()
+}
+
+fn collateral_attribute(api: &dyn Api, collaterals: &[PositionCollateral]) -> StdResult<String> {
+    let assets: Vec<String> = collaterals
+        .iter()
+        .filter(|collateral| !collateral.amount.is_zero())
+        .map(|collateral| {
+            Ok(collateral.amount.to_string() + &collateral.info.to_normal(api)?.to_string())
+        })
+        .collect::<StdResult<Vec<String>>>()?;
+
+    if assets.is_empty() {
+        return Ok(Uint128::zero().to_string() + &collaterals[0].info.to_normal(api)?.to_string());
+    }
+
+    Ok(assets.join(","))
+}
+
+/// Mints the asset to this contract, sells it through the base denom pair
+/// and locks the proceeds for the position owner
+#[allow(clippy::too_many_arguments)]
+fn short_sale_messages(
+    deps: Deps<SeiQueryWrapper>,
+    env: &Env,
+    config: &Config,
+    position_owner: &Addr,
+    position_idx: Uint128,
+    asset_info: &AssetInfo,
+    amount: Uint128,
+    short_params: ShortParams,
+) -> StdResult<Vec<CosmosMsg<SeiMsg>>> {
+    let pair_info = query_pair_info(
+        &QuerierWrapper::<Empty>::new(&*deps.querier),
+        deps.api.addr_humanize(&config.tswap_factory)?,
+        &[
+            AssetInfo::NativeToken {
+                denom: config.base_denom.clone(),
+            },
+            asset_info.clone(),
+        ],
+    )?;
+
+    let prev_balance: Uint128 = deps
+        .querier
+        .query_balance(env.contract.address.to_string(), config.base_denom.clone())?
+        .amount;
+
+    let mut messages: Vec<CosmosMsg<SeiMsg>> = match asset_info {
+        AssetInfo::Token { contract_addr } => vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.clone(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: env.contract.address.to_string(),
+                    amount,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.clone(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: pair_info.contract_addr,
+                    amount,
+                    msg: to_binary(&PairCw20HookMsg::Swap {
+                        belief_price: short_params.belief_price,
+                        max_spread: short_params.max_spread,
+                        to: None,
+                        deadline: None,
+                    })?,
+                })?,
+            }),
+        ],
+        AssetInfo::NativeToken { denom } => {
+            let minted_coin = Coin {
+                denom: denom.clone(),
+                amount,
+            };
+            vec![
+                CosmosMsg::Custom(SeiMsg::MintTokens {
+                    amount: minted_coin.clone(),
+                }),
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: pair_info.contract_addr,
+                    funds: vec![minted_coin],
+                    msg: to_binary(&PairExecuteMsg::Swap {
+                        offer_asset: Asset {
+                            info: asset_info.clone(),
+                            amount,
+                        },
+                        belief_price: short_params.belief_price,
+                        max_spread: short_params.max_spread,
+                        to: None,
+                        deadline: None,
+                    })?,
+                }),
+            ]
+        }
+    };
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::LockPositionFundsHook {
+            position_idx,
+            receiver: position_owner.to_string(),
+            prev_balance,
+        })?,
+    }));
+
+    messages.extend(increase_short_token_messages(
+        deps.api,
+        config,
+        position_owner,
+        asset_info,
+        amount,
+    )?);
+
+    Ok(messages)
+}
+
+/// short staking pools only exist for cw20 mAssets
+fn increase_short_token_messages(
+    api: &dyn Api,
+    config: &Config,
+    position_owner: &Addr,
+    asset_info: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<Vec<CosmosMsg<SeiMsg>>> {
+    let asset_token = match asset_info {
+        AssetInfo::Token { contract_addr } => contract_addr.clone(),
+        AssetInfo::NativeToken { .. } => return Ok(vec![]),
+    };
+
+    Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: api.addr_humanize(&config.staking)?.to_string(),
+        funds: vec![],
+        msg: to_binary(&StakingExecuteMsg::IncreaseShortToken {
+            staker_addr: position_owner.to_string(),
+            asset_token,
+            amount,
+        })?,
+    })])
+}
+
+fn decrease_short_token_messages(
+    api: &dyn Api,
+    config: &Config,
+    position_owner: &Addr,
+    asset_info: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<Vec<CosmosMsg<SeiMsg>>> {
+    let asset_token = match asset_info {
+        AssetInfo::Token { contract_addr } => contract_addr.clone(),
+        AssetInfo::NativeToken { .. } => return Ok(vec![]),
+    };
+
+    Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: api.addr_humanize(&config.staking)?.to_string(),
+        funds: vec![],
+        msg: to_binary(&StakingExecuteMsg::DecreaseShortToken {
+            staker_addr: position_owner.to_string(),
+            asset_token,
+            amount,
+        })?,
+    })])
+}
+
+/// Mints the asset to the recipient. Native assets are tokenfactory
+/// denoms, which are minted to this contract before being sent
+fn mint_messages(
+    asset_info: &AssetInfo,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<Vec<CosmosMsg<SeiMsg>>> {
+    match asset_info {
+        AssetInfo::Token { contract_addr } => Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.clone(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+        })]),
+        AssetInfo::NativeToken { denom } => {
+            let minted_coin = Coin {
+                denom: denom.clone(),
+                amount,
+            };
+            Ok(vec![
+                CosmosMsg::Custom(SeiMsg::MintTokens {
+                    amount: minted_coin.clone(),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: recipient.to_string(),
+                    amount: vec![minted_coin],
+                }),
+            ])
+        }
+    }
+}
+
+/// Burns the asset held by this contract
+fn burn_message(asset_info: &AssetInfo, amount: Uint128) -> StdResult<CosmosMsg<SeiMsg>> {
+    match asset_info {
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.clone(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        })),
+        AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Custom(SeiMsg::BurnTokens {
+            amount: Coin {
+                denom: denom.clone(),
+                amount,
+            },
+        })),
+    }
+}
+
+/// Same as `Asset::into_msg`, for responses carrying sei messages
+fn transfer_message(asset: Asset, recipient: Addr) -> StdResult<CosmosMsg<SeiMsg>> {
+    match asset.info {
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: asset.amount,
+            })?,
+        })),
+        AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom,
+                amount: asset.amount,
+            }],
+        })),
+    }
+}
+
+/// Burns the NFT of a removed position, if any, and releases its locked
+/// short sale proceeds to the receiver regardless of the unlock time
+fn release_position(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    base_denom: &str,
+    position_idx: Uint128,
+) -> StdResult<Vec<CosmosMsg<SeiMsg>>> {
+    let mut messages: Vec<CosmosMsg<SeiMsg>> = vec![];
+    if let Some(position_nft) = read_position_nft(storage, position_idx)? {
+        remove_position_nft(storage, position_idx);
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: api.addr_humanize(&position_nft)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw721ExecuteMsg::Burn {
+                token_id: position_idx.to_string(),
+            })?,
+        }));
+    }
+
+    messages.extend(release_short_position(
+        storage,
+        api,
+        base_denom,
+        position_idx,
+    )?);
+    Ok(messages)
+}
+
+fn release_short_position(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    base_denom: &str,
+    position_idx: Uint128,
+) -> StdResult<Vec<CosmosMsg<SeiMsg>>> {
+    if !is_short_position(storage, position_idx)? {
+        return Ok(vec![]);
+    }
+    remove_short_position(storage, position_idx);
+
+    match read_position_lock_info(storage, position_idx) {
+        Ok(lock_info) => {
+            remove_position_lock_info(storage, position_idx);
+            let released_funds = Asset {
+                info: AssetInfo::NativeToken {
+                    denom: base_denom.to_string(),
+                },
+                amount: lock_info.locked_amount,
+            };
+
+            Ok(vec![
+                transfer_message(released_funds, api.addr_humanize(&lock_info.receiver)?)?
+            ])
+        }
+        Err(_) => Ok(vec![]),
+    }
+}
+
+pub fn query_position(
+    deps: Deps<SeiQueryWrapper>,
+    env: Env,
+    position_idx: Uint128,
+) -> StdResult<PositionResponse> {
+    let position: Position = read_position(deps.storage, position_idx)?;
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &position.asset.info)?;
+    let resp = PositionResponse {
+        idx: position.idx,
+        owner: read_position_owner(deps, &position)?.to_string(),
+        collateral: position.collateral.to_normal(deps.api)?,
+        asset: position.asset.to_normal(deps.api)?,
+        is_short: is_short_position(deps.storage, position.idx)?,
+        other_collaterals: position
+            .other_collaterals
+            .iter()
+            .map(|collateral| collateral.to_normal(deps.api))
+            .collect::<StdResult<Vec<Asset>>>()?,
+        accrued_stability_fee: accrued_stability_fee(
+            &position,
+            stability_fee_index(&asset_config, env.block.time.seconds())?,
+        )?,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_positions(
+    deps: Deps<SeiQueryWrapper>,
+    env: Env,
+    owner_addr: Option<String>,
+    asset_token: Option<String>,
+    start_after: Option<Uint128>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<PositionsResponse> {
+    let positions: Vec<Position> = if let Some(owner_addr) = owner_addr {
+        read_positions_with_user_indexer(
+            deps.storage,
+            &deps.api.addr_canonicalize(&owner_addr)?,
+            start_after,
+            limit,
+            order_by,
+        )?
+    } else if let Some(asset_token) = asset_token {
+        read_positions_with_asset_indexer(
+            deps.storage,
+            &read_asset_info(deps.storage, deps.api, &asset_token)?,
+            start_after,
+            limit,
+            order_by,
+        )?
+    } else {
+        read_positions(deps.storage, start_after, limit, order_by)?
+    };
+
+    let position_responses: StdResult<Vec<PositionResponse>> = positions
+        .iter()
+        .map(|position| {
+            let asset_config: AssetConfig =
+                read_asset_config(deps.storage, &position.asset.info)?;
+            Ok(PositionResponse {
+                idx: position.idx,
+                owner: read_position_owner(deps, position)?.to_string(),
+                collateral: position.collateral.to_normal(deps.api)?,
+                asset: position.asset.to_normal(deps.api)?,
+                is_short: is_short_position(deps.storage, position.idx)?,
+                other_collaterals: position
+                    .other_collaterals
+                    .iter()
+                    .map(|collateral| collateral.to_normal(deps.api))
+                    .collect::<StdResult<Vec<Asset>>>()?,
+                accrued_stability_fee: accrued_stability_fee(
+                    position,
+                    stability_fee_index(&asset_config, env.block.time.seconds())?,
+                )?,
+            })
+        })
+        .collect();
+
+    Ok(PositionsResponse {
+        positions: position_responses?,
+    })
+}
+
+pub fn query_position_health(
+    deps: Deps<SeiQueryWrapper>,
+    env: Env,
+    position_idx: Uint128,
+) -> StdResult<PositionHealthResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let position: Position = read_position(deps.storage, position_idx)?;
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &position.asset.info)?;
+
+    let oracle: Addr = deps.api.addr_humanize(&config.oracle)?;
+    let asset_price: Decimal = load_asset_price(
+        deps,
+        oracle,
+        &position.asset.info,
+        Some(env.block.time.seconds()),
+    )?;
+
+    let mut collaterals: Vec<PositionCollateral> = load_position_collaterals(
+        deps,
+        &config,
+        &position,
+        Some(env.block.time.seconds()),
+    )?;
+
+    // ignore multipliers for de-listed assets
+    if asset_config.end_price.is_some() {
+        for collateral in collaterals.iter_mut() {
+            collateral.multiplier = Decimal::one();
+        }
+    }
+
+    let main_collateral = collaterals[0].clone();
+    let position_collateral_value: Uint128 = collateral_value(&collaterals)?;
+    let weighted_collateral: Uint128 = weighted_collateral_value(&collaterals)?;
+
+    let asset_price_in_collateral_asset: Decimal =
+        decimal_division(asset_price, main_collateral.price)?;
+    let asset_value_in_collateral_asset: Uint128 =
+        decimal_mul(position.asset.amount, asset_price_in_collateral_asset)?;
+
+    // the same rounding as the execute paths
+    let required_collateral = |asset_value_in_collateral_asset: Uint128| -> StdResult<Uint128> {
+        decimal_mul(
+            decimal_mul(
+                asset_value_in_collateral_asset,
+                asset_config.min_collateral_ratio,
+            )?,
+            main_collateral.multiplier,
+        )
+    };
+
+    let collateral_ratio: Option<Decimal> = if asset_value_in_collateral_asset.is_zero() {
+        None
+    } else {
+        Some(decimal_from_ratio(
+            position_collateral_value,
+            asset_value_in_collateral_asset,
+        )?)
+    };
+
+    // the position can be auctioned once the asset value exceeds
+    // weighted collateral / (min_collateral_ratio * multiplier)
+    let required_collateral_per_price = required_collateral(position.asset.amount)?;
+    let liquidation_price: Option<Decimal> =
+        if required_collateral_per_price.is_zero() || asset_config.end_price.is_some() {
+            None
+        } else {
+            Some(decimal_multiplication(
+                main_collateral.price,
+                decimal_from_ratio(weighted_collateral, required_collateral_per_price)?,
+            )?)
+        };
+
+    // nothing can be minted once the asset is delisted, minting is paused
+    // or the pre-IPO mint period has ended
+    let minting_stopped = asset_config.end_price.is_some()
+        || collaterals.iter().any(|collateral| collateral.is_revoked)
+        || assert_not_paused(deps.storage, &position.asset.info, PauseOperation::Mint).is_err()
+        || assert_mint_period(&asset_config, env.block.time.seconds()).is_err();
+
+    // largest asset amount passing the mint check; found by bisection
+    // so the rounding matches the execute path exactly
+    let mintable_amount: Uint128 = if minting_stopped {
+        Uint128::zero()
+    } else {
+        let is_collateralized = |asset_amount: Uint128| -> bool {
+            decimal_mul(asset_amount, asset_price_in_collateral_asset)
+                .and_then(required_collateral)
+                .map(|required| required <= weighted_collateral)
+                .unwrap_or(false)
+        };
+
+        let (mut low, mut high) = (0u128, u128::MAX);
+        while low < high {
+            let mid = high - (high - low) / 2;
+            if is_collateralized(Uint128::new(mid)) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        let mintable_amount = Uint128::new(low).saturating_sub(position.asset.amount);
+
+        // the mint cap bounds the amount left to mint across positions
+        let utilization = read_mint_utilization(deps.storage, &position.asset.info)?;
+        match utilization.cap {
+            Some(cap) => std::cmp::min(mintable_amount, cap.saturating_sub(utilization.total)),
+            None => mintable_amount,
+        }
+    };
+
+    // the main collateral counts at its full amount in the weighted value
+    let withdrawable_collateral = std::cmp::min(
+        main_collateral.amount,
+        weighted_collateral.saturating_sub(required_collateral(asset_value_in_collateral_asset)?),
+    );
+
+    // the multipliers of the other collaterals are averaged over the collateral value
+    let mut min_collateral_ratio: Decimal =
+        decimal_multiplication(asset_config.min_collateral_ratio, main_collateral.multiplier)?;
+    if !weighted_collateral.is_zero() && weighted_collateral != position_collateral_value {
+        min_collateral_ratio = decimal_multiplication(
+            min_collateral_ratio,
+            decimal_from_ratio(position_collateral_value, weighted_collateral)?,
+        )?;
+    }
+
+    Ok(PositionHealthResponse {
+        idx: position_idx,
+        collateral_ratio,
+        min_collateral_ratio,
+        liquidation_price,
+        mintable_amount,
+        withdrawable_collateral,
+    })
+}
+
+pub fn query_positions_health(
+    deps: Deps<SeiQueryWrapper>,
+    env: Env,
+    positions_idx: Vec<Uint128>,
+) -> StdResult<PositionsHealthResponse> {
+    let positions: StdResult<Vec<PositionHealthResponse>> = positions_idx
+        .into_iter()
+        .map(|position_idx| query_position_health(deps, env.clone(), position_idx))
+        .collect();
+
+    Ok(PositionsHealthResponse {
+        positions: positions?,
+    })
+}
+
+pub fn query_next_position_idx(deps: Deps<SeiQueryWrapper>) -> StdResult<NextPositionIdxResponse> {
+    let idx = read_position_idx(deps.storage)?;
+    let resp = NextPositionIdxResponse {
+        next_position_idx: idx,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_position_lock_info(
+    deps: Deps<SeiQueryWrapper>,
+    position_idx: Uint128,
+) -> StdResult<PositionLockInfoResponse> {
+    let lock_info: PositionLockInfo = read_position_lock_info(deps.storage, position_idx)?;
+    let resp = PositionLockInfoResponse {
+        idx: lock_info.idx,
+        receiver: deps.api.addr_humanize(&lock_info.receiver)?.to_string(),
+        locked_amount: lock_info.locked_amount,
+        unlock_time: lock_info.unlock_time,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_position_approval(
+    deps: Deps<SeiQueryWrapper>,
+    owner: String,
+    spender: String,
+) -> StdResult<PositionApprovalResponse> {
+    let expires: Expiration = read_position_approval(
+        deps.storage,
+        &deps.api.addr_canonicalize(&owner)?,
+        &deps.api.addr_canonicalize(&spender)?,
+    )?;
+
+    Ok(PositionApprovalResponse {
+        owner,
+        spender,
+        expires,
+    })
 }
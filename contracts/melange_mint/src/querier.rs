@@ -1,120 +1,182 @@
 use cosmwasm_std::{
-    to_binary, Addr, Decimal, Deps, QuerierWrapper, QueryRequest, StdResult, WasmQuery, Uint128
+    to_binary, Addr, CanonicalAddr, Decimal, Deps, QuerierWrapper, QueryRequest, StdResult,
+    Uint128, WasmQuery,
 };
 
 use crate::{
+    error::ContractError,
     math::decimal_division,
-    state::{read_config, read_fixed_price, Config},
+    state::{read_config, read_fixed_price, read_pre_ipo_price, Config},
 };
 use melange_protocol::collateral_oracle::{
     CollateralInfoResponse, CollateralPriceResponse, QueryMsg as CollateralOracleQueryMsg,
 };
+use melange_protocol::cw721::{OwnerOfResponse, QueryMsg as Cw721QueryMsg};
+use melange_protocol::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
 use terraswap::asset::AssetInfoRaw;
 use sei_cosmwasm::SeiQueryWrapper;
 const PRICE_EXPIRE_TIME: u64 = 60;
 
+/// Loads the asset price in base denom. When `block_time` is provided,
+/// prices older than `PRICE_EXPIRE_TIME` are rejected
 pub fn load_asset_price(
     deps: Deps<SeiQueryWrapper>,
     oracle: Addr,
     asset: &AssetInfoRaw,
-    check_expire: bool,
-) -> StdResult<Decimal> {
+    block_time: Option<u64>,
+) -> Result<Decimal, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
-    let stored_price = read_fixed_price(deps.storage, asset);
+    // deprecated assets are priced at their end price, pre-IPO assets at their fixed price
+    let fixed_price = read_fixed_price(deps.storage, asset)
+        .or_else(|| read_pre_ipo_price(deps.storage, asset));
 
-    let price: Decimal = if let Some(stored_price) = stored_price {
-        stored_price
+    let price: Decimal = if let Some(fixed_price) = fixed_price {
+        fixed_price
     } else {
         let asset_denom: String = (asset.to_normal(deps.api)?).to_string();
         if asset_denom == config.base_denom {
             Decimal::one()
         } else {
-            Decimal::one()
-            // to-do: fetch price from oracle
-            // query_price(&deps.querier, oracle, asset_denom, None, check_expire)?
+            query_price(&deps.querier, oracle, asset_denom, None, block_time)?
         }
     };
 
     Ok(price)
 }
 
+/// Loads the collateral price, multiplier and revoked flag
 pub fn load_collateral_info(
     deps: Deps<SeiQueryWrapper>,
     collateral_oracle: Addr,
     collateral: &AssetInfoRaw,
-    check_expire: bool,
-) -> StdResult<(Decimal, Decimal, bool)> {
-    // let config: Config = read_config(deps.storage)?;
-    // let collateral_denom: String = (collateral.to_normal(deps.api)?).to_string();
+    block_time: Option<u64>,
+) -> Result<(Decimal, Decimal, bool), ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_denom: String = (collateral.to_normal(deps.api)?).to_string();
 
     // base collateral
-    // if collateral_denom == config.base_denom {
-    //     return Ok((Decimal::one(), Decimal::one(), false));
-    // }
-
-    // check if the collateral is a revoked mAsset,
-    // let end_price = read_fixed_price(deps.storage, collateral);
-
-    // if let Some(end_price) = end_price {
-    //     // load collateral_multiplier from collateral oracle
-    //     // if asset is revoked, no need to check for old price
-    //     let (collateral_multiplier, _) =
-    //         query_collateral_info(&deps.querier, collateral_oracle, collateral_denom)?;
-
-    //     Ok((end_price, collateral_multiplier, true))
-    // } else {
-    //     // load collateral info from collateral oracle
-    //     let (collateral_oracle_price, collateral_multiplier, is_revoked) = query_collateral(
-    //         &deps.querier,
-    //         collateral_oracle,
-    //         collateral_denom,
-    //         check_expire,
-    //     )?;
-
-    //     Ok((collateral_oracle_price, collateral_multiplier, is_revoked))
-    // }
-    Ok((Decimal::one(), Decimal::one(), false))
+    if collateral_denom == config.base_denom {
+        return Ok((Decimal::one(), Decimal::one(), false));
+    }
+
+    // check if the collateral is a revoked mAsset
+    let end_price = read_fixed_price(deps.storage, collateral);
+
+    if let Some(end_price) = end_price {
+        // load collateral_multiplier from collateral oracle
+        // if asset is revoked, no need to check for old price
+        let (collateral_multiplier, _) =
+            query_collateral_info(&deps.querier, collateral_oracle, collateral_denom)?;
+
+        Ok((end_price, collateral_multiplier, true))
+    } else {
+        // load collateral info from collateral oracle
+        let (collateral_oracle_price, collateral_multiplier, is_revoked) = query_collateral(
+            &deps.querier,
+            collateral_oracle,
+            collateral_denom,
+            block_time,
+        )?;
+
+        Ok((collateral_oracle_price, collateral_multiplier, is_revoked))
+    }
 }
 
 pub fn query_price(
-    querier: &QuerierWrapper,
+    querier: &QuerierWrapper<SeiQueryWrapper>,
     oracle: Addr,
     base_asset: String,
     quote_asset: Option<String>,
-    check_expire: bool,
-) -> StdResult<Decimal> {
-    let timeframe: Option<u64> = if check_expire {
-        Some(PRICE_EXPIRE_TIME)
+    block_time: Option<u64>,
+) -> Result<Decimal, ContractError> {
+    let timeframe: Option<u64> = block_time.map(|_| PRICE_EXPIRE_TIME);
+
+    let base_res: PriceResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: oracle.to_string(),
+        msg: to_binary(&OracleQueryMsg::Price {
+            asset_token: base_asset,
+            timeframe,
+        })?,
+    }))?;
+    assert_price_not_expired(base_res.last_updated, block_time)?;
+
+    let rate: Decimal = if let Some(quote_asset) = quote_asset {
+        let quote_res: PriceResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: oracle.to_string(),
+            msg: to_binary(&OracleQueryMsg::Price {
+                asset_token: quote_asset,
+                timeframe,
+            })?,
+        }))?;
+        assert_price_not_expired(quote_res.last_updated, block_time)?;
+
+        decimal_division(base_res.rate, quote_res.rate)?
     } else {
-        None
+        base_res.rate
     };
-    let fixed_price = Decimal::new(Uint128::new(1u128));
-    Ok(fixed_price)
+
+    Ok(rate)
 }
 
 // queries the collateral oracle to get the asset rate and multiplier
 pub fn query_collateral(
-    querier: &QuerierWrapper,
+    querier: &QuerierWrapper<SeiQueryWrapper>,
     collateral_oracle: Addr,
     asset: String,
-    check_expire: bool,
-) -> StdResult<(Decimal, Decimal, bool)> {
-    let timeframe: Option<u64> = if check_expire {
-        Some(PRICE_EXPIRE_TIME)
-    } else {
-        None
-    };
-    let fixed_price = Decimal::new(Uint128::new(1u128));
-    Ok((fixed_price, fixed_price, true))
+    block_time: Option<u64>,
+) -> Result<(Decimal, Decimal, bool), ContractError> {
+    let timeframe: Option<u64> = block_time.map(|_| PRICE_EXPIRE_TIME);
+
+    let res: CollateralPriceResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: collateral_oracle.to_string(),
+        msg: to_binary(&CollateralOracleQueryMsg::CollateralPrice { asset, timeframe })?,
+    }))?;
+    assert_price_not_expired(res.last_updated, block_time)?;
+
+    Ok((res.rate, res.multiplier, res.is_revoked))
 }
 
 // queries only collateral information (multiplier and is_revoked), without price
 pub fn query_collateral_info(
-    querier: &QuerierWrapper,
+    querier: &QuerierWrapper<SeiQueryWrapper>,
     collateral_oracle: Addr,
     asset: String,
 ) -> StdResult<(Decimal, bool)> {
-    let fixed_price = Decimal::new(Uint128::new(1u128));
-    Ok((fixed_price, true))
+    let res: CollateralInfoResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: collateral_oracle.to_string(),
+        msg: to_binary(&CollateralOracleQueryMsg::CollateralAssetInfo { asset })?,
+    }))?;
+
+    Ok((res.multiplier, res.is_revoked))
+}
+
+fn assert_price_not_expired(
+    last_updated: u64,
+    block_time: Option<u64>,
+) -> Result<(), ContractError> {
+    if let Some(block_time) = block_time {
+        if last_updated.saturating_add(PRICE_EXPIRE_TIME) < block_time {
+            return Err(ContractError::StalePrice {});
+        }
+    }
+
+    Ok(())
+}
+
+/// queries the owner of the token representing the position
+pub fn query_nft_owner(
+    deps: Deps<SeiQueryWrapper>,
+    position_nft: &CanonicalAddr,
+    position_idx: Uint128,
+) -> StdResult<Addr> {
+    let res: OwnerOfResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: deps.api.addr_humanize(position_nft)?.to_string(),
+        msg: to_binary(&Cw721QueryMsg::OwnerOf {
+            token_id: position_idx.to_string(),
+            include_expired: None,
+        })?,
+    }))?;
+
+    deps.api.addr_validate(&res.owner)
 }
@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{
+    Addr, Decimal, Deps, DepsMut, Env, Order, Record, Response, StdResult, Storage, Uint128,
+};
+use melange_protocol::mint::{ShortParams, SimulationResponse};
+use sei_cosmwasm::{SeiMsg, SeiQueryWrapper};
+use terraswap::asset::{Asset, AssetInfo};
+
+use crate::{
+    error::ContractError,
+    positions::{
+        _burn, _mint, _open_position, _withdraw, query_position_health, PositionOutcome,
+    },
+    state::{read_position, read_position_idx},
+};
+
+/// Storage keeping the writes of a simulated execution in memory,
+/// on top of the read-only contract storage
+pub struct SimulationStorage<'a> {
+    storage: &'a dyn Storage,
+    writes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<'a> SimulationStorage<'a> {
+    pub fn new(storage: &'a dyn Storage) -> Self {
+        SimulationStorage {
+            storage,
+            writes: BTreeMap::new(),
+        }
+    }
+}
+
+impl<'a> Storage for SimulationStorage<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.writes.get(key) {
+            Some(value) => value.clone(),
+            None => self.storage.get(key),
+        }
+    }
+
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'b> {
+        let mut records: BTreeMap<Vec<u8>, Vec<u8>> =
+            self.storage.range(start, end, Order::Ascending).collect();
+
+        for (key, value) in self.writes.iter() {
+            let out_of_range = start.is_some_and(|start| key.as_slice() < start)
+                || end.is_some_and(|end| key.as_slice() >= end);
+            if out_of_range {
+                continue;
+            }
+
+            match value {
+                Some(value) => records.insert(key.clone(), value.clone()),
+                None => records.remove(key),
+            };
+        }
+
+        match order {
+            Order::Ascending => Box::new(records.into_iter()),
+            Order::Descending => Box::new(records.into_iter().rev()),
+        }
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes.insert(key.to_vec(), None);
+    }
+}
+
+fn simulation_deps<'a>(
+    deps: Deps<'a, SeiQueryWrapper>,
+    storage: &'a mut SimulationStorage,
+) -> DepsMut<'a, SeiQueryWrapper> {
+    DepsMut {
+        storage,
+        api: deps.api,
+        querier: deps.querier,
+    }
+}
+
+pub fn query_simulate_open_position(
+    deps: Deps<SeiQueryWrapper>,
+    env: Env,
+    sender: String,
+    collateral: Asset,
+    asset_info: AssetInfo,
+    collateral_ratio: Decimal,
+    short_params: Option<ShortParams>,
+) -> StdResult<SimulationResponse> {
+    let sender: Addr = deps.api.addr_validate(&sender)?;
+    let position_idx = read_position_idx(deps.storage)?;
+
+    let mut storage = SimulationStorage::new(deps.storage);
+    let res = _open_position(
+        simulation_deps(deps, &mut storage),
+        env.clone(),
+        sender,
+        collateral,
+        asset_info,
+        collateral_ratio,
+        short_params,
+    );
+
+    simulation_response(deps, &storage, env, position_idx, res)
+}
+
+pub fn query_simulate_mint(
+    deps: Deps<SeiQueryWrapper>,
+    env: Env,
+    sender: String,
+    position_idx: Uint128,
+    asset: Asset,
+    short_params: Option<ShortParams>,
+) -> StdResult<SimulationResponse> {
+    let sender: Addr = deps.api.addr_validate(&sender)?;
+
+    let mut storage = SimulationStorage::new(deps.storage);
+    let res = _mint(
+        simulation_deps(deps, &mut storage),
+        env.clone(),
+        sender,
+        position_idx,
+        asset,
+        short_params,
+    );
+
+    simulation_response(deps, &storage, env, position_idx, res)
+}
+
+pub fn query_simulate_burn(
+    deps: Deps<SeiQueryWrapper>,
+    env: Env,
+    sender: String,
+    position_idx: Uint128,
+    asset: Asset,
+) -> StdResult<SimulationResponse> {
+    let sender: Addr = deps.api.addr_validate(&sender)?;
+
+    let mut storage = SimulationStorage::new(deps.storage);
+    let res = _burn(
+        simulation_deps(deps, &mut storage),
+        env.clone(),
+        sender,
+        position_idx,
+        asset,
+    );
+
+    simulation_response(deps, &storage, env, position_idx, res)
+}
+
+pub fn query_simulate_withdraw(
+    deps: Deps<SeiQueryWrapper>,
+    env: Env,
+    sender: String,
+    position_idx: Uint128,
+    collateral: Option<Asset>,
+) -> StdResult<SimulationResponse> {
+    let sender: Addr = deps.api.addr_validate(&sender)?;
+
+    let mut storage = SimulationStorage::new(deps.storage);
+    let res = _withdraw(
+        simulation_deps(deps, &mut storage),
+        env.clone(),
+        sender,
+        position_idx,
+        collateral,
+    );
+
+    simulation_response(deps, &storage, env, position_idx, res)
+}
+
+/// Reports the simulated amounts of the operation and
+/// the resulting collateral ratio from the simulated state
+fn simulation_response(
+    deps: Deps<SeiQueryWrapper>,
+    storage: &SimulationStorage,
+    env: Env,
+    position_idx: Uint128,
+    res: Result<(Response<SeiMsg>, PositionOutcome), ContractError>,
+) -> StdResult<SimulationResponse> {
+    let (_, outcome) = res?;
+    let deps = Deps {
+        storage,
+        api: deps.api,
+        querier: deps.querier,
+    };
+
+    // closed positions have no ratio
+    let collateral_ratio: Option<Decimal> = if read_position(deps.storage, position_idx).is_ok() {
+        query_position_health(deps, env, position_idx)?.collateral_ratio
+    } else {
+        None
+    };
+
+    Ok(SimulationResponse {
+        mint_amount: outcome.mint_amount,
+        protocol_fee: outcome.protocol_fee,
+        refund_collateral: outcome.refund_collateral,
+        collateral_ratio,
+    })
+}
+
+#[cfg(test)]
+mod simulation_tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn simulation_storage_overlays_writes() {
+        let mut base = MockStorage::new();
+        base.set(b"a", b"1");
+        base.set(b"b", b"2");
+        base.set(b"c", b"3");
+
+        let mut storage = SimulationStorage::new(&base);
+        storage.set(b"b", b"20");
+        storage.remove(b"c");
+        storage.set(b"d", b"4");
+
+        assert_eq!(storage.get(b"a"), Some(b"1".to_vec()));
+        assert_eq!(storage.get(b"b"), Some(b"20".to_vec()));
+        assert_eq!(storage.get(b"c"), None);
+        assert_eq!(
+            storage
+                .range(Some(b"b"), None, Order::Descending)
+                .collect::<Vec<Record>>(),
+            vec![
+                (b"d".to_vec(), b"4".to_vec()),
+                (b"b".to_vec(), b"20".to_vec()),
+            ]
+        );
+
+        // the contract storage is untouched
+        assert_eq!(base.get(b"b"), Some(b"2".to_vec()));
+        assert_eq!(base.get(b"d"), None);
+    }
+}
@@ -1,19 +1,30 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, Decimal, StdError, StdResult, Storage, Uint128};
+use cosmwasm_std::{Api, CanonicalAddr, Decimal, StdError, StdResult, Storage, Uint128};
 
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
-use melange_protocol::common::OrderBy;
+use crate::error::ContractError;
+use cw20::Expiration;
+use melange_protocol::{common::OrderBy, mint::PauseOperation};
 use std::convert::TryInto;
 use terraswap::asset::{AssetInfoRaw, AssetRaw};
 
 pub static PREFIX_ASSET_CONFIG: &[u8] = b"asset_config";
-static PREFIX_POSITION: &[u8] = b"position";
+pub static PREFIX_POSITION: &[u8] = b"position";
 static PREFIX_INDEX_BY_USER: &[u8] = b"by_user";
 static PREFIX_INDEX_BY_ASSET: &[u8] = b"by_asset";
+static PREFIX_SHORT_POSITION: &[u8] = b"short_position";
+static PREFIX_POSITION_LOCK: &[u8] = b"position_lock";
+static PREFIX_POSITION_APPROVAL: &[u8] = b"position_approval";
+static PREFIX_POSITION_NFT: &[u8] = b"position_nft";
+pub static PREFIX_MINT_UTILIZATION: &[u8] = b"mint_utilization";
+pub static PREFIX_DEPOSIT_UTILIZATION: &[u8] = b"deposit_utilization";
+static PREFIX_PAUSE_INFO: &[u8] = b"pause_info";
 pub static KEY_CONFIG: &[u8] = b"config";
+static KEY_PAUSE_INFO: &[u8] = b"pause_info";
 static KEY_POSITION_IDX: &[u8] = b"position_idx";
+static KEY_UTILIZATION_CURSOR: &[u8] = b"utilization_cursor";
 
 pub fn store_position_idx(storage: &mut dyn Storage, position_idx: Uint128) -> StdResult<()> {
     singleton(storage, KEY_POSITION_IDX).save(&position_idx)
@@ -34,6 +45,9 @@ pub struct Config {
     pub base_denom: String,
     pub token_code_id: u64,
     pub protocol_fee_rate: Decimal,
+    pub lock_period: u64,
+    pub position_nft: Option<CanonicalAddr>, // cw721 contract of the new positions
+    pub guardian: Option<CanonicalAddr>,     // can pause operations but not resume them
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -46,53 +60,86 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AssetConfig {
-    pub token: CanonicalAddr,
+    pub asset_info: AssetInfoRaw, // cw20 token or tokenfactory denom
+    pub auction_discount: Decimal,
     pub min_collateral_ratio: Decimal,
     pub end_price: Option<Decimal>,
+    /// annualized fee accruing on the minted debt, zero when disabled
+    #[serde(default)]
+    pub stability_fee_rate: Decimal,
+    /// cumulative stability fee index, starting at one
+    #[serde(default = "Decimal::one")]
+    pub stability_fee_index: Decimal,
+    /// block time the index was last updated at
+    #[serde(default)]
+    pub stability_fee_last_update: u64,
+    /// set until the IPO of a pre-IPO asset
+    #[serde(default)]
+    pub ipo_params: Option<IpoParams>,
 }
 
+/// Parameters of a pre-IPO asset, minted at a fixed price until its listing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IpoParams {
+    pub mint_end: u64,
+    pub pre_ipo_price: Decimal,
+    pub min_collateral_ratio_after_ipo: Decimal,
+    pub trigger_addr: CanonicalAddr, // oracle feeder allowed to trigger the IPO
+}
+
+/// Asset configs are keyed by the canonical token address
+/// or by the native denom
 pub fn store_asset_config(
     storage: &mut dyn Storage,
-    asset_token: &CanonicalAddr,
+    asset_info: &AssetInfoRaw,
     asset: &AssetConfig,
 ) -> StdResult<()> {
     let mut asset_bucket: Bucket<AssetConfig> = Bucket::new(storage, PREFIX_ASSET_CONFIG);
-    asset_bucket.save(asset_token.as_slice(), asset)
+    asset_bucket.save(asset_info.as_bytes(), asset)
 }
 
 pub fn read_asset_config(
     storage: &dyn Storage,
-    asset_token: &CanonicalAddr,
-) -> StdResult<AssetConfig> {
+    asset_info: &AssetInfoRaw,
+) -> Result<AssetConfig, ContractError> {
     let asset_bucket: ReadonlyBucket<AssetConfig> =
         ReadonlyBucket::new(storage, PREFIX_ASSET_CONFIG);
-    let res = asset_bucket.load(asset_token.as_slice());
+    let res = asset_bucket.load(asset_info.as_bytes());
     match res {
         Ok(data) => Ok(data),
-        _ => Err(StdError::generic_err("no asset data stored")),
+        _ => Err(ContractError::AssetNotRegistered {}),
+    }
+}
+
+/// Resolves the asset info from a token address or a registered native denom
+pub fn read_asset_info(storage: &dyn Storage, api: &dyn Api, asset: &str) -> StdResult<AssetInfoRaw> {
+    let native_asset_info = AssetInfoRaw::NativeToken {
+        denom: asset.to_string(),
+    };
+    if read_asset_config(storage, &native_asset_info).is_ok() {
+        return Ok(native_asset_info);
     }
+
+    Ok(AssetInfoRaw::Token {
+        contract_addr: api.addr_canonicalize(asset)?,
+    })
 }
 
 pub fn read_fixed_price(storage: &dyn Storage, asset_info: &AssetInfoRaw) -> Option<Decimal> {
-    match asset_info {
-        AssetInfoRaw::Token { contract_addr } => {
-            let asset_bucket: ReadonlyBucket<AssetConfig> =
-                ReadonlyBucket::new(storage, PREFIX_ASSET_CONFIG);
-            let res = asset_bucket.load(contract_addr.as_slice());
-            match res {
-                Ok(data) => {
-                    let _ = data.end_price.is_some();
-                    {
-                        data.end_price
-                    }
-                }
-                _ => None,
-            }
-        }
+    match read_asset_config(storage, asset_info) {
+        Ok(asset_config) => asset_config.end_price,
         _ => None,
     }
 }
 
+pub fn read_pre_ipo_price(storage: &dyn Storage, asset_info: &AssetInfoRaw) -> Option<Decimal> {
+    match read_asset_config(storage, asset_info) {
+        Ok(asset_config) => asset_config
+            .ipo_params
+            .map(|ipo_params| ipo_params.pre_ipo_price),
+        _ => None,
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Position {
@@ -100,6 +147,13 @@ pub struct Position {
     pub owner: CanonicalAddr,
     pub collateral: AssetRaw,
     pub asset: AssetRaw,
+    /// collaterals deposited besides the main `collateral`
+    #[serde(default)]
+    pub other_collaterals: Vec<AssetRaw>,
+    /// stability fee index the debt was last settled at,
+    /// `None` for positions opened before stability fees
+    #[serde(default)]
+    pub stability_fee_index: Option<Decimal>,
 }
 
 /// create position with index
@@ -110,6 +164,7 @@ pub fn create_position(
 ) -> StdResult<()> {
     let mut position_bucket: Bucket<Position> = Bucket::new(storage, PREFIX_POSITION);
     position_bucket.save(&idx.u128().to_be_bytes(), position)?;
+    update_utilizations(storage, None, Some(position))?;
 
     let mut position_indexer_by_user: Bucket<bool> =
         Bucket::multilevel(storage, &[PREFIX_INDEX_BY_USER, position.owner.as_slice()]);
@@ -130,14 +185,41 @@ pub fn store_position(
     idx: Uint128,
     position: &Position,
 ) -> StdResult<()> {
+    let previous_position: Position = read_position(storage, idx)?;
+    update_utilizations(storage, Some(&previous_position), Some(position))?;
+
     let mut position_bucket: Bucket<Position> = Bucket::new(storage, PREFIX_POSITION);
     position_bucket.save(&idx.u128().to_be_bytes(), position)?;
     Ok(())
 }
 
+/// move the position to the new owner and update the user indexer
+pub fn transfer_position(
+    storage: &mut dyn Storage,
+    idx: Uint128,
+    new_owner: &CanonicalAddr,
+) -> StdResult<Position> {
+    let mut position: Position = read_position(storage, idx)?;
+
+    let mut position_indexer_by_user: Bucket<bool> =
+        Bucket::multilevel(storage, &[PREFIX_INDEX_BY_USER, position.owner.as_slice()]);
+    position_indexer_by_user.remove(&idx.u128().to_be_bytes());
+
+    position.owner = new_owner.clone();
+    store_position(storage, idx, &position)?;
+
+    let mut position_indexer_by_user: Bucket<bool> =
+        Bucket::multilevel(storage, &[PREFIX_INDEX_BY_USER, position.owner.as_slice()]);
+    position_indexer_by_user.save(&idx.u128().to_be_bytes(), &true)?;
+
+    Ok(position)
+}
+
 /// remove position with idx
 pub fn remove_position(storage: &mut dyn Storage, idx: Uint128) -> StdResult<()> {
     let position: Position = read_position(storage, idx)?;
+    update_utilizations(storage, Some(&position), None)?;
+
     let mut position_bucket: Bucket<Position> = Bucket::new(storage, PREFIX_POSITION);
     position_bucket.remove(&idx.u128().to_be_bytes());
 
@@ -156,12 +238,334 @@ pub fn remove_position(storage: &mut dyn Storage, idx: Uint128) -> StdResult<()>
     Ok(())
 }
 
+/// Operations paused for an asset, or for every asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseInfo {
+    pub open_position: bool,
+    pub mint: bool,
+    pub withdraw: bool,
+    pub all: bool,
+}
+
+impl PauseInfo {
+    pub fn set(&mut self, operation: &PauseOperation, paused: bool) {
+        match operation {
+            PauseOperation::OpenPosition => self.open_position = paused,
+            PauseOperation::Mint => self.mint = paused,
+            PauseOperation::Withdraw => self.withdraw = paused,
+            PauseOperation::All => self.all = paused,
+        }
+    }
+
+    /// `All` stands for the operations only stopped by a full pause
+    pub fn is_paused(&self, operation: &PauseOperation) -> bool {
+        self.all
+            || match operation {
+                PauseOperation::OpenPosition => self.open_position || self.mint,
+                PauseOperation::Mint => self.mint,
+                PauseOperation::Withdraw => self.withdraw,
+                PauseOperation::All => false,
+            }
+    }
+}
+
+/// Stores the pause info of the asset, or the global one when `None`
+pub fn store_pause_info(
+    storage: &mut dyn Storage,
+    asset_info: Option<&AssetInfoRaw>,
+    pause_info: &PauseInfo,
+) -> StdResult<()> {
+    match asset_info {
+        Some(asset_info) => {
+            let mut pause_info_bucket: Bucket<PauseInfo> = Bucket::new(storage, PREFIX_PAUSE_INFO);
+            pause_info_bucket.save(asset_info.as_bytes(), pause_info)
+        }
+        None => singleton(storage, KEY_PAUSE_INFO).save(pause_info),
+    }
+}
+
+pub fn read_pause_info(
+    storage: &dyn Storage,
+    asset_info: Option<&AssetInfoRaw>,
+) -> StdResult<PauseInfo> {
+    let pause_info = match asset_info {
+        Some(asset_info) => {
+            let pause_info_bucket: ReadonlyBucket<PauseInfo> =
+                ReadonlyBucket::new(storage, PREFIX_PAUSE_INFO);
+            pause_info_bucket.may_load(asset_info.as_bytes())?
+        }
+        None => singleton_read(storage, KEY_PAUSE_INFO).may_load()?,
+    };
+
+    Ok(pause_info.unwrap_or_default())
+}
+
+/// Running total of an asset over all positions with its optional cap,
+/// the minted amount of an mAsset or the deposited amount of a collateral
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Utilization {
+    pub total: Uint128,
+    pub cap: Option<Uint128>,
+}
+
+pub fn store_mint_utilization(
+    storage: &mut dyn Storage,
+    asset_info: &AssetInfoRaw,
+    utilization: &Utilization,
+) -> StdResult<()> {
+    let mut utilization_bucket: Bucket<Utilization> =
+        Bucket::new(storage, PREFIX_MINT_UTILIZATION);
+    utilization_bucket.save(asset_info.as_bytes(), utilization)
+}
+
+pub fn read_mint_utilization(
+    storage: &dyn Storage,
+    asset_info: &AssetInfoRaw,
+) -> StdResult<Utilization> {
+    let utilization_bucket: ReadonlyBucket<Utilization> =
+        ReadonlyBucket::new(storage, PREFIX_MINT_UTILIZATION);
+    Ok(utilization_bucket
+        .may_load(asset_info.as_bytes())?
+        .unwrap_or_default())
+}
+
+pub fn store_deposit_utilization(
+    storage: &mut dyn Storage,
+    collateral_info: &AssetInfoRaw,
+    utilization: &Utilization,
+) -> StdResult<()> {
+    let mut utilization_bucket: Bucket<Utilization> =
+        Bucket::new(storage, PREFIX_DEPOSIT_UTILIZATION);
+    utilization_bucket.save(collateral_info.as_bytes(), utilization)
+}
+
+pub fn read_deposit_utilization(
+    storage: &dyn Storage,
+    collateral_info: &AssetInfoRaw,
+) -> StdResult<Utilization> {
+    let utilization_bucket: ReadonlyBucket<Utilization> =
+        ReadonlyBucket::new(storage, PREFIX_DEPOSIT_UTILIZATION);
+    Ok(utilization_bucket
+        .may_load(collateral_info.as_bytes())?
+        .unwrap_or_default())
+}
+
+/// Last position counted in the totals while they are recounted after
+/// a migration, `None` once every position is counted
+pub fn store_utilization_cursor(
+    storage: &mut dyn Storage,
+    cursor: Option<Uint128>,
+) -> StdResult<()> {
+    match cursor {
+        Some(cursor) => singleton(storage, KEY_UTILIZATION_CURSOR).save(&cursor),
+        None => {
+            singleton::<Uint128>(storage, KEY_UTILIZATION_CURSOR).remove();
+            Ok(())
+        }
+    }
+}
+
+pub fn read_utilization_cursor(storage: &dyn Storage) -> StdResult<Option<Uint128>> {
+    singleton_read(storage, KEY_UTILIZATION_CURSOR).may_load()
+}
+
+/// Adds the next positions after the cursor to the minted and deposited
+/// totals and returns the new cursor, `None` once every position is counted
+pub fn count_position_utilizations(
+    storage: &mut dyn Storage,
+    limit: Option<u32>,
+) -> StdResult<Option<Uint128>> {
+    let cursor = match read_utilization_cursor(storage)? {
+        Some(cursor) => cursor,
+        None => return Ok(None),
+    };
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let positions = read_positions(storage, Some(cursor), Some(limit), Some(OrderBy::Asc))?;
+
+    // the cursor moves first so the position is not skipped by the update
+    let mut cursor = Some(cursor);
+    for position in positions.iter() {
+        cursor = Some(position.idx);
+        store_utilization_cursor(storage, cursor)?;
+        update_utilizations(storage, None, Some(position))?;
+    }
+
+    if positions.len() < limit as usize {
+        cursor = None;
+        store_utilization_cursor(storage, cursor)?;
+    }
+
+    Ok(cursor)
+}
+
+/// Moves the minted and deposited totals from the previous amounts
+/// of a position to its new amounts. Positions after the cursor are
+/// left out of the totals until they are counted
+pub fn update_utilizations(
+    storage: &mut dyn Storage,
+    previous_position: Option<&Position>,
+    position: Option<&Position>,
+) -> StdResult<()> {
+    let cursor = read_utilization_cursor(storage)?;
+    for (position, increase) in [(previous_position, false), (position, true)] {
+        let position = match position {
+            Some(position) => position,
+            None => continue,
+        };
+        if matches!(cursor, Some(cursor) if position.idx > cursor) {
+            continue;
+        }
+
+        update_utilization(storage, PREFIX_MINT_UTILIZATION, &position.asset, increase)?;
+        for collateral in
+            std::iter::once(&position.collateral).chain(position.other_collaterals.iter())
+        {
+            update_utilization(storage, PREFIX_DEPOSIT_UTILIZATION, collateral, increase)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn update_utilization(
+    storage: &mut dyn Storage,
+    prefix: &[u8],
+    asset: &AssetRaw,
+    increase: bool,
+) -> StdResult<()> {
+    if asset.amount.is_zero() {
+        return Ok(());
+    }
+
+    let mut utilization_bucket: Bucket<Utilization> = Bucket::new(storage, prefix);
+    let mut utilization: Utilization = utilization_bucket
+        .may_load(asset.info.as_bytes())?
+        .unwrap_or_default();
+    utilization.total = if increase {
+        utilization.total.checked_add(asset.amount)?
+    } else {
+        utilization.total.saturating_sub(asset.amount)
+    };
+    utilization_bucket.save(asset.info.as_bytes(), &utilization)
+}
+
 /// read position from store with position idx
 pub fn read_position(storage: &dyn Storage, idx: Uint128) -> StdResult<Position> {
     let position_bucket: ReadonlyBucket<Position> = ReadonlyBucket::new(storage, PREFIX_POSITION);
     position_bucket.load(&idx.u128().to_be_bytes())
 }
 
+/// record the cw721 contract the position was minted in
+pub fn store_position_nft(
+    storage: &mut dyn Storage,
+    idx: Uint128,
+    position_nft: &CanonicalAddr,
+) -> StdResult<()> {
+    let mut position_nft_bucket: Bucket<CanonicalAddr> = Bucket::new(storage, PREFIX_POSITION_NFT);
+    position_nft_bucket.save(&idx.u128().to_be_bytes(), position_nft)
+}
+
+/// returns the cw721 contract of positions minted as NFTs
+pub fn read_position_nft(storage: &dyn Storage, idx: Uint128) -> StdResult<Option<CanonicalAddr>> {
+    let position_nft_bucket: ReadonlyBucket<CanonicalAddr> =
+        ReadonlyBucket::new(storage, PREFIX_POSITION_NFT);
+    position_nft_bucket.may_load(&idx.u128().to_be_bytes())
+}
+
+pub fn remove_position_nft(storage: &mut dyn Storage, idx: Uint128) {
+    let mut position_nft_bucket: Bucket<CanonicalAddr> = Bucket::new(storage, PREFIX_POSITION_NFT);
+    position_nft_bucket.remove(&idx.u128().to_be_bytes());
+}
+
+/// flag position as a short position
+pub fn store_short_position(storage: &mut dyn Storage, idx: Uint128) -> StdResult<()> {
+    let mut short_position_bucket: Bucket<bool> = Bucket::new(storage, PREFIX_SHORT_POSITION);
+    short_position_bucket.save(&idx.u128().to_be_bytes(), &true)
+}
+
+/// remove short position flag
+pub fn remove_short_position(storage: &mut dyn Storage, idx: Uint128) {
+    let mut short_position_bucket: Bucket<bool> = Bucket::new(storage, PREFIX_SHORT_POSITION);
+    short_position_bucket.remove(&idx.u128().to_be_bytes())
+}
+
+pub fn is_short_position(storage: &dyn Storage, idx: Uint128) -> StdResult<bool> {
+    let short_position_bucket: ReadonlyBucket<bool> =
+        ReadonlyBucket::new(storage, PREFIX_SHORT_POSITION);
+    Ok(short_position_bucket
+        .may_load(&idx.u128().to_be_bytes())?
+        .unwrap_or(false))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PositionLockInfo {
+    pub idx: Uint128,
+    pub receiver: CanonicalAddr,
+    pub locked_amount: Uint128,
+    pub unlock_time: u64,
+}
+
+pub fn store_position_lock_info(
+    storage: &mut dyn Storage,
+    lock_info: &PositionLockInfo,
+) -> StdResult<()> {
+    let mut lock_bucket: Bucket<PositionLockInfo> = Bucket::new(storage, PREFIX_POSITION_LOCK);
+    lock_bucket.save(&lock_info.idx.u128().to_be_bytes(), lock_info)
+}
+
+pub fn remove_position_lock_info(storage: &mut dyn Storage, idx: Uint128) {
+    let mut lock_bucket: Bucket<PositionLockInfo> = Bucket::new(storage, PREFIX_POSITION_LOCK);
+    lock_bucket.remove(&idx.u128().to_be_bytes())
+}
+
+pub fn read_position_lock_info(
+    storage: &dyn Storage,
+    idx: Uint128,
+) -> Result<PositionLockInfo, ContractError> {
+    let lock_bucket: ReadonlyBucket<PositionLockInfo> =
+        ReadonlyBucket::new(storage, PREFIX_POSITION_LOCK);
+    match lock_bucket.load(&idx.u128().to_be_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(ContractError::NoLockedFunds {}),
+    }
+}
+
+/// approvals are given by an owner over all of its positions
+pub fn store_position_approval(
+    storage: &mut dyn Storage,
+    owner: &CanonicalAddr,
+    spender: &CanonicalAddr,
+    expires: &Expiration,
+) -> StdResult<()> {
+    let mut approval_bucket: Bucket<Expiration> =
+        Bucket::multilevel(storage, &[PREFIX_POSITION_APPROVAL, owner.as_slice()]);
+    approval_bucket.save(spender.as_slice(), expires)
+}
+
+pub fn remove_position_approval(
+    storage: &mut dyn Storage,
+    owner: &CanonicalAddr,
+    spender: &CanonicalAddr,
+) {
+    let mut approval_bucket: Bucket<Expiration> =
+        Bucket::multilevel(storage, &[PREFIX_POSITION_APPROVAL, owner.as_slice()]);
+    approval_bucket.remove(spender.as_slice())
+}
+
+pub fn read_position_approval(
+    storage: &dyn Storage,
+    owner: &CanonicalAddr,
+    spender: &CanonicalAddr,
+) -> Result<Expiration, ContractError> {
+    let approval_bucket: ReadonlyBucket<Expiration> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_POSITION_APPROVAL, owner.as_slice()]);
+    match approval_bucket.load(spender.as_slice()) {
+        Ok(v) => Ok(v),
+        _ => Err(ContractError::NoApproval {}),
+    }
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
@@ -217,13 +621,13 @@ pub fn read_positions_with_user_indexer(
 
 pub fn read_positions_with_asset_indexer(
     storage: &dyn Storage,
-    asset_token: &CanonicalAddr,
+    asset_info: &AssetInfoRaw,
     start_after: Option<Uint128>,
     limit: Option<u32>,
     order_by: Option<OrderBy>,
 ) -> StdResult<Vec<Position>> {
     let position_indexer: ReadonlyBucket<bool> =
-        ReadonlyBucket::multilevel(storage, &[PREFIX_INDEX_BY_ASSET, asset_token.as_slice()]);
+        ReadonlyBucket::multilevel(storage, &[PREFIX_INDEX_BY_ASSET, asset_info.as_bytes()]);
 
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let (start, end, order_by) = match order_by {
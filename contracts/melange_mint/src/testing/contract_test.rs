@@ -1,19 +1,19 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query};
+use crate::error::ContractError;
+use crate::migration::LegacyConfig;
+use crate::state::KEY_CONFIG;
 use crate::testing::mock_querier::mock_dependencies;
-use cosmwasm_std::testing::{mock_env, mock_info};
-use cosmwasm_std::{from_binary, to_binary, CosmosMsg, Deps, DepsMut, Decimal, StdError, SubMsg, WasmMsg};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{attr, from_binary, to_binary, CosmosMsg, Decimal, StdError, SubMsg, WasmMsg};
 use melange_protocol::collateral_oracle::{ExecuteMsg::RegisterCollateralAsset, SourceType};
 use melange_protocol::mint::{
-    AssetConfigResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    AssetConfigResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
 };
+use cosmwasm_std::Api;
+use cosmwasm_storage::singleton;
+use cw2::set_contract_version;
+use sei_cosmwasm::SeiMsg;
 use terraswap::asset::AssetInfo;
-use sei_cosmwasm::{
-    BulkOrderPlacementsResponse, ContractOrderResult, DepositInfo, DexTwapsResponse, EpochResponse,
-    ExchangeRatesResponse, GetLatestPriceResponse, GetOrderByIdResponse, GetOrdersResponse,
-    LiquidationRequest, LiquidationResponse, MsgPlaceOrdersResponse, OracleTwapsResponse, Order,
-    OrderSimulationResponse, OrderType, PositionDirection, SeiMsg, SeiQuerier, SeiQueryWrapper,
-    SettlementEntry, SudoMsg,
-};
 
 static TOKEN_CODE_ID: u64 = 10u64;
 
@@ -31,6 +31,7 @@ fn proper_initialization() {
         base_denom: "uusd".to_string(),
         token_code_id: TOKEN_CODE_ID,
         protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
     let info = mock_info("addr0000", &[]);
     // we can just call .unwrap() to assert this was a success
@@ -60,6 +61,7 @@ fn update_config() {
         base_denom: "uusd".to_string(),
         token_code_id: TOKEN_CODE_ID,
         protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -74,6 +76,9 @@ fn update_config() {
         protocol_fee_rate: None,
         collateral_oracle: None,
         staking: None,
+        lock_period: None,
+        position_nft: Some("positionnft0000".to_string()),
+        guardian: Some("guardian0000".to_string()),
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
@@ -82,6 +87,8 @@ fn update_config() {
     let config: ConfigResponse = from_binary(&res).unwrap();
     assert_eq!("owner0001", config.owner.as_str());
     assert_eq!(100u64, config.token_code_id);
+    assert_eq!(Some("positionnft0000".to_string()), config.position_nft);
+    assert_eq!(Some("guardian0000".to_string()), config.guardian);
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
@@ -93,12 +100,12 @@ fn update_config() {
         protocol_fee_rate: None,
         collateral_oracle: None,
         staking: None,
+        lock_period: None,
+        position_nft: None,
+        guardian: None,
     };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
-    match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
-        _ => panic!("Must return unauthorized error"),
-    }
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
 }
 #[test]
 fn register_asset() {
@@ -114,12 +121,15 @@ fn register_asset() {
         base_denom,
         token_code_id: TOKEN_CODE_ID,
         protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
     let info = mock_info("owner0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -154,45 +164,139 @@ fn register_asset() {
         asset_config,
         AssetConfigResponse {
             token: "asset0000".to_string(),
+            auction_discount: Decimal::percent(20),
             min_collateral_ratio: Decimal::percent(150),
             end_price: None,
+            stability_fee_rate: Decimal::zero(),
+            stability_fee_index: Decimal::one(),
+            ipo_params: None,
         }
     );
     // must be failed with the already registered token error
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
     let info = mock_info("owner0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "Asset was already registered"),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(res, ContractError::AssetAlreadyRegistered {});
     // must be failed with unauthorized error
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
     let info = mock_info("owner0001", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(res, ContractError::Unauthorized {});
     // must be failed with unauthorized error
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(50),
+        ipo_params: None,
     };
     let info = mock_info("owner0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => {
-            assert_eq!(msg, "min_collateral_ratio must be bigger or equal than 1.1")
+    assert_eq!(
+        res,
+        ContractError::InvalidMinCollateralRatio {
+            min_collateral_ratio: "1.1".to_string(),
+        }
+    );
+    // must be failed with invalid auction discount error
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(101),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidAuctionDiscount {});
+}
+#[test]
+fn register_native_asset() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterNativeAsset {
+        subdenom: "uapple".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let denom = format!("factory/{}/uapple", MOCK_CONTRACT_ADDR);
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Custom(SeiMsg::CreateDenom {
+                subdenom: "uapple".to_string(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "collateraloracle0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&RegisterCollateralAsset {
+                    asset: AssetInfo::NativeToken {
+                        denom: denom.clone(),
+                    },
+                    multiplier: Decimal::one(),
+                    price_source: SourceType::MelangeOracle {
+                        oracle_addr: "oracle0000".to_string(),
+                    },
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AssetConfig {
+            asset_token: denom.clone(),
+        },
+    )
+    .unwrap();
+    let asset_config: AssetConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        asset_config,
+        AssetConfigResponse {
+            token: denom,
+            auction_discount: Decimal::percent(20),
+            min_collateral_ratio: Decimal::percent(150),
+            end_price: None,
+            stability_fee_rate: Decimal::zero(),
+            stability_fee_index: Decimal::one(),
+            ipo_params: None,
         }
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    );
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::AssetAlreadyRegistered {});
 }
 #[test]
 fn update_asset() {
@@ -208,18 +312,24 @@ fn update_asset() {
         base_denom,
         token_code_id: TOKEN_CODE_ID,
         protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
     let info = mock_info("owner0000", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     let msg = ExecuteMsg::UpdateAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Some(Decimal::percent(30)),
         min_collateral_ratio: Some(Decimal::percent(200)),
+        stability_fee_rate: None,
+        ipo_params: None,
     };
     let info = mock_info("owner0000", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -236,30 +346,171 @@ fn update_asset() {
         asset_config,
         AssetConfigResponse {
             token: "asset0000".to_string(),
+            auction_discount: Decimal::percent(30),
             min_collateral_ratio: Decimal::percent(200),
             end_price: None,
+            stability_fee_rate: Decimal::zero(),
+            stability_fee_index: Decimal::one(),
+            ipo_params: None,
         }
     );
     let msg = ExecuteMsg::UpdateAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Some(Decimal::percent(30)),
         min_collateral_ratio: Some(Decimal::percent(50)),
+        stability_fee_rate: None,
+        ipo_params: None,
     };
     let info = mock_info("owner0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => {
-            assert_eq!(msg, "min_collateral_ratio must be bigger or equal than 1.1")
+    assert_eq!(
+        res,
+        ContractError::InvalidMinCollateralRatio {
+            min_collateral_ratio: "1.1".to_string(),
         }
-        _ => panic!("Must return unauthorized error"),
-    }
+    );
     let msg = ExecuteMsg::UpdateAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Some(Decimal::percent(30)),
         min_collateral_ratio: Some(Decimal::percent(200)),
+        stability_fee_rate: None,
+        ipo_params: None,
     };
     let info = mock_info("owner0001", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
-        _ => panic!("Must return unauthorized error"),
-    }
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::UpdateAsset {
+        asset_token: "asset0001".to_string(),
+        auction_discount: Some(Decimal::percent(30)),
+        min_collateral_ratio: None,
+        stability_fee_rate: None,
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::AssetNotRegistered {});
+}
+
+#[test]
+fn migrate_contract() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = MigrateMsg {
+        melange_oracle_contract: "oracle0001".to_string(),
+    };
+    let res = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("from_version", env!("CARGO_PKG_VERSION")),
+            attr("to_version", env!("CARGO_PKG_VERSION")),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("oracle0001", config.oracle.as_str());
+
+    // downgrades are refused
+    set_contract_version(&mut deps.storage, "crates.io:melange-mint", "9.0.0").unwrap();
+    let res = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Cannot migrate from a newer version: 9.0.0")
+    );
+}
+
+#[test]
+fn migrate_unversioned_contract() {
+    let mut deps = mock_dependencies(&[]);
+
+    // config stored by the contracts deployed before the versioning
+    let legacy_config = LegacyConfig {
+        owner: deps.api.addr_canonicalize("owner0000").unwrap(),
+        oracle: deps.api.addr_canonicalize("oracle0000").unwrap(),
+        collector: deps.api.addr_canonicalize("collector0000").unwrap(),
+        collateral_oracle: deps.api.addr_canonicalize("collateraloracle0000").unwrap(),
+        staking: deps.api.addr_canonicalize("staking0000").unwrap(),
+        tswap_factory: deps.api.addr_canonicalize("tswap_factory").unwrap(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+    };
+    singleton(&mut deps.storage, KEY_CONFIG)
+        .save(&legacy_config)
+        .unwrap();
+
+    let msg = MigrateMsg {
+        melange_oracle_contract: "oracle0001".to_string(),
+    };
+    let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("from_version", "unversioned"),
+            attr("to_version", env!("CARGO_PKG_VERSION")),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            oracle: "oracle0001".to_string(),
+            collector: "collector0000".to_string(),
+            collateral_oracle: "collateraloracle0000".to_string(),
+            staking: "staking0000".to_string(),
+            tswap_factory: "tswap_factory".to_string(),
+            base_denom: "uusd".to_string(),
+            token_code_id: TOKEN_CODE_ID,
+            protocol_fee_rate: Decimal::percent(1),
+            lock_period: 1_209_600,
+            position_nft: None,
+            guardian: None,
+        }
+    );
+
+    // the totals are recounted from the stored positions, none here
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CountUtilizations { limit: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "count_utilizations"),
+            attr("last_counted", "done"),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CountUtilizations { limit: None },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::UtilizationsCounted {});
 }
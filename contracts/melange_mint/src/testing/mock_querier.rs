@@ -1,10 +1,11 @@
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier, QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery, Empty};
+use cosmwasm_std::{from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier, QuerierResult, QueryRequest, SystemError, SystemResult, WasmQuery, Empty};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use melange_protocol::collateral_oracle::CollateralPriceResponse;
+use melange_protocol::collateral_oracle::{CollateralInfoResponse, CollateralPriceResponse};
+use melange_protocol::cw721::OwnerOfResponse;
 use terraswap::{asset::AssetInfo, asset::PairInfo};
 use sei_cosmwasm::SeiQueryWrapper;
 
@@ -28,34 +29,10 @@ pub fn mock_dependencies(
 
 pub struct WasmMockQuerier {
     base: MockQuerier<>,
-    tax_querier: TaxQuerier,
     oracle_price_querier: OraclePriceQuerier,
     collateral_oracle_querier: CollateralOracleQuerier,
     tswap_pair_querier: TswapPairQuerier,
-}
-
-#[derive(Clone, Default)]
-pub struct TaxQuerier {
-    rate: Decimal,
-    // this lets us iterate over all pairs that match the first string
-    caps: HashMap<String, Uint128>,
-}
-
-impl TaxQuerier {
-    pub fn new(rate: Decimal, caps: &[(&String, &Uint128)]) -> Self {
-        TaxQuerier {
-            rate,
-            caps: caps_to_map(caps),
-        }
-    }
-}
-
-pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint128> {
-    let mut owner_map: HashMap<String, Uint128> = HashMap::new();
-    for (denom, cap) in caps.iter() {
-        owner_map.insert(denom.to_string(), **cap);
-    }
-    owner_map
+    nft_owners: HashMap<String, String>,
 }
 
 #[derive(Clone, Default)]
@@ -151,42 +128,6 @@ impl Querier for WasmMockQuerier {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct TerraQueryWrapper {
-    pub route: TerraRoute,
-    pub query_data: TerraQuery,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum TerraRoute {
-    Market,
-    Treasury,
-    Oracle,
-    Wasm,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum TerraQuery {
-    Swap {
-        offer_coin: Coin,
-        ask_denom: String,
-    },
-    TaxRate {},
-    TaxCap {
-        denom: String,
-    },
-    ExchangeRates {
-        base_denom: String,
-        quote_denoms: Vec<String>,
-    },
-    ContractInfo {
-        contract_address: String,
-    },
-}
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PriceResponse {
     pub rate: Decimal,
@@ -203,9 +144,16 @@ pub enum MockQueryMsg {
     CollateralPrice {
         asset: String,
     },
+    CollateralAssetInfo {
+        asset: String,
+    },
     Pair {
         asset_infos: [AssetInfo; 2],
     },
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
 }
 
 impl WasmMockQuerier {
@@ -214,7 +162,7 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart {
                                    contract_addr: _,
                                    msg,
-                               }) => match from_binary(&msg).unwrap() {
+                               }) => match from_binary(msg).unwrap() {
                 MockQueryMsg::Price {
                     asset_token,
                     timeframe: _,
@@ -247,6 +195,22 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                MockQueryMsg::CollateralAssetInfo { asset } => {
+                    match self.collateral_oracle_querier.collateral_infos.get(&asset) {
+                        Some(collateral_info) => SystemResult::Ok(ContractResult::from(to_binary(
+                            &CollateralInfoResponse {
+                                asset,
+                                multiplier: collateral_info.1,
+                                source_type: "melange_oracle".to_string(),
+                                is_revoked: collateral_info.2,
+                            },
+                        ))),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "Collateral info does not exist".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
                 MockQueryMsg::Pair { asset_infos } => {
                     match self
                         .tswap_pair_querier
@@ -267,6 +231,21 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                MockQueryMsg::OwnerOf {
+                    token_id,
+                    include_expired: _,
+                } => match self.nft_owners.get(&token_id) {
+                    Some(owner) => SystemResult::Ok(ContractResult::from(to_binary(
+                        &OwnerOfResponse {
+                            owner: owner.to_string(),
+                            approvals: vec![],
+                        },
+                    ))),
+                    None => SystemResult::Err(SystemError::InvalidRequest {
+                        error: "Token does not exist".to_string(),
+                        request: msg.as_slice().into(),
+                    }),
+                },
             },
             _ => self.base.handle_query(request),
         }
@@ -277,18 +256,13 @@ impl WasmMockQuerier {
     pub fn new(base: MockQuerier) -> Self {
         WasmMockQuerier {
             base,
-            tax_querier: TaxQuerier::default(),
             oracle_price_querier: OraclePriceQuerier::default(),
             collateral_oracle_querier: CollateralOracleQuerier::default(),
             tswap_pair_querier: TswapPairQuerier::default(),
+            nft_owners: HashMap::new(),
         }
     }
 
-    // configure the token owner mock querier
-    pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
-        self.tax_querier = TaxQuerier::new(rate, caps);
-    }
-
     // configure the oracle price mock querier
     pub fn with_oracle_price(&mut self, oracle_price: &[(&String, &Decimal)]) {
         self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
@@ -306,4 +280,14 @@ impl WasmMockQuerier {
     pub fn with_tswap_pair(&mut self, pairs: &[(&String, &String, &String)]) {
         self.tswap_pair_querier = TswapPairQuerier::new(pairs);
     }
+
+    // configure the owner of a position NFT
+    pub fn with_nft_owner(&mut self, token_id: &str, owner: &str) {
+        self.nft_owners.insert(token_id.to_string(), owner.to_string());
+    }
+
+    // configure the native balance of an address
+    pub fn with_balance(&mut self, addr: &str, balance: &[Coin]) {
+        self.base.update_balance(addr, balance.to_vec());
+    }
 }
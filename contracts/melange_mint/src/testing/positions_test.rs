@@ -1,14 +1,21 @@
-use std::fmt::Debug;
 use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
 use crate::testing::mock_querier::mock_dependencies;
-use cosmwasm_std::testing::{mock_env, mock_info};
-use cosmwasm_std::{attr, from_binary, to_binary, BankMsg, BlockInfo, Coin, CosmosMsg, Decimal, Env, StdError, SubMsg, Timestamp, Uint128, WasmMsg, Deps};
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{attr, from_binary, to_binary, BankMsg, BlockInfo, Coin, CosmosMsg, Decimal, Env, StdError, StdResult, SubMsg, Timestamp, Uint128, WasmMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
 use melange_protocol::common::OrderBy;
+use melange_protocol::cw721::ExecuteMsg as Cw721ExecuteMsg;
 use melange_protocol::mint::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PositionResponse, PositionsResponse, QueryMsg,
+    AssetConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, IpoParams,
+    NextPositionIdxResponse, PauseInfoResponse, PauseOperation, PositionApprovalResponse,
+    PositionHealthResponse, PositionLockInfoResponse, PositionResponse, PositionsHealthResponse,
+    PositionsResponse, QueryMsg, ShortParams, SimulationResponse, UtilizationResponse,
 };
+use melange_protocol::staking::ExecuteMsg as StakingExecuteMsg;
 use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::Cw20HookMsg as PairCw20HookMsg;
+use sei_cosmwasm::SeiMsg;
 
 static TOKEN_CODE_ID: u64 = 10u64;
 fn mock_env_with_block_time(time: u64) -> Env {
@@ -51,6 +58,7 @@ fn open_position() {
         base_denom,
         token_code_id: TOKEN_CODE_ID,
         protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -58,7 +66,9 @@ fn open_position() {
 
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -66,7 +76,9 @@ fn open_position() {
 
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0001".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -79,6 +91,7 @@ fn open_position() {
                 contract_addr: "asset9999".to_string(),
             },
             collateral_ratio: Decimal::percent(150),
+            short_params: None,
         })
             .unwrap(),
         sender: "addr0000".to_string(),
@@ -100,6 +113,7 @@ fn open_position() {
             contract_addr: "asset0000".to_string(),
         },
         collateral_ratio: Decimal::percent(140),
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -110,13 +124,7 @@ fn open_position() {
         }],
     );
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(
-            msg,
-            "Can not open a position with low collateral ratio than minimum"
-        ),
-        _ => panic!("DO NOT ENTER ERROR"),
-    }
+    assert_eq!(res, ContractError::OpenPositionBelowMinCollateralRatio {});
 
     // successful attempt
     let msg = ExecuteMsg::OpenPosition {
@@ -130,6 +138,7 @@ fn open_position() {
             contract_addr: "asset0000".to_string(),
         },
         collateral_ratio: Decimal::percent(150),
+        short_params: None,
     };
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
@@ -182,6 +191,9 @@ fn open_position() {
                 },
                 amount: Uint128::from(1000000u128),
             },
+            is_short: false,
+            other_collaterals: vec![],
+            accrued_stability_fee: Uint128::zero(),
         }
     );
 
@@ -217,6 +229,9 @@ fn open_position() {
                     },
                     amount: Uint128::from(1000000u128),
                 },
+                is_short: false,
+                other_collaterals: vec![],
+                accrued_stability_fee: Uint128::zero(),
             }],
         }
     );
@@ -233,12 +248,10 @@ fn open_position() {
             contract_addr: "asset0000".to_string(),
         },
         collateral_ratio: Decimal::percent(150),
+        short_params: None,
     };
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(res, ContractError::Unauthorized {});
 
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         msg: to_binary(&Cw20HookMsg::OpenPosition {
@@ -246,6 +259,7 @@ fn open_position() {
                 contract_addr: "asset0000".to_string(),
             },
             collateral_ratio: Decimal::percent(300), // 15 * 2 (multiplier)
+            short_params: None,
         })
             .unwrap(),
         sender: "addr0000".to_string(),
@@ -254,21 +268,15 @@ fn open_position() {
     let env = mock_env_with_block_time(1000);
     let info = mock_info("asset0001", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    // println!("***");
-    // println!("*** old value: 166666asset0000");
-    // println!("***");
     assert_eq!(
         res.attributes,
         vec![
             attr("action", "open_position"),
             attr("position_idx", "2"),
-            attr("mint_amount", "333333asset0000"), // 1000000 * 0.5 (price to asset) * 0.5 multiplier / 1.5 (mcr)
+            attr("mint_amount", "166666asset0000"), // 1000000 * 0.5 (price to asset) * 0.5 multiplier / 1.5 (mcr)
             attr("collateral_amount", "1000000asset0001"),
         ]
     );
-    // println!("***");
-    // println!("*** old value: 166666u128");
-    // println!("***");
     assert_eq!(
         res.messages,
         vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -276,7 +284,7 @@ fn open_position() {
             funds: vec![],
             msg: to_binary(&Cw20ExecuteMsg::Mint {
                 recipient: "addr0000".to_string(),
-                amount: Uint128::from(333333u128),
+                amount: Uint128::from(166666u128),
             })
                 .unwrap(),
         }))]
@@ -291,9 +299,6 @@ fn open_position() {
     )
         .unwrap();
     let position: PositionResponse = from_binary(&res).unwrap();
-    // println!("***");
-    // println!("*** old value: 166666u128");
-    // println!("***");
     assert_eq!(
         position,
         PositionResponse {
@@ -303,7 +308,7 @@ fn open_position() {
                 info: AssetInfo::Token {
                     contract_addr: "asset0000".to_string(),
                 },
-                amount: Uint128::from(333333u128),
+                amount: Uint128::from(166666u128),
             },
             collateral: Asset {
                 info: AssetInfo::Token {
@@ -311,6 +316,9 @@ fn open_position() {
                 },
                 amount: Uint128::from(1000000u128),
             },
+            is_short: false,
+            other_collaterals: vec![],
+            accrued_stability_fee: Uint128::zero(),
         }
     );
 
@@ -328,9 +336,6 @@ fn open_position() {
     )
         .unwrap();
     let positions: PositionsResponse = from_binary(&res).unwrap();
-    // println!("***");
-    // println!("*** old value: 166666u128");
-    // println!("***");
     assert_eq!(
         positions,
         PositionsResponse {
@@ -342,7 +347,7 @@ fn open_position() {
                         info: AssetInfo::Token {
                             contract_addr: "asset0000".to_string(),
                         },
-                        amount: Uint128::from(333333u128),
+                        amount: Uint128::from(166666u128),
                     },
                     collateral: Asset {
                         info: AssetInfo::Token {
@@ -350,6 +355,9 @@ fn open_position() {
                         },
                         amount: Uint128::from(1000000u128),
                     },
+                    is_short: false,
+                    other_collaterals: vec![],
+                    accrued_stability_fee: Uint128::zero(),
                 },
                 PositionResponse {
                     idx: Uint128::from(1u128),
@@ -366,6 +374,9 @@ fn open_position() {
                         },
                         amount: Uint128::from(1000000u128),
                     },
+                    is_short: false,
+                    other_collaterals: vec![],
+                    accrued_stability_fee: Uint128::zero(),
                 }
             ],
         }
@@ -402,6 +413,9 @@ fn open_position() {
                     },
                     amount: Uint128::from(1000000u128),
                 },
+                is_short: false,
+                other_collaterals: vec![],
+                accrued_stability_fee: Uint128::zero(),
             }],
         }
     );
@@ -434,6 +448,7 @@ fn deposit() {
         base_denom,
         token_code_id: TOKEN_CODE_ID,
         protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -441,7 +456,9 @@ fn deposit() {
 
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -449,7 +466,9 @@ fn deposit() {
 
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0001".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -467,6 +486,7 @@ fn deposit() {
             contract_addr: "asset0000".to_string(),
         },
         collateral_ratio: Decimal::percent(150),
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -485,6 +505,7 @@ fn deposit() {
                 contract_addr: "asset0000".to_string(),
             },
             collateral_ratio: Decimal::percent(150),
+            short_params: None,
         })
             .unwrap(),
         sender: "addr0000".to_string(),
@@ -538,6 +559,9 @@ fn deposit() {
                 },
                 amount: Uint128::from(2000000u128),
             },
+            is_short: false,
+            other_collaterals: vec![],
+            accrued_stability_fee: Uint128::zero(),
         }
     );
 
@@ -553,10 +577,7 @@ fn deposit() {
     };
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
-        _ => panic!("Must return unauthorized error"),
-    }
+    assert_eq!(res, ContractError::Unauthorized {});
 
     // deposit other token asset
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
@@ -580,9 +601,6 @@ fn deposit() {
         .unwrap();
 
     let position: PositionResponse = from_binary(&res).unwrap();
-    // println!("***");
-    // println!("*** old value: 333333u128");
-    // println!("***");
     assert_eq!(
         position,
         PositionResponse {
@@ -592,7 +610,7 @@ fn deposit() {
                 info: AssetInfo::Token {
                     contract_addr: "asset0000".to_string(),
                 },
-                amount: Uint128::from(666666u128),
+                amount: Uint128::from(333333u128),
             },
             collateral: Asset {
                 info: AssetInfo::Token {
@@ -600,6 +618,9 @@ fn deposit() {
                 },
                 amount: Uint128::from(2000000u128),
             },
+            is_short: false,
+            other_collaterals: vec![],
+            accrued_stability_fee: Uint128::zero(),
         }
     );
 }
@@ -637,6 +658,7 @@ fn mint() {
         base_denom,
         token_code_id: TOKEN_CODE_ID,
         protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -644,7 +666,9 @@ fn mint() {
 
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -652,7 +676,9 @@ fn mint() {
 
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0001".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -670,6 +696,7 @@ fn mint() {
             contract_addr: "asset0000".to_string(),
         },
         collateral_ratio: Decimal::percent(150),
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -688,6 +715,7 @@ fn mint() {
                 contract_addr: "asset0000".to_string(),
             },
             collateral_ratio: Decimal::percent(150),
+            short_params: None,
         })
             .unwrap(),
         sender: "addr0000".to_string(),
@@ -740,6 +768,7 @@ fn mint() {
             },
             amount: Uint128::from(6668u128),
         },
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000u64);
     let info = mock_info("addr0000", &[]);
@@ -752,12 +781,7 @@ fn mint() {
     // println!("********** env.block.height: {}", env.block.height);
     // println!("********** env.contract.address: {}", env.contract.address);
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => {
-            assert_eq!(msg, "Cannot mint asset over than min collateral ratio")
-        }
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(res, ContractError::MintBelowMinCollateralRatio {});
 
     // successfully mint within the min_collateral_ratio
     let msg = ExecuteMsg::Mint {
@@ -768,6 +792,7 @@ fn mint() {
             },
             amount: Uint128::from(6667u128),
         },
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000u64);
     let info = mock_info("addr0000", &[]);
@@ -802,16 +827,12 @@ fn mint() {
             },
             amount: Uint128::from(333334u128),
         },
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000u64);
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => {
-            assert_eq!(msg, "Cannot mint asset over than min collateral ratio")
-        }
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(res, ContractError::MintBelowMinCollateralRatio {});
 
     // mint with other token;
     let msg = ExecuteMsg::Mint {
@@ -822,6 +843,7 @@ fn mint() {
             },
             amount: Uint128::from(333333u128),
         },
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000u64);
     let info = mock_info("addr0000", &[]);
@@ -881,6 +903,7 @@ fn burn() {
         base_denom,
         token_code_id: TOKEN_CODE_ID,
         protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -888,7 +911,9 @@ fn burn() {
 
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -896,7 +921,9 @@ fn burn() {
 
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0001".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
 
     let info = mock_info("owner0000", &[]);
@@ -914,6 +941,7 @@ fn burn() {
             contract_addr: "asset0000".to_string(),
         },
         collateral_ratio: Decimal::percent(150),
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -932,6 +960,7 @@ fn burn() {
                 contract_addr: "asset0000".to_string(),
             },
             collateral_ratio: Decimal::percent(150),
+            short_params: None,
         })
             .unwrap(),
         sender: "addr0000".to_string(),
@@ -980,6 +1009,7 @@ fn burn() {
             },
             amount: Uint128::from(6667u128),
         },
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000u64);
     let info = mock_info("addr0000", &[]);
@@ -996,12 +1026,7 @@ fn burn() {
     });
     let info = mock_info("asset0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => {
-            assert_eq!(msg, "Cannot burn asset more than you mint")
-        }
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(res, ContractError::BurnExceedsAsset {});
 
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
@@ -1053,6 +1078,7 @@ fn burn() {
             },
             amount: Uint128::from(333333u128),
         },
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000u64);
     let info = mock_info("addr0000", &[]);
@@ -1069,12 +1095,7 @@ fn burn() {
     });
     let info = mock_info("asset0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => {
-            assert_eq!(msg, "Cannot burn asset more than you mint")
-        }
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(res, ContractError::BurnExceedsAsset {});
 
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
@@ -1118,30 +1139,54 @@ fn burn() {
             }))
         ]
     );
-}
 
-#[test]
-fn withdraw() {
-    let mut deps = mock_dependencies(&[]);
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(2u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000u64);
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // the protocol fee of a single unit is worth more than the collateral
     deps.querier.with_oracle_price(&[
         (&"uusd".to_string(), &Decimal::one()),
         (
             &"asset0000".to_string(),
-            &Decimal::from_ratio(100u128, 1u128),
+            &Decimal::from_ratio(10_000_000_000u128, 1u128),
         ),
         (
             &"asset0001".to_string(),
             &Decimal::from_ratio(50u128, 1u128),
         ),
     ]);
-    deps.querier.with_collateral_infos(&[(
-        &"asset0001".to_string(),
-        &Decimal::from_ratio(50u128, 1u128),
-        &Decimal::one(),
-        &false,
-    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1u128),
+        msg: to_binary(&Cw20HookMsg::Burn {
+            position_idx: Uint128::from(2u128),
+        })
+            .unwrap(),
+    });
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::ProtocolFeeExceedsCollateral {});
+}
 
-    let base_denom = "uusd".to_string();
+#[test]
+fn close_position() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
 
     let msg = InstantiateMsg {
         owner: "owner0000".to_string(),
@@ -1150,9 +1195,10 @@ fn withdraw() {
         collateral_oracle: "collateraloracle0000".to_string(),
         staking: "staking0000".to_string(),
         tswap_factory: "tswap_factory".to_string(),
-        base_denom,
+        base_denom: "uusd".to_string(),
         token_code_id: TOKEN_CODE_ID,
         protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -1160,21 +1206,13 @@ fn withdraw() {
 
     let msg = ExecuteMsg::RegisterAsset {
         asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
         min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
     };
-
-    let info = mock_info("owner0000", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    let msg = ExecuteMsg::RegisterAsset {
-        asset_token: "asset0001".to_string(),
-        min_collateral_ratio: Decimal::percent(150),
-    };
-
     let info = mock_info("owner0000", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // open uusd-asset0000 position
     let msg = ExecuteMsg::OpenPosition {
         collateral: Asset {
             info: AssetInfo::NativeToken {
@@ -1186,6 +1224,7 @@ fn withdraw() {
             contract_addr: "asset0000".to_string(),
         },
         collateral_ratio: Decimal::percent(150),
+        short_params: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -1197,107 +1236,3292 @@ fn withdraw() {
     );
     let _res = execute(deps.as_mut(), env, info, msg).unwrap();
 
-    // open asset0001-asset0000 position
+    // must burn the whole debt
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        msg: to_binary(&Cw20HookMsg::OpenPosition {
-            asset_info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-            collateral_ratio: Decimal::percent(150),
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(666665u128),
+        msg: to_binary(&Cw20HookMsg::ClosePosition {
+            position_idx: Uint128::from(1u128),
         })
             .unwrap(),
-        sender: "addr0000".to_string(),
-        amount: Uint128::from(1000000u128),
     });
     let env = mock_env_with_block_time(1000);
-    let info = mock_info("asset0001", &[]);
-    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::CloseAmountMismatch {});
 
-    // cannot withdraw more than (100 collateral token == 1 token)
-    // due to min collateral ratio
-    let msg = ExecuteMsg::Withdraw {
-        position_idx: Uint128::from(1u128),
-        collateral: Some(Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
+    // only the owner can close the position
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(666666u128),
+        msg: to_binary(&Cw20HookMsg::ClosePosition {
+            position_idx: Uint128::from(1u128),
+        })
+            .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(666666u128),
+        msg: to_binary(&Cw20HookMsg::ClosePosition {
+            position_idx: Uint128::from(1u128),
+        })
+            .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "close_position"),
+            attr("position_idx", "1"),
+            attr("burn_amount", "666666asset0000"),
+            attr("protocol_fee", "6666uusd"), // 666666 * 1 (price) * 0.01 (protocol_fee)
+            attr("refund_collateral_amount", "993334uusd"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(666666u128),
+                })
+                    .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(6666u128)
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(993334u128)
+                }],
+            })),
+        ]
+    );
+
+    // the position and its indexers are removed
+    let _res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Position {
+            position_idx: Uint128::from(1u128),
+        },
+    )
+        .unwrap_err();
+
+    let res: PositionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Positions {
+                owner_addr: Some("addr0000".to_string()),
+                asset_token: None,
+                start_after: None,
+                limit: None,
+                order_by: None,
             },
-            amount: Uint128::from(100u128), // fixme
-        }),
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.positions, vec![]);
+
+    let res: PositionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Positions {
+                owner_addr: None,
+                asset_token: Some("asset0000".to_string()),
+                start_after: None,
+                limit: None,
+                order_by: None,
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.positions, vec![]);
+}
+
+#[test]
+fn transfer_and_approve_position() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
     };
-    let env = mock_env_with_block_time(1000u64);
+
     let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(
-            msg,
-            "Cannot withdraw collateral over than minimum collateral ratio"
-        ),
-        _ => panic!("DO NOT ENTER HERE"),
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let uusd = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(amount),
+    };
+    let uusd_coins = |amount: u128| {
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(amount),
+        }]
+    };
+
+    // open two positions
+    for _ in 0..2 {
+        let msg = ExecuteMsg::OpenPosition {
+            collateral: uusd(1000000u128),
+            asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            collateral_ratio: Decimal::percent(200),
+            short_params: None,
+        };
+        let env = mock_env_with_block_time(1000);
+        let info = mock_info("addr0000", &uusd_coins(1000000u128));
+        let _res = execute(deps.as_mut(), env, info, msg).unwrap();
     }
 
-    let msg = ExecuteMsg::Withdraw {
+    // not approved yet
+    let msg = ExecuteMsg::TransferPosition {
         position_idx: Uint128::from(1u128),
-        collateral: Some(Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(100u128),
-        }),
+        recipient: "addr0002".to_string(),
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::ApprovePosition {
+        spender: "addr0000".to_string(),
+        expires: None,
     };
-    let env = mock_env_with_block_time(1000u64);
     let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::CannotSetOwnAccount {});
 
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    // println!("***** {:?}", res.attributes);
+    let msg = ExecuteMsg::ApprovePosition {
+        spender: "addr0001".to_string(),
+        expires: Some(Expiration::AtTime(Timestamp::from_seconds(1000))),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidExpiration {});
+
+    let msg = ExecuteMsg::ApprovePosition {
+        spender: "addr0001".to_string(),
+        expires: Some(Expiration::AtTime(Timestamp::from_seconds(2000))),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "withdraw"),
-            attr("position_idx", "1"),
-            attr("withdraw_amount", "100uusd"),
+            attr("action", "approve_position"),
+            attr("owner", "addr0000"),
+            attr("spender", "addr0001"),
+            attr("expires", "expiration time: 2000.000000000"),
         ]
     );
 
-    // cannot withdraw more than (2 collateral token == 1 token)
-    // due to min collateral ratio
-    let msg = ExecuteMsg::Withdraw {
-        position_idx: Uint128::from(2u128),
-        collateral: Some(Asset {
-            info: AssetInfo::Token {
-                contract_addr: "asset0001".to_string(),
+    let res: PositionApprovalResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PositionApproval {
+                owner: "addr0000".to_string(),
+                spender: "addr0001".to_string(),
             },
-            amount: Uint128::from(2u128),
-        }),
-    };
-    let env = mock_env_with_block_time(1000u64);
-    let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(
-            msg,
-            "Cannot withdraw collateral over than minimum collateral ratio"
-        ),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(
+        res,
+        PositionApprovalResponse {
+            owner: "addr0000".to_string(),
+            spender: "addr0001".to_string(),
+            expires: Expiration::AtTime(Timestamp::from_seconds(2000)),
+        }
+    );
 
-    let msg = ExecuteMsg::Withdraw {
-        position_idx: Uint128::from(2u128),
-        collateral: Some(Asset {
+    // the spender operates the position, minted assets go to the owner
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(1u128),
+        asset: Asset {
             info: AssetInfo::Token {
-                contract_addr: "asset0001".to_string(),
+                contract_addr: "asset0000".to_string(),
             },
-            amount: Uint128::from(1u128),
-        }),
+            amount: Uint128::from(1000u128),
+        },
+        short_params: None,
     };
-    let env = mock_env_with_block_time(1000u64);
-    let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "withdraw"),
-            attr("position_idx", "2"),
-            attr("withdraw_amount", "1asset0001"),
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1000u128),
+            })
+                .unwrap(),
+        }))]
+    );
+
+    let msg = ExecuteMsg::TransferPosition {
+        position_idx: Uint128::from(1u128),
+        recipient: "addr0002".to_string(),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "transfer_position"),
+            attr("position_idx", "1"),
+            attr("from", "addr0000"),
+            attr("to", "addr0002"),
+        ]
+    );
+
+    let res: PositionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Position {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.owner, "addr0002".to_string());
+
+    // the user indexer follows the owner
+    let positions_of = |owner: &str| -> Vec<Uint128> {
+        let res: PositionsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Positions {
+                    owner_addr: Some(owner.to_string()),
+                    asset_token: None,
+                    start_after: None,
+                    limit: None,
+                    order_by: Some(OrderBy::Asc),
+                },
+            )
+                .unwrap(),
+        )
+            .unwrap();
+        res.positions.iter().map(|position| position.idx).collect()
+    };
+    assert_eq!(positions_of("addr0000"), vec![Uint128::from(2u128)]);
+    assert_eq!(positions_of("addr0002"), vec![Uint128::from(1u128)]);
+
+    // approvals of the previous owner do not apply anymore
+    let msg = ExecuteMsg::Deposit {
+        position_idx: Uint128::from(1u128),
+        collateral: uusd(1000u128),
+    };
+    let info = mock_info("addr0001", &uusd_coins(1000u128));
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    // the approval expired
+    let msg = ExecuteMsg::Deposit {
+        position_idx: Uint128::from(2u128),
+        collateral: uusd(1000u128),
+    };
+    let env = mock_env_with_block_time(2000);
+    let info = mock_info("addr0001", &uusd_coins(1000u128));
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::RevokePosition {
+        spender: "addr0001".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PositionApproval {
+            owner: "addr0000".to_string(),
+            spender: "addr0001".to_string(),
+        },
+    )
+        .unwrap_err();
+    assert_eq!(res, ContractError::NoApproval {}.into());
+}
+
+#[test]
+fn position_nft() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle: None,
+        collector: None,
+        collateral_oracle: None,
+        tswap_factory: None,
+        token_code_id: None,
+        protocol_fee_rate: None,
+        staking: None,
+        lock_period: None,
+        position_nft: Some("positionnft0000".to_string()),
+        guardian: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(500000u128),
+                })
+                    .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "positionnft0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw721ExecuteMsg::Mint {
+                    token_id: "1".to_string(),
+                    owner: "addr0000".to_string(),
+                    token_uri: None,
+                    extension: None,
+                })
+                    .unwrap(),
+            })),
+        ]
+    );
+
+    // the token was sold
+    deps.querier.with_nft_owner("1", "addr0001");
+
+    let res: PositionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Position {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.owner, "addr0001".to_string());
+
+    // the previous owner cannot operate the position anymore
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(1u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(1000u128),
+        },
+        short_params: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(1000u128),
+            })
+                .unwrap(),
+        }))]
+    );
+
+    // the user indexer follows the token owner
+    let res: PositionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Positions {
+                owner_addr: Some("addr0001".to_string()),
+                asset_token: None,
+                start_after: None,
+                limit: None,
+                order_by: None,
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.positions.len(), 1);
+
+    let msg = ExecuteMsg::TransferPosition {
+        position_idx: Uint128::from(1u128),
+        recipient: "addr0002".to_string(),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::NftPosition {});
+
+    // closing the position burns its token
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(501000u128),
+        msg: to_binary(&Cw20HookMsg::ClosePosition {
+            position_idx: Uint128::from(1u128),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env, mock_info("asset0000", &[]), msg).unwrap();
+    assert!(res.messages.contains(&SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "positionnft0000".to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw721ExecuteMsg::Burn {
+            token_id: "1".to_string(),
+        })
+        .unwrap(),
+    }))));
+}
+
+#[test]
+fn position_health() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+    deps.querier.with_collateral_infos(&[(
+        &"asset0001".to_string(),
+        &Decimal::percent(50),
+        &Decimal::percent(200), // 2 collateral_multiplier
+        &false,
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // base denom collateral, mints 500000 asset
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // collateral with multiplier, mints 125000 asset
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::OpenPosition {
+            asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            collateral_ratio: Decimal::percent(400),
+            short_params: None,
+        })
+            .unwrap(),
+    });
+    let info = mock_info("asset0001", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res: PositionHealthResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PositionHealth {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(
+        res,
+        PositionHealthResponse {
+            idx: Uint128::from(1u128),
+            collateral_ratio: Some(Decimal::percent(200)),
+            min_collateral_ratio: Decimal::percent(150),
+            liquidation_price: Some(Decimal::from_ratio(4u128, 3u128)),
+            mintable_amount: Uint128::from(166667u128),
+            withdrawable_collateral: Uint128::from(250000u128),
+        }
+    );
+
+    let res: PositionsHealthResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::PositionsHealth {
+                positions_idx: vec![Uint128::from(2u128)],
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(
+        res.positions,
+        vec![PositionHealthResponse {
+            idx: Uint128::from(2u128),
+            collateral_ratio: Some(Decimal::percent(400)),
+            min_collateral_ratio: Decimal::percent(300),
+            liquidation_price: Some(Decimal::from_ratio(4u128, 3u128)),
+            mintable_amount: Uint128::from(41666u128),
+            withdrawable_collateral: Uint128::from(250000u128),
+        }]
+    );
+
+    // the mintable amount is the largest amount accepted by mint
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(2u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(41667u128),
+        },
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::MintBelowMinCollateralRatio {});
+
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(2u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(41666u128),
+        },
+        short_params: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // the mint cap bounds the mintable amount, 666666 are minted
+    let msg = ExecuteMsg::UpdateMintCap {
+        asset_token: "asset0000".to_string(),
+        mint_cap: Some(Uint128::from(700000u128)),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let res: PositionHealthResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PositionHealth {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.mintable_amount, Uint128::from(33334u128));
+
+    // nothing can be minted while minting is paused
+    let msg = ExecuteMsg::Pause {
+        asset_token: Some("asset0000".to_string()),
+        operations: vec![PauseOperation::Mint],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let res: PositionHealthResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::PositionHealth {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.mintable_amount, Uint128::zero());
+
+    // prices are checked like in the execute paths
+    let res = query(
+        deps.as_ref(),
+        mock_env_with_block_time(2000),
+        QueryMsg::PositionHealth {
+            position_idx: Uint128::from(1u128),
+        },
+    )
+        .unwrap_err();
+    assert_eq!(res, StdError::generic_err("Price is too old"));
+}
+
+#[test]
+fn simulate_operations() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let collateral = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(1000000u128),
+    };
+    let asset = |amount: u128| Asset {
+        info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        amount: Uint128::from(amount),
+    };
+    let env = mock_env_with_block_time(1000);
+    let simulate = |msg: QueryMsg| -> StdResult<SimulationResponse> {
+        from_binary(&query(deps.as_ref(), env.clone(), msg)?)
+    };
+
+    let res = simulate(QueryMsg::SimulateOpenPosition {
+        sender: "addr0000".to_string(),
+        collateral: collateral.clone(),
+        asset_info: asset(0).info,
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    })
+        .unwrap();
+    assert_eq!(
+        res,
+        SimulationResponse {
+            mint_amount: Uint128::from(500000u128),
+            protocol_fee: vec![],
+            refund_collateral: vec![],
+            collateral_ratio: Some(Decimal::percent(200)),
+        }
+    );
+
+    let res = simulate(QueryMsg::SimulateOpenPosition {
+        sender: "addr0000".to_string(),
+        collateral: collateral.clone(),
+        asset_info: asset(0).info,
+        collateral_ratio: Decimal::percent(140),
+        short_params: None,
+    })
+        .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Can not open a position with low collateral ratio than minimum")
+    );
+
+    // nothing was written
+    let res: NextPositionIdxResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::NextPositionIdx {}).unwrap())
+            .unwrap();
+    assert_eq!(res.next_position_idx, Uint128::from(1u128));
+
+    let msg = ExecuteMsg::OpenPosition {
+        collateral,
+        asset_info: asset(0).info,
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let simulate = |msg: QueryMsg| -> StdResult<SimulationResponse> {
+        from_binary(&query(deps.as_ref(), env.clone(), msg)?)
+    };
+
+    let res = simulate(QueryMsg::SimulateMint {
+        sender: "addr0000".to_string(),
+        position_idx: Uint128::from(1u128),
+        asset: asset(100000u128),
+        short_params: None,
+    })
+        .unwrap();
+    assert_eq!(
+        res,
+        SimulationResponse {
+            mint_amount: Uint128::from(100000u128),
+            protocol_fee: vec![],
+            refund_collateral: vec![],
+            collateral_ratio: Some(Decimal::from_ratio(1000000u128, 600000u128)),
+        }
+    );
+
+    let res = simulate(QueryMsg::SimulateMint {
+        sender: "addr0001".to_string(),
+        position_idx: Uint128::from(1u128),
+        asset: asset(100000u128),
+        short_params: None,
+    })
+        .unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let res = simulate(QueryMsg::SimulateBurn {
+        sender: "addr0000".to_string(),
+        position_idx: Uint128::from(1u128),
+        asset: asset(100000u128),
+    })
+        .unwrap();
+    assert_eq!(
+        res,
+        SimulationResponse {
+            mint_amount: Uint128::zero(),
+            // 100000 * 1 (price) * 0.01 (protocol_fee)
+            protocol_fee: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(1000u128),
+            }],
+            refund_collateral: vec![],
+            collateral_ratio: Some(Decimal::from_ratio(999000u128, 400000u128)),
+        }
+    );
+
+    let res = simulate(QueryMsg::SimulateWithdraw {
+        sender: "addr0000".to_string(),
+        position_idx: Uint128::from(1u128),
+        collateral: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(250000u128),
+        }),
+    })
+        .unwrap();
+    assert_eq!(
+        res,
+        SimulationResponse {
+            mint_amount: Uint128::zero(),
+            protocol_fee: vec![],
+            refund_collateral: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(250000u128),
+            }],
+            collateral_ratio: Some(Decimal::percent(150)),
+        }
+    );
+
+    let res = simulate(QueryMsg::SimulateWithdraw {
+        sender: "addr0000".to_string(),
+        position_idx: Uint128::from(1u128),
+        collateral: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(250001u128),
+        }),
+    })
+        .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Cannot withdraw collateral over than minimum collateral ratio")
+    );
+
+    // the position is unchanged
+    let res: PositionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Position {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.collateral.amount, Uint128::from(1000000u128));
+    assert_eq!(res.asset.amount, Uint128::from(500000u128));
+}
+
+#[test]
+fn withdraw() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (
+            &"asset0000".to_string(),
+            &Decimal::from_ratio(100u128, 1u128),
+        ),
+        (
+            &"asset0001".to_string(),
+            &Decimal::from_ratio(50u128, 1u128),
+        ),
+    ]);
+    deps.querier.with_collateral_infos(&[(
+        &"asset0001".to_string(),
+        &Decimal::from_ratio(50u128, 1u128),
+        &Decimal::one(),
+        &false,
+    )]);
+
+    let base_denom = "uusd".to_string();
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom,
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0001".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // open uusd-asset0000 position
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(150),
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // open asset0001-asset0000 position
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        msg: to_binary(&Cw20HookMsg::OpenPosition {
+            asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            collateral_ratio: Decimal::percent(150),
+            short_params: None,
+        })
+            .unwrap(),
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+    });
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("asset0001", &[]);
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // cannot withdraw more than (100 collateral token == 1 token)
+    // due to min collateral ratio
+    let msg = ExecuteMsg::Withdraw {
+        position_idx: Uint128::from(1u128),
+        collateral: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(101u128),
+        }),
+    };
+    let env = mock_env_with_block_time(1000u64);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::WithdrawBelowMinCollateralRatio {});
+
+    let msg = ExecuteMsg::Withdraw {
+        position_idx: Uint128::from(1u128),
+        collateral: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100u128),
+        }),
+    };
+    let env = mock_env_with_block_time(1000u64);
+    let info = mock_info("addr0000", &[]);
+
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw"),
+            attr("position_idx", "1"),
+            attr("withdraw_amount", "100uusd"),
+            attr("tax_amount", "0uusd"),
+        ]
+    );
+
+    // cannot withdraw more than (2 collateral token == 1 token)
+    // due to min collateral ratio
+    let msg = ExecuteMsg::Withdraw {
+        position_idx: Uint128::from(2u128),
+        collateral: Some(Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        }),
+    };
+    let env = mock_env_with_block_time(1000u64);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::WithdrawBelowMinCollateralRatio {});
+
+    let msg = ExecuteMsg::Withdraw {
+        position_idx: Uint128::from(2u128),
+        collateral: Some(Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        }),
+    };
+    let env = mock_env_with_block_time(1000u64);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw"),
+            attr("position_idx", "2"),
+            attr("withdraw_amount", "1asset0001"),
+            attr("tax_amount", "0asset0001"),
+        ]
+    );
+}
+
+#[test]
+fn open_position_with_stale_price() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(150),
+        short_params: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+
+    // oracle price was updated at 1000, so it expires after 1060
+    let env = mock_env_with_block_time(1061);
+    let res = execute(deps.as_mut(), env, info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::StalePrice {});
+
+    let env = mock_env_with_block_time(1060);
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+}
+
+#[test]
+fn auction() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (
+            &"asset0000".to_string(),
+            &Decimal::from_ratio(100u128, 1u128),
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // open uusd-asset0000 position; mint 1000000 / 100 / 2 = 5000
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // cannot liquidate a safely collateralized position
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::Auction {
+            position_idx: Uint128::from(1u128),
+        })
+        .unwrap(),
+    });
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::SafelyCollateralizedPosition {});
+
+    // price rises to 150; collateral ratio 1000000 / (5000 * 150) = 133% < 150%
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (
+            &"asset0000".to_string(),
+            &Decimal::from_ratio(150u128, 1u128),
+        ),
+    ]);
+
+    // discounted price = 150 / (1 - 0.2) = 187.5
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::Auction {
+            position_idx: Uint128::from(1u128),
+        })
+        .unwrap(),
+    });
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "auction"),
+            attr("position_idx", "1"),
+            attr("owner", "addr0000"),
+            attr("return_collateral_amount", "186000uusd"), // 1000 * 187.5 - 1500
+            attr("liquidated_amount", "1000asset0000"),
+            attr("protocol_fee", "1500uusd"), // 1000 * 150 * 0.01
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(1000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(186000u128)
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(1500u128)
+                }],
+            })),
+        ]
+    );
+
+    // cannot liquidate more than the left asset amount
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(4001u128),
+        msg: to_binary(&Cw20HookMsg::Auction {
+            position_idx: Uint128::from(1u128),
+        })
+        .unwrap(),
+    });
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::LiquidationExceedsAsset {});
+
+    // pay back the whole debt; left collateral is refunded to the owner
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(4000u128),
+        msg: to_binary(&Cw20HookMsg::Auction {
+            position_idx: Uint128::from(1u128),
+        })
+        .unwrap(),
+    });
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(62500u128) // 812500 - 4000 * 187.5
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(4000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(744000u128)
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(6000u128)
+                }],
+            })),
+        ]
+    );
+
+    // position is closed
+    let _res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Position {
+            position_idx: Uint128::from(1u128),
+        },
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn short_position() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::one()),
+    ]);
+    deps.querier.with_tswap_pair(&[(
+        &"uusd".to_string(),
+        &"asset0000".to_string(),
+        &"pair0000".to_string(),
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // open short position; the minted asset is sold and the proceeds are locked
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: Some(ShortParams {
+            belief_price: None,
+            max_spread: None,
+        }),
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(500000u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "pair0000".to_string(),
+                    amount: Uint128::from(500000u128),
+                    msg: to_binary(&PairCw20HookMsg::Swap {
+                        belief_price: None,
+                        max_spread: None,
+                        to: None,
+                        deadline: None,
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::LockPositionFundsHook {
+                    position_idx: Uint128::from(1u128),
+                    receiver: "addr0000".to_string(),
+                    prev_balance: Uint128::zero(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "staking0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&StakingExecuteMsg::IncreaseShortToken {
+                    staker_addr: "addr0000".to_string(),
+                    asset_token: "asset0000".to_string(),
+                    amount: Uint128::from(500000u128),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Position {
+            position_idx: Uint128::from(1u128),
+        },
+    )
+    .unwrap();
+    let position: PositionResponse = from_binary(&res).unwrap();
+    assert!(position.is_short);
+
+    // only the contract itself can execute the lock hook
+    let msg = ExecuteMsg::LockPositionFundsHook {
+        position_idx: Uint128::from(1u128),
+        receiver: "addr0000".to_string(),
+        prev_balance: Uint128::zero(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    // swap returned 499000uusd
+    deps.querier.with_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(499000u128),
+        }],
+    );
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "lock_position_funds_hook"),
+            attr("position_idx", "1"),
+            attr("locked_amount", "499000uusd"),
+            attr("total_locked_amount", "499000uusd"),
+            attr("unlock_time", "2000"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PositionLockInfo {
+            position_idx: Uint128::from(1u128),
+        },
+    )
+    .unwrap();
+    let lock_info: PositionLockInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        lock_info,
+        PositionLockInfoResponse {
+            idx: Uint128::from(1u128),
+            receiver: "addr0000".to_string(),
+            locked_amount: Uint128::from(499000u128),
+            unlock_time: 2000u64,
+        }
+    );
+
+    // cannot unlock before the lock period expires
+    let msg = ExecuteMsg::UnlockPositionFunds {
+        positions_idx: vec![Uint128::from(1u128)],
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1999),
+        info.clone(),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::LockPeriodNotExpired {
+            position_idx: Uint128::from(1u128),
+            unlock_time: 2000,
+        }
+    );
+
+    // only the receiver can unlock
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(2000),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), mock_env_with_block_time(2000), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(499000u128),
+            }],
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_position_funds"),
+            attr("positions_idx", "1"),
+            attr("unlocked_amount", "499000uusd"),
+        ]
+    );
+}
+
+#[test]
+fn transfer_short_position() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::one()),
+    ]);
+    deps.querier.with_tswap_pair(&[(
+        &"uusd".to_string(),
+        &"asset0000".to_string(),
+        &"pair0000".to_string(),
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: Some(ShortParams {
+            belief_price: None,
+            max_spread: None,
+        }),
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(499000u128),
+        }],
+    );
+    let msg = ExecuteMsg::LockPositionFundsHook {
+        position_idx: Uint128::from(1u128),
+        receiver: "addr0000".to_string(),
+        prev_balance: Uint128::zero(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg)
+        .unwrap();
+
+    // the locked proceeds move with the position
+    let msg = ExecuteMsg::TransferPosition {
+        position_idx: Uint128::from(1u128),
+        recipient: "addr0001".to_string(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // the new owner sells more of the position
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(1u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(100000u128),
+        },
+        short_params: Some(ShortParams {
+            belief_price: None,
+            max_spread: None,
+        }),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+    assert!(res.messages.contains(&SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::LockPositionFundsHook {
+            position_idx: Uint128::from(1u128),
+            receiver: "addr0001".to_string(),
+            prev_balance: Uint128::from(499000u128),
+        })
+        .unwrap(),
+    }))));
+
+    // swap returned 99000uusd, which cannot be locked for the previous owner
+    deps.querier.with_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(598000u128),
+        }],
+    );
+    let msg = ExecuteMsg::LockPositionFundsHook {
+        position_idx: Uint128::from(1u128),
+        receiver: "addr0000".to_string(),
+        prev_balance: Uint128::from(499000u128),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(MOCK_CONTRACT_ADDR, &[]), msg)
+        .unwrap_err();
+    assert_eq!(res, ContractError::LockReceiverMismatch {});
+
+    let msg = ExecuteMsg::LockPositionFundsHook {
+        position_idx: Uint128::from(1u128),
+        receiver: "addr0001".to_string(),
+        prev_balance: Uint128::from(499000u128),
+    };
+    let _res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PositionLockInfo {
+            position_idx: Uint128::from(1u128),
+        },
+    )
+    .unwrap();
+    let lock_info: PositionLockInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        lock_info,
+        PositionLockInfoResponse {
+            idx: Uint128::from(1u128),
+            receiver: "addr0001".to_string(),
+            locked_amount: Uint128::from(598000u128),
+            unlock_time: 2000u64,
+        }
+    );
+
+    // the previous owner cannot unlock them
+    let msg = ExecuteMsg::UnlockPositionFunds {
+        positions_idx: vec![Uint128::from(1u128)],
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(2000),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(2000),
+        mock_info("addr0001", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(598000u128),
+            }],
+        }))]
+    );
+}
+
+#[test]
+fn native_asset() {
+    let mut deps = mock_dependencies(&[]);
+    let denom = format!("factory/{}/uapple", MOCK_CONTRACT_ADDR);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&denom, &Decimal::from_ratio(100u128, 1u128)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterNativeAsset {
+        subdenom: "uapple".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Custom(SeiMsg::CreateDenom {
+            subdenom: "uapple".to_string(),
+        }))
+    );
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "register"), attr("asset_token", denom.clone())]
+    );
+
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::NativeToken {
+            denom: denom.clone(),
+        },
+        collateral_ratio: Decimal::percent(150),
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Custom(SeiMsg::MintTokens {
+                amount: Coin {
+                    denom: denom.clone(),
+                    amount: Uint128::from(6666u128),
+                },
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: denom.clone(),
+                    amount: Uint128::from(6666u128),
+                }],
+            })),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Positions {
+            owner_addr: None,
+            asset_token: Some(denom.clone()),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let positions: PositionsResponse = from_binary(&res).unwrap();
+    assert_eq!(positions.positions.len(), 1);
+
+    // native assets must be burnt with the attached funds
+    let burn_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: denom.clone(),
+        },
+        amount: Uint128::from(6666u128),
+    };
+    let msg = ExecuteMsg::Burn {
+        position_idx: Uint128::from(1u128),
+        asset: burn_asset.clone(),
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::Std(StdError::generic_err(
+            "Native token balance mismatch between the argument and the transferred"
+        ))
+    );
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: denom.clone(),
+            amount: Uint128::from(6666u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Custom(SeiMsg::BurnTokens {
+                amount: Coin {
+                    denom: denom.clone(),
+                    amount: Uint128::from(6666u128),
+                },
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(6666u128),
+                }],
+            })),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Position {
+            position_idx: Uint128::from(1u128),
+        },
+    )
+    .unwrap();
+    let position: PositionResponse = from_binary(&res).unwrap();
+    assert_eq!(position.asset.amount, Uint128::zero());
+}
+
+#[test]
+fn open_position_with_extreme_amounts() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::raw(1_000_000_000u128)),
+        (&"asset0001".to_string(), &Decimal::raw(1u128)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for asset_token in ["asset0000", "asset0001"] {
+        let msg = ExecuteMsg::RegisterAsset {
+            asset_token: asset_token.to_string(),
+            auction_discount: Decimal::percent(20),
+            min_collateral_ratio: Decimal::percent(150),
+            ipo_params: None,
+        };
+        let info = mock_info("owner0000", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let collateral_amount = Uint128::new(u128::MAX);
+    let open_position = |asset_token: &str| ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: collateral_amount,
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: asset_token.to_string(),
+        },
+        collateral_ratio: Decimal::percent(150),
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_amount,
+        }],
+    );
+
+    // the mint amount does not fit in Uint128
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), open_position("asset0000"))
+        .unwrap_err();
+    assert!(matches!(res, ContractError::Std(StdError::Overflow { .. })));
+
+    // the smallest asset price is kept at full precision, the mint amount overflows
+    let res = execute(deps.as_mut(), env, info, open_position("asset0001")).unwrap_err();
+    assert!(matches!(res, ContractError::Std(StdError::Overflow { .. })));
+}
+
+#[test]
+fn multi_collateral_position() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+    deps.querier.with_collateral_infos(&[(
+        &"asset0001".to_string(),
+        &Decimal::percent(50),
+        &Decimal::percent(200), // 2 collateral_multiplier
+        &false,
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // mints 500000 asset
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // a second collateral asset joins the position
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::Deposit {
+            position_idx: Uint128::from(1u128),
+        })
+            .unwrap(),
+    });
+    let info = mock_info("asset0001", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res: PositionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Position {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(
+        res,
+        PositionResponse {
+            idx: Uint128::from(1u128),
+            owner: "addr0000".to_string(),
+            collateral: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(1000000u128),
+            },
+            asset: Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(500000u128),
+            },
+            is_short: false,
+            other_collaterals: vec![Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0001".to_string(),
+                },
+                amount: Uint128::from(1000000u128),
+            }],
+            accrued_stability_fee: Uint128::zero(),
+        }
+    );
+
+    // collateral value: 1000000 + 1000000 * 0.5 = 1500000
+    // weighted by the multipliers: 1000000 + 1000000 * 0.5 / 2 = 1250000
+    let res: PositionHealthResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PositionHealth {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(
+        res,
+        PositionHealthResponse {
+            idx: Uint128::from(1u128),
+            collateral_ratio: Some(Decimal::percent(300)),
+            min_collateral_ratio: Decimal::percent(180),
+            liquidation_price: Some(Decimal::from_ratio(5u128, 3u128)),
+            mintable_amount: Uint128::from(333333u128),
+            withdrawable_collateral: Uint128::from(500000u128),
+        }
+    );
+
+    // the mint check runs over the weighted value of both collaterals
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(1u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(333334u128),
+        },
+        short_params: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::MintBelowMinCollateralRatio {});
+
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(1u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(333333u128),
+        },
+        short_params: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // withdrawing the second collateral breaks the ratio
+    let msg = ExecuteMsg::Withdraw {
+        position_idx: Uint128::from(1u128),
+        collateral: Some(Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        }),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::WithdrawBelowMinCollateralRatio {});
+
+    // the protocol fee is taken from the main collateral first
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(333333u128),
+        msg: to_binary(&Cw20HookMsg::Burn {
+            position_idx: Uint128::from(1u128),
+        })
+            .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "burn"),
+            attr("position_idx", "1"),
+            attr("burn_amount", "333333asset0000"),
+            attr("protocol_fee", "3333uusd"),
+        ]
+    );
+
+    let msg = ExecuteMsg::Withdraw {
+        position_idx: Uint128::from(1u128),
+        collateral: Some(Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            amount: Uint128::from(600000u128),
+        }),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw"),
+            attr("position_idx", "1"),
+            attr("withdraw_amount", "600000asset0001"),
+            attr("tax_amount", "0asset0001"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0001".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(600000u128),
+            })
+                .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // closing the position refunds every collateral
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(500000u128),
+        msg: to_binary(&Cw20HookMsg::ClosePosition {
+            position_idx: Uint128::from(1u128),
+        })
+            .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env, mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "close_position"),
+            attr("position_idx", "1"),
+            attr("burn_amount", "500000asset0000"),
+            attr("protocol_fee", "5000uusd"),
+            attr("refund_collateral_amount", "991667uusd,400000asset0001"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(500000u128),
+                })
+                    .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(5000u128)
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(991667u128)
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0001".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(400000u128),
+                })
+                    .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+}
+
+#[test]
+fn simulate_multi_collateral_withdraw() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+    deps.querier.with_collateral_infos(&[(
+        &"asset0001".to_string(),
+        &Decimal::percent(50),
+        &Decimal::one(),
+        &false,
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // mints 500000 asset
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::Deposit {
+            position_idx: Uint128::from(1u128),
+        })
+            .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("asset0001", &[]), msg).unwrap();
+
+    // repays the whole debt, 5000 uusd protocol fee
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(500000u128),
+        msg: to_binary(&Cw20HookMsg::Burn {
+            position_idx: Uint128::from(1u128),
+        })
+            .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+
+    let res: SimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::SimulateWithdraw {
+                sender: "addr0000".to_string(),
+                position_idx: Uint128::from(1u128),
+                collateral: None,
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(
+        res,
+        SimulationResponse {
+            mint_amount: Uint128::zero(),
+            protocol_fee: vec![],
+            refund_collateral: vec![
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(995000u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "asset0001".to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            collateral_ratio: None,
+        }
+    );
+}
+
+#[test]
+fn stability_fee() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: None,
+        min_collateral_ratio: None,
+        stability_fee_rate: Some(Decimal::one()),
+        ipo_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidStabilityFeeRate {});
+
+    let msg = ExecuteMsg::UpdateAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: None,
+        min_collateral_ratio: None,
+        stability_fee_rate: Some(Decimal::percent(10)),
+        ipo_params: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // mints 500000 asset
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // half a year later the debt accrued 5%
+    let env = mock_env_with_block_time(1000 + 31536000 / 2);
+    let res: PositionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Position {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.accrued_stability_fee, Uint128::from(25000u128));
+
+    let res: AssetConfigResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::AssetConfig {
+                asset_token: "asset0000".to_string(),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.stability_fee_index, Decimal::percent(105));
+
+    // touching the position settles the fee into collateral
+    let msg = ExecuteMsg::Deposit {
+        position_idx: Uint128::from(1u128),
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100000u128),
+        },
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(25000u128),
+            }],
+        }))]
+    );
+
+    let res: PositionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Position {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.collateral.amount, Uint128::from(1075000u128));
+    assert_eq!(res.asset.amount, Uint128::from(500000u128));
+    assert_eq!(res.accrued_stability_fee, Uint128::zero());
+
+    // fees compound on each settlement: 500000 * 1.05 * 1.05 / 1.05
+    let env = mock_env_with_block_time(1000 + 31536000);
+    let res: PositionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Position {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.accrued_stability_fee, Uint128::from(25000u128));
+}
+
+#[test]
+fn debt_ceilings() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the owner can set caps
+    let msg = ExecuteMsg::UpdateMintCap {
+        asset_token: "asset0000".to_string(),
+        mint_cap: Some(Uint128::from(600000u128)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_mint_cap"),
+            attr("asset_token", "asset0000"),
+            attr("mint_cap", "600000"),
+        ]
+    );
+
+    let msg = ExecuteMsg::UpdateDepositCap {
+        collateral: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        deposit_cap: Some(Uint128::from(1500000u128)),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    // mints 500000 asset
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res: UtilizationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Utilization {
+                asset_info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(
+        res,
+        UtilizationResponse {
+            asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            total_minted: Uint128::from(500000u128),
+            mint_cap: Some(Uint128::from(600000u128)),
+            mint_utilization: Some(Decimal::from_ratio(5u128, 6u128)),
+            total_deposited: Uint128::zero(),
+            deposit_cap: None,
+            deposit_utilization: None,
+        }
+    );
+
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(1u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(100001u128),
+        },
+        short_params: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::MintCapExceeded {});
+
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(1u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(100000u128),
+        },
+        short_params: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Deposit {
+        position_idx: Uint128::from(1u128),
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(500001u128),
+        },
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500001u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::DepositCapExceeded {});
+
+    // burning releases room under both caps, the protocol fee leaves the collateral
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_binary(&Cw20HookMsg::Burn {
+            position_idx: Uint128::from(1u128),
+        })
+            .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+
+    let res: UtilizationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Utilization {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(
+        res,
+        UtilizationResponse {
+            asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            total_minted: Uint128::zero(),
+            mint_cap: None,
+            mint_utilization: None,
+            total_deposited: Uint128::from(999000u128),
+            deposit_cap: Some(Uint128::from(1500000u128)),
+            deposit_utilization: Some(Decimal::permille(666)),
+        }
+    );
+
+    // closing the position clears its totals
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(500000u128),
+        msg: to_binary(&Cw20HookMsg::ClosePosition {
+            position_idx: Uint128::from(1u128),
+        })
+            .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+
+    let res: UtilizationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Utilization {
+                asset_info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.total_minted, Uint128::zero());
+    assert_eq!(res.mint_utilization, Some(Decimal::zero()));
+}
+
+#[test]
+fn pause() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(100)),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle: None,
+        collector: None,
+        collateral_oracle: None,
+        tswap_factory: None,
+        token_code_id: None,
+        protocol_fee_rate: None,
+        staking: None,
+        lock_period: None,
+        position_nft: None,
+        guardian: Some("guardian0000".to_string()),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(150),
+        ipo_params: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let open_msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let open_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let env = mock_env_with_block_time(1000);
+    let _res = execute(deps.as_mut(), env.clone(), open_info.clone(), open_msg.clone()).unwrap();
+
+    // only the owner and the guardian can pause
+    let msg = ExecuteMsg::Pause {
+        asset_token: Some("asset0000".to_string()),
+        operations: vec![PauseOperation::Mint],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("guardian0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "pause"),
+            attr("asset_token", "asset0000"),
+            attr("operations", "mint"),
+        ]
+    );
+
+    // opening a position mints
+    let res = execute(deps.as_mut(), env.clone(), open_info.clone(), open_msg.clone())
+        .unwrap_err();
+    assert_eq!(res, ContractError::OperationPaused {});
+
+    let mint_msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(1u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(100000u128),
+        },
+        short_params: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), mint_msg.clone())
+        .unwrap_err();
+    assert_eq!(res, ContractError::OperationPaused {});
+
+    // other operations are unaffected
+    let deposit_msg = ExecuteMsg::Deposit {
+        position_idx: Uint128::from(1u128),
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100000u128),
+        },
+    };
+    let deposit_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), deposit_info.clone(), deposit_msg.clone())
+        .unwrap();
+
+    // the guardian cannot resume
+    let msg = ExecuteMsg::Unpause {
+        asset_token: Some("asset0000".to_string()),
+        operations: vec![PauseOperation::Mint],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("guardian0000", &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), mint_msg).unwrap();
+
+    // a global pause of all operations stops every asset
+    let msg = ExecuteMsg::Pause {
+        asset_token: None,
+        operations: vec![PauseOperation::Withdraw, PauseOperation::All],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("guardian0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "pause"),
+            attr("asset_token", "all"),
+            attr("operations", "withdraw,all"),
+        ]
+    );
+
+    let res: PauseInfoResponse = from_binary(
+        &query(deps.as_ref(), env.clone(), QueryMsg::PauseInfo { asset_token: None }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        PauseInfoResponse {
+            asset_token: None,
+            open_position: false,
+            mint: false,
+            withdraw: true,
+            all: true,
+        }
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), deposit_info.clone(), deposit_msg.clone())
+        .unwrap_err();
+    assert_eq!(res, ContractError::OperationPaused {});
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_binary(&Cw20HookMsg::Burn {
+            position_idx: Uint128::from(1u128),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::OperationPaused {});
+
+    // resuming all operations keeps withdrawals paused
+    let msg = ExecuteMsg::Unpause {
+        asset_token: None,
+        operations: vec![PauseOperation::All],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    let _res = execute(deps.as_mut(), env.clone(), deposit_info, deposit_msg).unwrap();
+
+    let msg = ExecuteMsg::Withdraw {
+        position_idx: Uint128::from(1u128),
+        collateral: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000u128),
+        }),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::OperationPaused {});
+
+    let res: PauseInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::PauseInfo {
+                asset_token: Some("asset0000".to_string()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        PauseInfoResponse {
+            asset_token: Some("asset0000".to_string()),
+            open_position: false,
+            mint: false,
+            withdraw: false,
+            all: false,
+        }
+    );
+}
+
+#[test]
+fn pre_ipo_asset() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_oracle_price(&[
+        (&"uusd".to_string(), &Decimal::one()),
+        (&"asset0000".to_string(), &Decimal::percent(40)),
+    ]);
+    deps.querier.with_collateral_infos(&[(
+        &"asset0001".to_string(),
+        &Decimal::percent(50),
+        &Decimal::one(),
+        &false,
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle: "oracle0000".to_string(),
+        collector: "collector0000".to_string(),
+        collateral_oracle: "collateraloracle0000".to_string(),
+        staking: "staking0000".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        token_code_id: TOKEN_CODE_ID,
+        protocol_fee_rate: Decimal::percent(1),
+        lock_period: 1000,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let ipo_params = IpoParams {
+        mint_end: 1030,
+        pre_ipo_price: Decimal::percent(50),
+        min_collateral_ratio_after_ipo: Decimal::percent(150),
+        trigger_addr: "feeder0000".to_string(),
+    };
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset0000".to_string(),
+        auction_discount: Decimal::percent(20),
+        min_collateral_ratio: Decimal::percent(200),
+        ipo_params: Some(ipo_params.clone()),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let res: AssetConfigResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::AssetConfig {
+                asset_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.ipo_params, Some(ipo_params));
+
+    // pre-IPO positions only accept the base denom
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::OpenPosition {
+            asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            collateral_ratio: Decimal::percent(200),
+            short_params: None,
+        })
+        .unwrap(),
+    });
+    let env = mock_env_with_block_time(1000);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("asset0001", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::PreIpoCollateral {});
+
+    // minted at the pre-IPO price instead of the oracle price
+    let msg = ExecuteMsg::OpenPosition {
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        collateral_ratio: Decimal::percent(200),
+        short_params: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "open_position"),
+            attr("position_idx", "1"),
+            attr("mint_amount", "1000000asset0000"),
+            attr("collateral_amount", "1000000uusd"),
+        ]
+    );
+
+    // no burn or withdraw until the IPO
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_binary(&Cw20HookMsg::Burn {
+            position_idx: Uint128::from(1u128),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::PreIpoAsset {});
+
+    let withdraw_msg = ExecuteMsg::Withdraw {
+        position_idx: Uint128::from(1u128),
+        collateral: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100000u128),
+        }),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        withdraw_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::PreIpoAsset {});
+
+    // minting stops at the end of the minting period
+    let msg = ExecuteMsg::Mint {
+        position_idx: Uint128::from(1u128),
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        short_params: None,
+    };
+    let env = mock_env_with_block_time(1031);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::MintPeriodEnded { mint_end: 1030 });
+
+    let res: PositionHealthResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PositionHealth {
+                position_idx: Uint128::from(1u128),
+            },
+        )
+            .unwrap(),
+    )
+        .unwrap();
+    assert_eq!(res.mintable_amount, Uint128::zero());
+
+    // only the trigger address converts the asset
+    let msg = ExecuteMsg::TriggerIpo {
+        asset_token: "asset0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("feeder0000", &[]), msg.clone())
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "trigger_ipo"), attr("asset_token", "asset0000")]
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("feeder0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::NotPreIpoAsset {});
+
+    let res: AssetConfigResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::AssetConfig {
+                asset_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.min_collateral_ratio, Decimal::percent(150));
+    assert_eq!(res.ipo_params, None);
+
+    // the oracle price applies after the IPO: 1000000 asset at 0.4 needs 600000 collateral
+    let res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), withdraw_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw"),
+            attr("position_idx", "1"),
+            attr("withdraw_amount", "100000uusd"),
+            attr("tax_amount", "0uusd"),
         ]
     );
 }
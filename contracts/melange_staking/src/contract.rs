@@ -0,0 +1,298 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Binary, CanonicalAddr, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
+
+use crate::rewards::{adjust_premium, deposit_reward, query_reward_info, withdraw_reward};
+use crate::staking::{
+    auto_stake, auto_stake_hook, bond, decrease_short_token, increase_short_token, unbond,
+};
+use crate::state::{
+    read_config, read_pool_info, store_config, store_pool_info, Config, MigrationParams, PoolInfo,
+};
+use cw20::Cw20ReceiveMsg;
+use melange_protocol::staking::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg,
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    store_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_canonicalize(&msg.owner)?,
+            melange_token: deps.api.addr_canonicalize(&msg.melange_token)?,
+            mint_contract: deps.api.addr_canonicalize(&msg.mint_contract)?,
+            oracle_contract: deps.api.addr_canonicalize(&msg.oracle_contract)?,
+            tswap_factory: deps.api.addr_canonicalize(&msg.tswap_factory)?,
+            base_denom: msg.base_denom,
+            premium_min_update_interval: msg.premium_min_update_interval,
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            premium_min_update_interval,
+        } => update_config(deps, info, owner, premium_min_update_interval),
+        ExecuteMsg::RegisterAsset {
+            asset_token,
+            staking_token,
+        } => register_asset(deps, info, asset_token, staking_token),
+        ExecuteMsg::DeprecateStakingToken {
+            asset_token,
+            new_staking_token,
+        } => deprecate_staking_token(deps, info, asset_token, new_staking_token),
+        ExecuteMsg::IncreaseShortToken {
+            staker_addr,
+            asset_token,
+            amount,
+        } => increase_short_token(deps, info, staker_addr, asset_token, amount),
+        ExecuteMsg::DecreaseShortToken {
+            staker_addr,
+            asset_token,
+            amount,
+        } => decrease_short_token(deps, info, staker_addr, asset_token, amount),
+        ExecuteMsg::Unbond {
+            asset_token,
+            amount,
+        } => unbond(deps, info.sender.to_string(), asset_token, amount),
+        ExecuteMsg::Withdraw { asset_token } => withdraw_reward(deps, info, asset_token),
+        ExecuteMsg::AutoStake {
+            assets,
+            slippage_tolerance,
+        } => auto_stake(deps, env, info, assets, slippage_tolerance),
+        ExecuteMsg::AutoStakeHook {
+            asset_token,
+            staking_token,
+            staker_addr,
+            prev_staking_token_amount,
+        } => auto_stake_hook(
+            deps,
+            env,
+            info,
+            asset_token,
+            staking_token,
+            staker_addr,
+            prev_staking_token_amount,
+        ),
+        ExecuteMsg::AdjustPremium { asset_tokens } => adjust_premium(deps, env, asset_tokens),
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> StdResult<Response> {
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Bond { asset_token }) => {
+            let pool_info: PoolInfo =
+                read_pool_info(deps.storage, &deps.api.addr_canonicalize(&asset_token)?)?;
+
+            // only staking token contract can execute this message
+            if pool_info.staking_token != deps.api.addr_canonicalize(info.sender.as_str())? {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+
+            bond(deps, cw20_msg.sender, asset_token, cw20_msg.amount)
+        }
+        Ok(Cw20HookMsg::DepositReward { rewards }) => {
+            let config: Config = read_config(deps.storage)?;
+
+            // only reward token contract can execute this message
+            if config.melange_token != deps.api.addr_canonicalize(info.sender.as_str())? {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+
+            deposit_reward(deps, rewards, cw20_msg.amount)
+        }
+        Err(_) => Err(StdError::generic_err("data should be given")),
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    premium_min_update_interval: Option<u64>,
+) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    if let Some(premium_min_update_interval) = premium_min_update_interval {
+        config.premium_min_update_interval = premium_min_update_interval;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+fn register_asset(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_token: String,
+    staking_token: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let asset_token_raw = deps.api.addr_canonicalize(&asset_token)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if read_pool_info(deps.storage, &asset_token_raw).is_ok() {
+        return Err(StdError::generic_err("Asset was already registered"));
+    }
+
+    store_pool_info(
+        deps.storage,
+        &asset_token_raw,
+        &PoolInfo {
+            staking_token: deps.api.addr_canonicalize(&staking_token)?,
+            total_bond_amount: Uint128::zero(),
+            total_short_amount: Uint128::zero(),
+            reward_index: Decimal::zero(),
+            short_reward_index: Decimal::zero(),
+            pending_reward: Uint128::zero(),
+            short_pending_reward: Uint128::zero(),
+            premium_rate: Decimal::zero(),
+            short_reward_weight: Decimal::zero(),
+            premium_updated_time: 0,
+            migration_params: None,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_asset"),
+        attr("asset_token", asset_token),
+    ]))
+}
+
+/// Replaces the staking token of the asset. The positions bonded with the
+/// deprecated token stop accruing rewards and must be unbonded to migrate
+fn deprecate_staking_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_token: String,
+    new_staking_token: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let asset_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&asset_token)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let mut pool_info: PoolInfo = read_pool_info(deps.storage, &asset_token_raw)?;
+
+    if pool_info.migration_params.is_some() {
+        return Err(StdError::generic_err(
+            "This asset LP token has already been migrated",
+        ));
+    }
+
+    let deprecated_token_addr = deps.api.addr_humanize(&pool_info.staking_token)?;
+
+    pool_info.total_bond_amount = Uint128::zero();
+    pool_info.migration_params = Some(MigrationParams {
+        index_snapshot: pool_info.reward_index,
+        deprecated_staking_token: pool_info.staking_token,
+    });
+    pool_info.staking_token = deps.api.addr_canonicalize(&new_staking_token)?;
+
+    store_pool_info(deps.storage, &asset_token_raw, &pool_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deprecate_staking_token"),
+        attr("asset_token", asset_token),
+        attr(
+            "deprecated_staking_token",
+            deprecated_token_addr.to_string(),
+        ),
+        attr("new_staking_token", new_staking_token),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PoolInfo { asset_token } => to_binary(&query_pool_info(deps, asset_token)?),
+        QueryMsg::RewardInfo {
+            staker_addr,
+            asset_token,
+        } => to_binary(&query_reward_info(deps, staker_addr, asset_token)?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = read_config(deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.addr_humanize(&state.owner)?.to_string(),
+        melange_token: deps.api.addr_humanize(&state.melange_token)?.to_string(),
+        mint_contract: deps.api.addr_humanize(&state.mint_contract)?.to_string(),
+        oracle_contract: deps.api.addr_humanize(&state.oracle_contract)?.to_string(),
+        tswap_factory: deps.api.addr_humanize(&state.tswap_factory)?.to_string(),
+        base_denom: state.base_denom,
+        premium_min_update_interval: state.premium_min_update_interval,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_pool_info(deps: Deps, asset_token: String) -> StdResult<PoolInfoResponse> {
+    let asset_token_raw = deps.api.addr_canonicalize(&asset_token)?;
+    let pool_info: PoolInfo = read_pool_info(deps.storage, &asset_token_raw)?;
+    let (migration_index_snapshot, migration_deprecated_staking_token) =
+        match pool_info.migration_params {
+            Some(params) => (
+                Some(params.index_snapshot),
+                Some(
+                    deps.api
+                        .addr_humanize(&params.deprecated_staking_token)?
+                        .to_string(),
+                ),
+            ),
+            None => (None, None),
+        };
+
+    Ok(PoolInfoResponse {
+        asset_token,
+        staking_token: deps
+            .api
+            .addr_humanize(&pool_info.staking_token)?
+            .to_string(),
+        total_bond_amount: pool_info.total_bond_amount,
+        total_short_amount: pool_info.total_short_amount,
+        reward_index: pool_info.reward_index,
+        short_reward_index: pool_info.short_reward_index,
+        pending_reward: pool_info.pending_reward,
+        short_pending_reward: pool_info.short_pending_reward,
+        premium_rate: pool_info.premium_rate,
+        short_reward_weight: pool_info.short_reward_weight,
+        premium_updated_time: pool_info.premium_updated_time,
+        migration_index_snapshot,
+        migration_deprecated_staking_token,
+    })
+}
@@ -0,0 +1,9 @@
+pub mod contract;
+mod math;
+mod querier;
+mod rewards;
+mod staking;
+mod state;
+
+#[cfg(test)]
+mod testing;
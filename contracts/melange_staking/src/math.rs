@@ -0,0 +1,30 @@
+use cosmwasm_std::{Decimal, Uint128};
+
+const DECIMAL_FRACTIONAL: Uint128 = Uint128::new(1_000_000_000u128);
+
+/// premium rate above which the short reward weight is capped
+const PREMIUM_RATE_CAP: Decimal = Decimal::percent(7);
+/// maximum share of the rewards distributed to short positions
+const MAX_SHORT_REWARD_WEIGHT: Decimal = Decimal::percent(40);
+
+/// return a / b
+pub fn decimal_division(a: Decimal, b: Decimal) -> Decimal {
+    Decimal::from_ratio(DECIMAL_FRACTIONAL * a, b * DECIMAL_FRACTIONAL)
+}
+
+pub fn decimal_multiplication(a: Decimal, b: Decimal) -> Decimal {
+    Decimal::from_ratio(a * DECIMAL_FRACTIONAL * b, DECIMAL_FRACTIONAL)
+}
+
+/// Share of the rewards distributed to short positions. It grows linearly
+/// with the premium rate until it reaches `MAX_SHORT_REWARD_WEIGHT`
+pub fn short_reward_weight(premium_rate: Decimal) -> Decimal {
+    if premium_rate >= PREMIUM_RATE_CAP {
+        return MAX_SHORT_REWARD_WEIGHT;
+    }
+
+    decimal_multiplication(
+        MAX_SHORT_REWARD_WEIGHT,
+        decimal_division(premium_rate, PREMIUM_RATE_CAP),
+    )
+}
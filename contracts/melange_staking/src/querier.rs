@@ -0,0 +1,63 @@
+use cosmwasm_std::{to_binary, Addr, Decimal, Deps, QueryRequest, StdResult, WasmQuery};
+use melange_protocol::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
+use terraswap::asset::AssetInfo;
+use terraswap::pair::{PoolResponse, QueryMsg as PairQueryMsg};
+use terraswap::querier::query_pair_info;
+
+use crate::math::decimal_division;
+
+/// Computes the premium of the terraswap price over the oracle price.
+/// A discounted or empty pool has no premium
+pub fn compute_premium_rate(
+    deps: Deps,
+    oracle_contract: Addr,
+    tswap_factory: Addr,
+    asset_token: Addr,
+    base_denom: String,
+) -> StdResult<Decimal> {
+    let pair_info = query_pair_info(
+        &deps.querier,
+        tswap_factory,
+        &[
+            AssetInfo::NativeToken { denom: base_denom },
+            AssetInfo::Token {
+                contract_addr: asset_token.to_string(),
+            },
+        ],
+    )?;
+
+    let pool: PoolResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: pair_info.contract_addr,
+        msg: to_binary(&PairQueryMsg::Pool {})?,
+    }))?;
+
+    let (base_pool, asset_pool) = if pool.assets[0].is_native_token() {
+        (pool.assets[0].amount, pool.assets[1].amount)
+    } else {
+        (pool.assets[1].amount, pool.assets[0].amount)
+    };
+
+    if base_pool.is_zero() || asset_pool.is_zero() {
+        return Ok(Decimal::zero());
+    }
+
+    let tswap_price = Decimal::from_ratio(base_pool, asset_pool);
+    let oracle_price = query_price(deps, oracle_contract, asset_token.to_string())?;
+    if oracle_price.is_zero() || tswap_price <= oracle_price {
+        return Ok(Decimal::zero());
+    }
+
+    Ok(decimal_division(tswap_price - oracle_price, oracle_price))
+}
+
+pub fn query_price(deps: Deps, oracle_contract: Addr, asset_token: String) -> StdResult<Decimal> {
+    let res: PriceResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: oracle_contract.to_string(),
+        msg: to_binary(&OracleQueryMsg::Price {
+            asset_token,
+            timeframe: None,
+        })?,
+    }))?;
+
+    Ok(res.rate)
+}
@@ -0,0 +1,289 @@
+use cosmwasm_std::{
+    attr, to_binary, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+    Response, StdError, StdResult, Storage, Uint128, WasmMsg,
+};
+
+use crate::math::short_reward_weight;
+use crate::querier::compute_premium_rate;
+use crate::state::{
+    read_config, read_is_migrated, read_pool_info, rewards_read, rewards_store, store_pool_info,
+    Config, PoolInfo, RewardInfo,
+};
+use cw20::Cw20ExecuteMsg;
+use melange_protocol::staking::{RewardInfoResponse, RewardInfoResponseItem};
+
+/// Adjusts the premium rate of the given assets, which decides the share of
+/// the rewards distributed to the short positions
+pub fn adjust_premium(deps: DepsMut, env: Env, asset_tokens: Vec<String>) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+    let tswap_factory = deps.api.addr_humanize(&config.tswap_factory)?;
+
+    for asset_token in asset_tokens.iter() {
+        let asset_token_raw = deps.api.addr_canonicalize(asset_token)?;
+        let mut pool_info: PoolInfo = read_pool_info(deps.storage, &asset_token_raw)?;
+        if env.block.time.seconds()
+            < pool_info.premium_updated_time + config.premium_min_update_interval
+        {
+            return Err(StdError::generic_err(
+                "cannot adjust premium before premium_min_update_interval passed",
+            ));
+        }
+
+        let premium_rate = compute_premium_rate(
+            deps.as_ref(),
+            oracle_contract.clone(),
+            tswap_factory.clone(),
+            deps.api.addr_validate(asset_token)?,
+            config.base_denom.clone(),
+        )?;
+
+        pool_info.premium_rate = premium_rate;
+        pool_info.short_reward_weight = short_reward_weight(premium_rate);
+        pool_info.premium_updated_time = env.block.time.seconds();
+        store_pool_info(deps.storage, &asset_token_raw, &pool_info)?;
+    }
+
+    Ok(Response::new().add_attribute("action", "premium_adjustment"))
+}
+
+// deposit_reward must be from reward token contract
+pub fn deposit_reward(
+    deps: DepsMut,
+    rewards: Vec<(String, Uint128)>,
+    rewards_amount: Uint128,
+) -> StdResult<Response> {
+    let total_rewards: Uint128 = rewards.iter().map(|(_, amount)| *amount).sum();
+    if total_rewards != rewards_amount {
+        return Err(StdError::generic_err("rewards amount miss matched"));
+    }
+
+    for (asset_token, amount) in rewards.iter() {
+        let asset_token_raw: CanonicalAddr = deps.api.addr_canonicalize(asset_token)?;
+        let mut pool_info: PoolInfo = read_pool_info(deps.storage, &asset_token_raw)?;
+
+        // split the rewards between the lp stakers and the short positions
+        let short_reward = *amount * pool_info.short_reward_weight;
+        let normal_reward = amount.checked_sub(short_reward)?;
+
+        if pool_info.total_bond_amount.is_zero() {
+            pool_info.pending_reward += normal_reward;
+        } else {
+            let reward_per_bond = Decimal::from_ratio(
+                normal_reward + pool_info.pending_reward,
+                pool_info.total_bond_amount,
+            );
+            pool_info.reward_index += reward_per_bond;
+            pool_info.pending_reward = Uint128::zero();
+        }
+
+        if pool_info.total_short_amount.is_zero() {
+            pool_info.short_pending_reward += short_reward;
+        } else {
+            let reward_per_bond = Decimal::from_ratio(
+                short_reward + pool_info.short_pending_reward,
+                pool_info.total_short_amount,
+            );
+            pool_info.short_reward_index += reward_per_bond;
+            pool_info.short_pending_reward = Uint128::zero();
+        }
+
+        store_pool_info(deps.storage, &asset_token_raw, &pool_info)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deposit_reward"),
+        attr("rewards_amount", rewards_amount.to_string()),
+    ]))
+}
+
+// withdraw all rewards or single reward depending on asset_token
+pub fn withdraw_reward(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_token: Option<String>,
+) -> StdResult<Response> {
+    let staker_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let asset_token = asset_token
+        .map(|a| deps.api.addr_canonicalize(&a))
+        .transpose()?;
+
+    let normal_reward = _withdraw_reward(deps.storage, &staker_addr, &asset_token, false)?;
+    let short_reward = _withdraw_reward(deps.storage, &staker_addr, &asset_token, true)?;
+
+    let amount = normal_reward + short_reward;
+    if amount.is_zero() {
+        return Err(StdError::generic_err("No rewards to withdraw"));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.melange_token)?.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }))
+        .add_attributes(vec![
+            attr("action", "withdraw"),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
+fn _withdraw_reward(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_token: &Option<CanonicalAddr>,
+    is_short: bool,
+) -> StdResult<Uint128> {
+    let reward_pairs = read_reward_pairs(storage, staker_addr, asset_token, is_short)?;
+
+    let mut amount: Uint128 = Uint128::zero();
+    for (asset_token, mut reward_info) in reward_pairs {
+        let pool_info: PoolInfo = read_pool_info(storage, &asset_token)?;
+
+        // Withdraw reward to pending reward
+        let (pool_index, _) = position_index(
+            storage,
+            &pool_info,
+            &asset_token,
+            staker_addr,
+            &reward_info,
+            is_short,
+        );
+        before_share_change(pool_index, &mut reward_info)?;
+
+        amount += reward_info.pending_reward;
+        reward_info.pending_reward = Uint128::zero();
+
+        // Update rewards info
+        if reward_info.bond_amount.is_zero() {
+            rewards_store(storage, staker_addr, is_short).remove(asset_token.as_slice());
+        } else {
+            rewards_store(storage, staker_addr, is_short)
+                .save(asset_token.as_slice(), &reward_info)?;
+        }
+    }
+
+    Ok(amount)
+}
+
+/// Moves the rewards accrued since the last share change to the pending rewards
+pub fn before_share_change(pool_index: Decimal, reward_info: &mut RewardInfo) -> StdResult<()> {
+    let pending_reward = (reward_info.bond_amount * pool_index)
+        .checked_sub(reward_info.bond_amount * reward_info.index)?;
+
+    reward_info.index = pool_index;
+    reward_info.pending_reward += pending_reward;
+    Ok(())
+}
+
+/// Returns the pool index the position accrues rewards with and whether the
+/// position still holds the deprecated staking token. Such positions stop
+/// accruing rewards at the migration snapshot
+pub fn position_index(
+    storage: &dyn Storage,
+    pool_info: &PoolInfo,
+    asset_token: &CanonicalAddr,
+    staker_addr: &CanonicalAddr,
+    reward_info: &RewardInfo,
+    is_short: bool,
+) -> (Decimal, Option<bool>) {
+    if is_short {
+        return (pool_info.short_reward_index, None);
+    }
+
+    match &pool_info.migration_params {
+        Some(params)
+            if !reward_info.bond_amount.is_zero()
+                && !read_is_migrated(storage, asset_token, staker_addr) =>
+        {
+            (params.index_snapshot, Some(true))
+        }
+        Some(_) => (pool_info.reward_index, Some(false)),
+        None => (pool_info.reward_index, None),
+    }
+}
+
+pub fn query_reward_info(
+    deps: Deps,
+    staker_addr: String,
+    asset_token: Option<String>,
+) -> StdResult<RewardInfoResponse> {
+    let staker_addr_raw = deps.api.addr_canonicalize(&staker_addr)?;
+    let asset_token = asset_token
+        .map(|a| deps.api.addr_canonicalize(&a))
+        .transpose()?;
+
+    let reward_infos: Vec<RewardInfoResponseItem> = [
+        _read_reward_infos(deps, &staker_addr_raw, &asset_token, false)?,
+        _read_reward_infos(deps, &staker_addr_raw, &asset_token, true)?,
+    ]
+    .concat();
+
+    Ok(RewardInfoResponse {
+        staker_addr,
+        reward_infos,
+    })
+}
+
+fn _read_reward_infos(
+    deps: Deps,
+    staker_addr: &CanonicalAddr,
+    asset_token: &Option<CanonicalAddr>,
+    is_short: bool,
+) -> StdResult<Vec<RewardInfoResponseItem>> {
+    let reward_pairs = read_reward_pairs(deps.storage, staker_addr, asset_token, is_short)?;
+
+    reward_pairs
+        .into_iter()
+        .map(|(asset_token, mut reward_info)| {
+            let pool_info: PoolInfo = read_pool_info(deps.storage, &asset_token)?;
+
+            let (pool_index, should_migrate) = position_index(
+                deps.storage,
+                &pool_info,
+                &asset_token,
+                staker_addr,
+                &reward_info,
+                is_short,
+            );
+            before_share_change(pool_index, &mut reward_info)?;
+
+            Ok(RewardInfoResponseItem {
+                asset_token: deps.api.addr_humanize(&asset_token)?.to_string(),
+                bond_amount: reward_info.bond_amount,
+                pending_reward: reward_info.pending_reward,
+                is_short,
+                should_migrate,
+            })
+        })
+        .collect()
+}
+
+fn read_reward_pairs(
+    storage: &dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_token: &Option<CanonicalAddr>,
+    is_short: bool,
+) -> StdResult<Vec<(CanonicalAddr, RewardInfo)>> {
+    let rewards_bucket = rewards_read(storage, staker_addr, is_short);
+
+    // single reward withdraw
+    if let Some(asset_token) = asset_token {
+        return Ok(match rewards_bucket.may_load(asset_token.as_slice())? {
+            Some(reward_info) => vec![(asset_token.clone(), reward_info)],
+            None => vec![],
+        });
+    }
+
+    rewards_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
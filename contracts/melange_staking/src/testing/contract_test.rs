@@ -0,0 +1,140 @@
+use crate::contract::{execute, instantiate, query};
+use crate::testing::mock_querier::mock_dependencies;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{attr, from_binary, Decimal, StdError, Uint128};
+use melange_protocol::staking::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg,
+};
+
+pub(crate) fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: "owner".to_string(),
+        melange_token: "reward".to_string(),
+        mint_contract: "mint".to_string(),
+        oracle_contract: "oracle".to_string(),
+        tswap_factory: "tswap_factory".to_string(),
+        base_denom: "uusd".to_string(),
+        premium_min_update_interval: 3600,
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // it worked, let's query the state
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        ConfigResponse {
+            owner: "owner".to_string(),
+            melange_token: "reward".to_string(),
+            mint_contract: "mint".to_string(),
+            oracle_contract: "oracle".to_string(),
+            tswap_factory: "tswap_factory".to_string(),
+            base_denom: "uusd".to_string(),
+            premium_min_update_interval: 3600,
+        },
+        config
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    // update owner
+    let info = mock_info("owner", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner2".to_string()),
+        premium_min_update_interval: Some(7200),
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // it worked, let's query the state
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("owner2", config.owner.as_str());
+    assert_eq!(7200, config.premium_min_update_interval);
+
+    // unauthorized err
+    let info = mock_info("owner", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        premium_min_update_interval: Some(7200),
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+}
+
+#[test]
+fn test_register() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset".to_string(),
+        staking_token: "staking".to_string(),
+    };
+
+    // failed with unauthorized error
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_asset"),
+            attr("asset_token", "asset"),
+        ]
+    );
+
+    // can not register twice
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, StdError::generic_err("Asset was already registered"));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pool_info,
+        PoolInfoResponse {
+            asset_token: "asset".to_string(),
+            staking_token: "staking".to_string(),
+            total_bond_amount: Uint128::zero(),
+            total_short_amount: Uint128::zero(),
+            reward_index: Decimal::zero(),
+            short_reward_index: Decimal::zero(),
+            pending_reward: Uint128::zero(),
+            short_pending_reward: Uint128::zero(),
+            premium_rate: Decimal::zero(),
+            short_reward_weight: Decimal::zero(),
+            premium_updated_time: 0,
+            migration_index_snapshot: None,
+            migration_deprecated_staking_token: None,
+        }
+    );
+}
@@ -0,0 +1,231 @@
+use crate::contract::{execute, instantiate, query};
+use crate::testing::contract_test::instantiate_msg;
+use crate::testing::mock_querier::mock_dependencies;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, CosmosMsg, Decimal, StdError, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use melange_protocol::staking::{
+    Cw20HookMsg, ExecuteMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse, RewardInfoResponseItem,
+};
+
+fn bond_msg(staker: &str, amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: staker.to_string(),
+        amount: Uint128::from(amount),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            asset_token: "asset".to_string(),
+        })
+        .unwrap(),
+    })
+}
+
+fn deposit_reward_msg(amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "factory".to_string(),
+        amount: Uint128::from(amount),
+        msg: to_binary(&Cw20HookMsg::DepositReward {
+            rewards: vec![("asset".to_string(), Uint128::from(amount))],
+        })
+        .unwrap(),
+    })
+}
+
+fn query_reward_info(deps: cosmwasm_std::Deps, staker_addr: &str) -> Vec<RewardInfoResponseItem> {
+    let data = query(
+        deps,
+        mock_env(),
+        QueryMsg::RewardInfo {
+            asset_token: None,
+            staker_addr: staker_addr.to_string(),
+        },
+    )
+    .unwrap();
+    let res: RewardInfoResponse = from_binary(&data).unwrap();
+    res.reward_infos
+}
+
+#[test]
+fn test_deprecate() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset".to_string(),
+        staking_token: "staking".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let bond_info = mock_info("staking", &[]);
+    let reward_info = mock_info("reward", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        bond_info.clone(),
+        bond_msg("addr", 100),
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        reward_info.clone(),
+        deposit_reward_msg(100),
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::DeprecateStakingToken {
+        asset_token: "asset".to_string(),
+        new_staking_token: "new_staking".to_string(),
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deprecate_staking_token"),
+            attr("asset_token", "asset"),
+            attr("deprecated_staking_token", "staking"),
+            attr("new_staking_token", "new_staking"),
+        ]
+    );
+
+    // can only be deprecated once
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("This asset LP token has already been migrated")
+    );
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(pool_info.staking_token, "new_staking".to_string());
+    assert_eq!(pool_info.total_bond_amount, Uint128::zero());
+    assert_eq!(pool_info.migration_index_snapshot, Some(Decimal::one()));
+    assert_eq!(
+        pool_info.migration_deprecated_staking_token,
+        Some("staking".to_string())
+    );
+
+    // the deprecated staking token can not be bonded anymore
+    let res = execute(deps.as_mut(), mock_env(), bond_info, bond_msg("addr", 100)).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    // a new staker bonds the new staking token and receives all the new rewards
+    let new_bond_info = mock_info("new_staking", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        new_bond_info.clone(),
+        bond_msg("addr2", 100),
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        reward_info,
+        deposit_reward_msg(100),
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_reward_info(deps.as_ref(), "addr"),
+        vec![RewardInfoResponseItem {
+            asset_token: "asset".to_string(),
+            bond_amount: Uint128::from(100u128),
+            pending_reward: Uint128::from(100u128),
+            is_short: false,
+            should_migrate: Some(true),
+        }]
+    );
+    assert_eq!(
+        query_reward_info(deps.as_ref(), "addr2"),
+        vec![RewardInfoResponseItem {
+            asset_token: "asset".to_string(),
+            bond_amount: Uint128::from(100u128),
+            pending_reward: Uint128::from(100u128),
+            is_short: false,
+            should_migrate: Some(false),
+        }]
+    );
+
+    // the position must be closed before bonding the new staking token
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        new_bond_info.clone(),
+        bond_msg("addr", 100),
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("The LP token for this asset has been deprecated, withdraw all your deprecated tokens to migrate your position")
+    );
+
+    // unbonding refunds the deprecated staking token
+    let msg = ExecuteMsg::Unbond {
+        asset_token: "asset".to_string(),
+        amount: Uint128::from(100u128),
+    };
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // the new staking token bonds are untouched
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(100u128));
+
+    // the position is migrated with the new staking token
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        new_bond_info,
+        bond_msg("addr", 100),
+    )
+    .unwrap();
+    assert_eq!(
+        query_reward_info(deps.as_ref(), "addr"),
+        vec![RewardInfoResponseItem {
+            asset_token: "asset".to_string(),
+            bond_amount: Uint128::from(100u128),
+            pending_reward: Uint128::from(100u128),
+            is_short: false,
+            should_migrate: Some(false),
+        }]
+    );
+}
@@ -0,0 +1,225 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, Empty, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+};
+use cw20::BalanceResponse;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use melange_protocol::oracle::PriceResponse;
+use terraswap::asset::{Asset, AssetInfo, PairInfo};
+use terraswap::pair::PoolResponse;
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        api: MockApi::default(),
+        storage: MockStorage::default(),
+        querier: custom_querier,
+        custom_query_type: PhantomData,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    token_querier: TokenQuerier,
+    oracle_price_querier: OraclePriceQuerier,
+    tswap_pair_querier: TswapPairQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct TokenQuerier {
+    // maps token contract to the balances of each address
+    balances: HashMap<String, HashMap<String, Uint128>>,
+}
+
+impl TokenQuerier {
+    pub fn new(balances: &[(&String, &[(&String, &Uint128)])]) -> Self {
+        let mut balances_map: HashMap<String, HashMap<String, Uint128>> = HashMap::new();
+        for (contract_addr, balances) in balances.iter() {
+            let mut contract_balances_map: HashMap<String, Uint128> = HashMap::new();
+            for (addr, balance) in balances.iter() {
+                contract_balances_map.insert(addr.to_string(), **balance);
+            }
+
+            balances_map.insert(contract_addr.to_string(), contract_balances_map);
+        }
+
+        TokenQuerier {
+            balances: balances_map,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct OraclePriceQuerier {
+    oracle_price: HashMap<String, Decimal>,
+}
+
+impl OraclePriceQuerier {
+    pub fn new(oracle_price: &[(&String, &Decimal)]) -> Self {
+        let mut oracle_price_map: HashMap<String, Decimal> = HashMap::new();
+        for (asset_token, oracle_price) in oracle_price.iter() {
+            oracle_price_map.insert(asset_token.to_string(), **oracle_price);
+        }
+
+        OraclePriceQuerier {
+            oracle_price: oracle_price_map,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct TswapPairQuerier {
+    // maps the asset infos to the pair contract and its liquidity token
+    pairs: HashMap<String, (String, String)>,
+    // maps the pair contract to its pool assets
+    pools: HashMap<String, [Asset; 2]>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {:?}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MockQueryMsg {
+    Balance {
+        address: String,
+    },
+    Price {
+        asset_token: String,
+        timeframe: Option<u64>,
+    },
+    Pair {
+        asset_infos: [AssetInfo; 2],
+    },
+    Pool {},
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    MockQueryMsg::Balance { address } => {
+                        let balance = self
+                            .token_querier
+                            .balances
+                            .get(contract_addr)
+                            .and_then(|balances| balances.get(&address))
+                            .cloned()
+                            .unwrap_or_default();
+
+                        SystemResult::Ok(ContractResult::from(to_binary(&BalanceResponse {
+                            balance,
+                        })))
+                    }
+                    MockQueryMsg::Price { asset_token, .. } => {
+                        match self.oracle_price_querier.oracle_price.get(&asset_token) {
+                            Some(rate) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                                    rate: *rate,
+                                    last_updated: 1000u64,
+                                })))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No oracle price exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                    MockQueryMsg::Pair { asset_infos } => {
+                        let key = asset_infos[0].to_string() + &asset_infos[1].to_string();
+                        match self.tswap_pair_querier.pairs.get(&key) {
+                            Some((pair, liquidity_token)) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
+                                    asset_infos,
+                                    contract_addr: pair.to_string(),
+                                    liquidity_token: liquidity_token.to_string(),
+                                    asset_decimals: [6u8, 6u8],
+                                })))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No pair exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                    MockQueryMsg::Pool {} => {
+                        match self.tswap_pair_querier.pools.get(contract_addr) {
+                            Some(assets) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(&PoolResponse {
+                                    assets: assets.clone(),
+                                    total_share: Uint128::zero(),
+                                })))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No pool exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            token_querier: TokenQuerier::default(),
+            oracle_price_querier: OraclePriceQuerier::default(),
+            tswap_pair_querier: TswapPairQuerier::default(),
+        }
+    }
+
+    // configure the cw20 token balances mock querier
+    pub fn with_token_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
+        self.token_querier = TokenQuerier::new(balances);
+    }
+
+    // configure the oracle price mock querier
+    pub fn with_oracle_price(&mut self, oracle_price: &[(&String, &Decimal)]) {
+        self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
+    }
+
+    // configure the tswap factory pair mock querier, the asset infos are
+    // mapped to the pair contract and its liquidity token
+    pub fn with_tswap_pair(&mut self, asset_infos: &[AssetInfo; 2], pair: &str, lp_token: &str) {
+        self.tswap_pair_querier.pairs.insert(
+            asset_infos[0].to_string() + &asset_infos[1].to_string(),
+            (pair.to_string(), lp_token.to_string()),
+        );
+    }
+
+    // configure the pool assets of a tswap pair
+    pub fn with_pool(&mut self, pair: &str, assets: [Asset; 2]) {
+        self.tswap_pair_querier
+            .pools
+            .insert(pair.to_string(), assets);
+    }
+}
@@ -0,0 +1,6 @@
+mod contract_test;
+mod deprecate_test;
+mod mock_querier;
+mod reward_test;
+mod short_test;
+mod staking_test;
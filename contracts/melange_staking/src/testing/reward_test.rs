@@ -0,0 +1,289 @@
+use crate::contract::{execute, instantiate, query};
+use crate::testing::contract_test::instantiate_msg;
+use crate::testing::mock_querier::mock_dependencies;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, CosmosMsg, Decimal, StdError, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use melange_protocol::staking::{
+    Cw20HookMsg, ExecuteMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse, RewardInfoResponseItem,
+};
+
+fn bond_msg(staker: &str, amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: staker.to_string(),
+        amount: Uint128::from(amount),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            asset_token: "asset".to_string(),
+        })
+        .unwrap(),
+    })
+}
+
+fn deposit_reward_msg(amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "factory".to_string(),
+        amount: Uint128::from(amount),
+        msg: to_binary(&Cw20HookMsg::DepositReward {
+            rewards: vec![("asset".to_string(), Uint128::from(amount))],
+        })
+        .unwrap(),
+    })
+}
+
+#[test]
+fn test_deposit_reward() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset".to_string(),
+        staking_token: "staking".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // bond 100 tokens
+    let info = mock_info("staking", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, bond_msg("addr", 100)).unwrap();
+
+    // unauthorized, only the reward token can deposit rewards
+    let info = mock_info("staking", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, deposit_reward_msg(100)).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    // the rewards must match the sent amount
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "factory".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositReward {
+            rewards: vec![("asset".to_string(), Uint128::from(50u128))],
+        })
+        .unwrap(),
+    });
+    let info = mock_info("reward", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, StdError::generic_err("rewards amount miss matched"));
+
+    let res = execute(deps.as_mut(), mock_env(), info, deposit_reward_msg(100)).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_reward"),
+            attr("rewards_amount", "100"),
+        ]
+    );
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(pool_info.reward_index, Decimal::one());
+    assert_eq!(pool_info.pending_reward, Uint128::zero());
+    assert_eq!(pool_info.short_pending_reward, Uint128::zero());
+}
+
+#[test]
+fn test_deposit_reward_when_no_bonding() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset".to_string(),
+        staking_token: "staking".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the rewards are kept pending until someone bonds
+    let info = mock_info("reward", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        deposit_reward_msg(100),
+    )
+    .unwrap();
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(pool_info.reward_index, Decimal::zero());
+    assert_eq!(pool_info.pending_reward, Uint128::from(100u128));
+
+    // the pending rewards are distributed with the next deposit
+    let bond_info = mock_info("staking", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), bond_info, bond_msg("addr", 100)).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, deposit_reward_msg(100)).unwrap();
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(pool_info.reward_index, Decimal::from_ratio(2u128, 1u128));
+    assert_eq!(pool_info.pending_reward, Uint128::zero());
+}
+
+#[test]
+fn test_before_share_changes() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset".to_string(),
+        staking_token: "staking".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let bond_info = mock_info("staking", &[]);
+    let reward_info = mock_info("reward", &[]);
+
+    // addr bonds 100 and receives the first 100 rewards
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        bond_info.clone(),
+        bond_msg("addr", 100),
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        reward_info.clone(),
+        deposit_reward_msg(100),
+    )
+    .unwrap();
+
+    // addr2 bonds 300 and the next 100 rewards are shared
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        bond_info.clone(),
+        bond_msg("addr2", 300),
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        reward_info,
+        deposit_reward_msg(100),
+    )
+    .unwrap();
+
+    // the pending rewards are kept after unbonding
+    let msg = ExecuteMsg::Unbond {
+        asset_token: "asset".to_string(),
+        amount: Uint128::from(100u128),
+    };
+    let info = mock_info("addr", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardInfo {
+            asset_token: None,
+            staker_addr: "addr".to_string(),
+        },
+    )
+    .unwrap();
+    let res: RewardInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(
+        res.reward_infos,
+        vec![RewardInfoResponseItem {
+            asset_token: "asset".to_string(),
+            bond_amount: Uint128::zero(),
+            pending_reward: Uint128::from(125u128),
+            is_short: false,
+            should_migrate: None,
+        }]
+    );
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardInfo {
+            asset_token: Some("asset".to_string()),
+            staker_addr: "addr2".to_string(),
+        },
+    )
+    .unwrap();
+    let res: RewardInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(
+        res.reward_infos,
+        vec![RewardInfoResponseItem {
+            asset_token: "asset".to_string(),
+            bond_amount: Uint128::from(300u128),
+            pending_reward: Uint128::from(75u128),
+            is_short: false,
+            should_migrate: None,
+        }]
+    );
+}
+
+#[test]
+fn test_withdraw() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset".to_string(),
+        staking_token: "staking".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("staking", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, bond_msg("addr", 100)).unwrap();
+
+    let info = mock_info("reward", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, deposit_reward_msg(100)).unwrap();
+
+    let msg = ExecuteMsg::Withdraw {
+        asset_token: Some("asset".to_string()),
+    };
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "reward".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // nothing left to withdraw
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, StdError::generic_err("No rewards to withdraw"));
+}
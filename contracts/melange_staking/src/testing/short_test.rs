@@ -0,0 +1,272 @@
+use crate::contract::{execute, instantiate, query};
+use crate::testing::contract_test::instantiate_msg;
+use crate::testing::mock_querier::mock_dependencies;
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{attr, from_binary, to_binary, Decimal, StdError, Uint128};
+use cw20::Cw20ReceiveMsg;
+use melange_protocol::staking::{
+    Cw20HookMsg, ExecuteMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse, RewardInfoResponseItem,
+};
+use terraswap::asset::{Asset, AssetInfo};
+
+#[test]
+fn test_increase_short_token() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset".to_string(),
+        staking_token: "staking".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::IncreaseShortToken {
+        staker_addr: "addr".to_string(),
+        asset_token: "asset".to_string(),
+        amount: Uint128::from(100u128),
+    };
+
+    // only the mint contract can increase the short position
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("unauthorized"));
+
+    let info = mock_info("mint", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "increase_short_token"),
+            attr("staker_addr", "addr"),
+            attr("asset_token", "asset"),
+            attr("amount", "100"),
+        ]
+    );
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(pool_info.total_short_amount, Uint128::from(100u128));
+    assert_eq!(pool_info.total_bond_amount, Uint128::zero());
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardInfo {
+            asset_token: None,
+            staker_addr: "addr".to_string(),
+        },
+    )
+    .unwrap();
+    let res: RewardInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(
+        res.reward_infos,
+        vec![RewardInfoResponseItem {
+            asset_token: "asset".to_string(),
+            bond_amount: Uint128::from(100u128),
+            pending_reward: Uint128::zero(),
+            is_short: true,
+            should_migrate: None,
+        }]
+    );
+
+    // decrease the whole short position
+    let msg = ExecuteMsg::DecreaseShortToken {
+        staker_addr: "addr".to_string(),
+        asset_token: "asset".to_string(),
+        amount: Uint128::from(100u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "decrease_short_token"),
+            attr("staker_addr", "addr"),
+            attr("asset_token", "asset"),
+            attr("amount", "100"),
+        ]
+    );
+    assert_eq!(res.messages, vec![]);
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(pool_info.total_short_amount, Uint128::zero());
+}
+
+#[test]
+fn test_adjust_premium() {
+    let mut deps = mock_dependencies(&[]);
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset".to_string(),
+        },
+    ];
+    deps.querier
+        .with_tswap_pair(&asset_infos, "pair", "staking");
+    deps.querier
+        .with_oracle_price(&[(&"asset".to_string(), &Decimal::one())]);
+
+    // the terraswap price is 5% above the oracle price
+    deps.querier.with_pool(
+        "pair",
+        [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: Uint128::from(105u128),
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: Uint128::from(100u128),
+            },
+        ],
+    );
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_token: "asset".to_string(),
+        staking_token: "staking".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::AdjustPremium {
+        asset_tokens: vec!["asset".to_string()],
+    };
+    let info = mock_info("addr", &[]);
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(pool_info.premium_rate, Decimal::percent(5));
+    assert_eq!(
+        pool_info.short_reward_weight,
+        Decimal::from_ratio(285714285u128, 1000000000u128)
+    );
+    assert_eq!(pool_info.premium_updated_time, env.block.time.seconds());
+
+    // can not adjust the premium before the update interval
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("cannot adjust premium before premium_min_update_interval passed")
+    );
+
+    // the short reward weight is capped at 40%
+    deps.querier.with_pool(
+        "pair",
+        [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: Uint128::from(110u128),
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: Uint128::from(100u128),
+            },
+        ],
+    );
+    env.block.time = env.block.time.plus_seconds(3600);
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PoolInfo {
+            asset_token: "asset".to_string(),
+        },
+    )
+    .unwrap();
+    let pool_info: PoolInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(pool_info.premium_rate, Decimal::percent(10));
+    assert_eq!(pool_info.short_reward_weight, Decimal::percent(40));
+
+    // the rewards are split between the lp stakers and the short positions
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            asset_token: "asset".to_string(),
+        })
+        .unwrap(),
+    });
+    let info = mock_info("staking", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::IncreaseShortToken {
+        staker_addr: "addr".to_string(),
+        asset_token: "asset".to_string(),
+        amount: Uint128::from(200u128),
+    };
+    let info = mock_info("mint", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "factory".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::DepositReward {
+            rewards: vec![("asset".to_string(), Uint128::from(1000u128))],
+        })
+        .unwrap(),
+    });
+    let info = mock_info("reward", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let data = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardInfo {
+            asset_token: Some("asset".to_string()),
+            staker_addr: "addr".to_string(),
+        },
+    )
+    .unwrap();
+    let res: RewardInfoResponse = from_binary(&data).unwrap();
+    assert_eq!(
+        res.reward_infos,
+        vec![
+            RewardInfoResponseItem {
+                asset_token: "asset".to_string(),
+                bond_amount: Uint128::from(100u128),
+                pending_reward: Uint128::from(600u128),
+                is_short: false,
+                should_migrate: None,
+            },
+            RewardInfoResponseItem {
+                asset_token: "asset".to_string(),
+                bond_amount: Uint128::from(200u128),
+                pending_reward: Uint128::from(400u128),
+                is_short: true,
+                should_migrate: None,
+            },
+        ]
+    );
+}
@@ -94,6 +94,8 @@ pub enum SourceType {
     FixedPrice {
         price: Decimal,
     },
+    /// Spot price of a pair from its current reserves. It has no TWAP and
+    /// never goes stale, so it is only suited to trusted, deep pools
     AmmPair {
         pair_addr: String,
         intermediate_denom: Option<String>,
@@ -107,6 +109,12 @@ pub enum SourceType {
     Lunax {
         staking_contract_addr: String,
     },
+    /// Price of a native denom read from the Sei oracle module. Uses the
+    /// TWAP over `twap_lookback_seconds` when set, the spot rate otherwise
+    SeiOracle {
+        denom: String,
+        twap_lookback_seconds: Option<u64>,
+    },
 }
 
 impl fmt::Display for SourceType {
@@ -118,6 +126,7 @@ impl fmt::Display for SourceType {
             SourceType::AnchorMarket { .. } => write!(f, "anchor_market"),
             SourceType::Native { .. } => write!(f, "native"),
             SourceType::Lunax { .. } => write!(f, "lunax"),
+            SourceType::SeiOracle { .. } => write!(f, "sei_oracle"),
         }
     }
 }
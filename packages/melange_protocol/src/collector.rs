@@ -1,6 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use terraswap::asset::AssetInfo;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub owner: String,
@@ -8,20 +10,12 @@ pub struct InstantiateMsg {
     pub tswap_factory: String,
     pub melange_token: String,
     pub base_denom: String,
-    // aUST params
-    pub aust_token: String,
-    pub anchor_market: String,
-    // bLuna params
-    pub bluna_token: String,
-    // Lunax params
-    pub lunax_token: String,
     // when set, use this address instead of querying from tswap
-    pub mln_ust_pair: Option<String>,
+    pub mln_base_pair: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     UpdateConfig {
         owner: Option<String>,
@@ -29,32 +23,45 @@ pub enum ExecuteMsg {
         tswap_factory: Option<String>,
         melange_token: Option<String>,
         base_denom: Option<String>,
-        aust_token: Option<String>,
-        anchor_market: Option<String>,
-        bluna_token: Option<String>,
-        mln_ust_pair: Option<String>,
-        lunax_token: Option<String>,
+        mln_base_pair: Option<String>,
+    },
+    /// Sets the swaps used to convert the asset to the base denom.
+    /// Removes the route when `route` is not given, the asset then
+    /// falls back to its terraswap pair against the base denom
+    UpdateConversionRoute {
+        asset_info: AssetInfo,
+        route: Option<Vec<ConversionStep>>,
     },
+    /// Converts the collected asset to the base denom,
+    /// or the base denom to MLN when the melange token is given
     Convert {
         asset_token: String,
     },
+    /// Sends the collected MLN to the distribution contract
     Distribute {},
-    /// Internal operation to swap Luna for UST
-    LunaSwapHook {},
+    /// Internal operation to swap the whole balance of an
+    /// intermediate asset of a conversion route
+    SwapHook {
+        offer_asset_info: AssetInfo,
+        pair_contract: String,
+    },
 }
 
+/// A single swap of a conversion route
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum QueryMsg {
-    Config {},
+pub struct ConversionStep {
+    /// terraswap compatible pair executing the swap
+    pub pair_contract: String,
+    /// asset received from the swap, offered to the next step
+    pub ask_asset_info: AssetInfo,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum MoneyMarketCw20HookMsg {
-    /// Return stable coins to a user
-    /// according to exchange rate
-    RedeemStable {},
+pub enum QueryMsg {
+    Config {},
+    ConversionRoute { asset_token: String },
+    ConversionRoutes {},
 }
 
 // We define a custom struct for each query response
@@ -65,14 +72,19 @@ pub struct ConfigResponse {
     pub tswap_factory: String,
     pub melange_token: String,
     pub base_denom: String,
-    pub aust_token: String,
-    pub anchor_market: String,
-    pub bluna_token: String,
-    pub lunax_token: String,
-    pub mln_ust_pair: Option<String>,
+    pub mln_base_pair: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConversionRouteResponse {
+    pub asset_info: AssetInfo,
+    pub route: Vec<ConversionStep>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {
-    pub lunax_token: String,
+pub struct ConversionRoutesResponse {
+    pub routes: Vec<ConversionRouteResponse>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
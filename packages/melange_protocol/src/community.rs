@@ -5,9 +5,11 @@ use cosmwasm_std::Uint128;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-    pub owner: String,        // melange gov contract
-    pub melange_token: String, // melange token address
-    pub spend_limit: Uint128, // spend limit per each `spend` request
+    pub owner: String,              // melange gov contract
+    pub melange_token: String,      // melange token address
+    pub spend_limit: Uint128,       // spend limit per each `spend` request
+    pub epoch_spend_limit: Uint128, // spend limit of all the requests of an epoch
+    pub epoch_length: u64,          // epoch length in seconds
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -16,11 +18,25 @@ pub enum ExecuteMsg {
     UpdateConfig {
         owner: Option<String>,
         spend_limit: Option<Uint128>,
+        epoch_spend_limit: Option<Uint128>,
+        epoch_length: Option<u64>,
     },
     Spend {
         recipient: String,
         amount: Uint128,
     },
+    /// Reserves `amount` for the recipient, released linearly
+    /// between the `start` and `end` times (in seconds)
+    CreateGrant {
+        recipient: String,
+        amount: Uint128,
+        start: u64,
+        end: u64,
+    },
+    /// Sends the released amount of the grant to its recipient
+    ClaimGrant {
+        grant_id: u64,
+    },
 }
 
 /// We currently take no arguments for migrations
@@ -31,6 +47,15 @@ pub struct MigrateMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    SpendState {},
+    Grant {
+        grant_id: u64,
+    },
+    Grants {
+        recipient: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -39,4 +64,30 @@ pub struct ConfigResponse {
     pub owner: String,
     pub melange_token: String,
     pub spend_limit: Uint128,
+    pub epoch_spend_limit: Uint128,
+    pub epoch_length: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendStateResponse {
+    pub epoch_start: u64,
+    pub epoch_spent: Uint128,
+    /// amount reserved for the unclaimed grants
+    pub total_granted: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GrantResponse {
+    pub grant_id: u64,
+    pub recipient: String,
+    pub amount: Uint128,
+    pub claimed: Uint128,
+    pub claimable: Uint128,
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GrantsResponse {
+    pub grants: Vec<GrantResponse>,
 }
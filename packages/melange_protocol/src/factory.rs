@@ -24,6 +24,7 @@ pub enum ExecuteMsg {
         oracle_contract: String,
         mint_contract: String,
         commission_collector: String,
+        collateral_oracle: String,
     },
     UpdateConfig {
         owner: Option<String>,
@@ -34,6 +35,18 @@ pub enum ExecuteMsg {
         asset_token: String,
         weight: u32,
     },
+    /// Instantiates a new mAsset token and registers it
+    /// to the mint, staking and oracle contracts
+    Whitelist {
+        /// asset name used to create token contract
+        name: String,
+        /// asset symbol used to create token contract
+        symbol: String,
+        /// oracle proxy that will provide prices for this asset
+        oracle_proxy: String,
+        /// used to create all necessary contract or register asset
+        params: Params,
+    },
     PassCommand {
         contract_addr: String,
         msg: Binary,
@@ -75,6 +88,7 @@ pub struct ConfigResponse {
     pub staking_contract: String,
     pub commission_collector: String,
     pub oracle_contract: String,
+    pub collateral_oracle: String,
     pub tswap_factory: String,
     pub token_code_id: u64,
     pub base_denom: String,
@@ -97,8 +111,12 @@ pub struct MigrateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct Params {
+    /// Auction discount rate applied to asset mint
+    pub auction_discount: Decimal,
     /// Minimum collateral ratio applied to asset mint
     pub min_collateral_ratio: Decimal,
     /// Distribution weight (default is 30, which is 1/10 of MLGN distribution weight)
     pub weight: Option<u32>,
+    /// Multiplier registered to the collateral oracle (default is 1)
+    pub collateral_multiplier: Option<Decimal>,
 }
@@ -154,7 +154,7 @@ pub enum QueryMsg {
     },
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
     pub melange_token: String,
@@ -168,7 +168,7 @@ pub struct ConfigResponse {
     pub poll_gas_limit: u64,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StateResponse {
     pub poll_count: u64,
     pub total_share: Uint128,
@@ -201,7 +201,7 @@ pub struct PollsResponse {
     pub polls: Vec<PollResponse>,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PollCountResponse {
     pub poll_count: u64,
 }
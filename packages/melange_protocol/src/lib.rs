@@ -2,7 +2,9 @@ pub mod collateral_oracle;
 pub mod collector;
 pub mod common;
 pub mod community;
+pub mod cw721;
 pub mod factory;
 pub mod gov;
 pub mod mint;
+pub mod oracle;
 pub mod staking;
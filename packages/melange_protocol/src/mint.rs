@@ -2,7 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{Decimal, Uint128};
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ReceiveMsg, Expiration};
 use terraswap::asset::{Asset, AssetInfo};
 
 use crate::common::OrderBy;
@@ -18,6 +18,7 @@ pub struct InstantiateMsg {
     pub base_denom: String,
     pub token_code_id: u64,
     pub protocol_fee_rate: Decimal,
+    pub lock_period: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -29,7 +30,9 @@ pub enum ExecuteMsg {
     /// Owner Operations
     //////////////////////
 
-    /// Update config; only owner is allowed to execute it
+    /// Update config; only owner is allowed to execute it. Once `position_nft`
+    /// is set, new positions are minted as NFTs of that cw721 contract.
+    /// The `guardian` can pause operations but not resume them
     UpdateConfig {
         owner: Option<String>,
         oracle: Option<String>,
@@ -39,21 +42,65 @@ pub enum ExecuteMsg {
         token_code_id: Option<u64>,
         protocol_fee_rate: Option<Decimal>,
         staking: Option<String>,
+        lock_period: Option<u64>,
+        position_nft: Option<String>,
+        guardian: Option<String>,
     },
     /// Update asset related parameters
     UpdateAsset {
         asset_token: String,
+        auction_discount: Option<Decimal>,
         min_collateral_ratio: Option<Decimal>,
+        /// annualized fee accruing on the minted debt
+        stability_fee_rate: Option<Decimal>,
+        ipo_params: Option<IpoParams>,
+    },
+    /// Cap the amount of the asset minted over all positions, `None` removes the cap
+    UpdateMintCap {
+        asset_token: String,
+        mint_cap: Option<Uint128>,
+    },
+    /// Cap the amount of the collateral deposited over all positions,
+    /// `None` removes the cap
+    UpdateDepositCap {
+        collateral: AssetInfo,
+        deposit_cap: Option<Uint128>,
     },
     /// Generate asset token initialize msg and register required infos except token address
     RegisterAsset {
         asset_token: String,
+        auction_discount: Decimal,
         min_collateral_ratio: Decimal,
+        ipo_params: Option<IpoParams>,
+    },
+    /// Create a tokenfactory denom owned by this contract and register it as
+    /// a native mAsset; the registered denom is `factory/{contract}/{subdenom}`
+    RegisterNativeAsset {
+        subdenom: String,
+        auction_discount: Decimal,
+        min_collateral_ratio: Decimal,
+        ipo_params: Option<IpoParams>,
     },
     RegisterMigration {
         asset_token: String,
         end_price: Decimal,
     },
+    /// Convert a pre-IPO asset to a regular one once it is listed;
+    /// only the `trigger_addr` of its IPO params is allowed to execute it
+    TriggerIpo {
+        asset_token: String,
+    },
+    /// Pause operations of the asset, or of every asset when `asset_token` is not
+    /// provided; the owner and the guardian are allowed to execute it
+    Pause {
+        asset_token: Option<String>,
+        operations: Vec<PauseOperation>,
+    },
+    /// Resume paused operations; only owner is allowed to execute it
+    Unpause {
+        asset_token: Option<String>,
+        operations: Vec<PauseOperation>,
+    },
 
     //////////////////////
     /// User Operations
@@ -63,13 +110,15 @@ pub enum ExecuteMsg {
         collateral: Asset,
         asset_info: AssetInfo,
         collateral_ratio: Decimal,
+        short_params: Option<ShortParams>,
     },
-    /// Deposit more collateral
+    /// Deposit more collateral; a listed asset the position does not
+    /// hold yet is added to its collaterals
     Deposit {
         position_idx: Uint128,
         collateral: Asset,
     },
-    /// Withdraw collateral
+    /// Withdraw the given collateral, or all collaterals when not provided
     Withdraw {
         position_idx: Uint128,
         collateral: Option<Asset>,
@@ -78,9 +127,95 @@ pub enum ExecuteMsg {
     Mint {
         position_idx: Uint128,
         asset: Asset,
+        short_params: Option<ShortParams>,
+    },
+    /// Burn the native asset attached to the message
+    Burn {
+        position_idx: Uint128,
+        asset: Asset,
+    },
+    /// Buy discounted collateral from the contract with the attached native asset
+    Auction {
+        position_idx: Uint128,
+        asset: Asset,
+    },
+    /// Burn the whole debt with the attached native asset, refund the
+    /// collateral and remove the position
+    ClosePosition {
+        position_idx: Uint128,
+        asset: Asset,
+    },
+    /// Release the locked short sale proceeds of the given positions
+    UnlockPositionFunds {
+        positions_idx: Vec<Uint128>,
+    },
+    /// Transfer the position to the recipient; the owner or
+    /// an approved spender can execute it
+    TransferPosition {
+        position_idx: Uint128,
+        recipient: String,
+    },
+    /// Allow the spender to operate all the positions of the sender
+    /// until `expires`; the approval never expires by default
+    ApprovePosition {
+        spender: String,
+        expires: Option<Expiration>,
+    },
+    /// Revoke the approval given to the spender
+    RevokePosition {
+        spender: String,
+    },
+    /// Add the next `limit` positions to the minted and deposited totals
+    /// recounted after a migration; anyone is allowed to execute it
+    CountUtilizations {
+        limit: Option<u32>,
+    },
+
+    //////////////////////
+    /// Internal Operations
+    //////////////////////
+    /// Lock the base denom received from a short sale;
+    /// `prev_balance` is the contract balance before the sale
+    LockPositionFundsHook {
+        position_idx: Uint128,
+        receiver: String,
+        prev_balance: Uint128,
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ShortParams {
+    pub belief_price: Option<Decimal>,
+    pub max_spread: Option<Decimal>,
+}
+
+/// Parameters of a pre-IPO asset, minted at a fixed price until its listing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IpoParams {
+    /// block time the minting period ends at
+    pub mint_end: u64,
+    /// fixed price of the asset until the IPO
+    pub pre_ipo_price: Decimal,
+    /// min collateral ratio applied once the IPO is triggered
+    pub min_collateral_ratio_after_ipo: Decimal,
+    /// oracle feeder allowed to trigger the IPO
+    pub trigger_addr: String,
+}
+
+/// Operations the owner and the guardian can pause
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseOperation {
+    /// `OpenPosition`
+    OpenPosition,
+    /// `Mint`, and `OpenPosition` which mints
+    Mint,
+    /// `Withdraw`
+    Withdraw,
+    /// every position operation, including burns and auctions
+    All,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
@@ -88,17 +223,23 @@ pub enum Cw20HookMsg {
     OpenPosition {
         asset_info: AssetInfo,
         collateral_ratio: Decimal,
+        short_params: Option<ShortParams>,
     },
     /// Deposit more collateral
     Deposit { position_idx: Uint128 },
     /// Convert specified asset amount and send back to user
     Burn { position_idx: Uint128 },
+    /// Buy discounted collateral from the contract with asset tokens
+    Auction { position_idx: Uint128 },
+    /// Burn the whole debt, refund the collateral and remove the position
+    ClosePosition { position_idx: Uint128 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// `asset_token` is the token address or the native denom of the asset
     AssetConfig {
         asset_token: String,
     },
@@ -113,6 +254,54 @@ pub enum QueryMsg {
         order_by: Option<OrderBy>,
     },
     NextPositionIdx {},
+    PositionLockInfo {
+        position_idx: Uint128,
+    },
+    PositionApproval {
+        owner: String,
+        spender: String,
+    },
+    /// Collateral ratios and limits of the position at the current prices
+    PositionHealth {
+        position_idx: Uint128,
+    },
+    PositionsHealth {
+        positions_idx: Vec<Uint128>,
+    },
+    /// Minted and deposited totals of the asset against their caps
+    Utilization {
+        asset_info: AssetInfo,
+    },
+    /// Operations paused for the asset, or for every asset when
+    /// `asset_token` is not provided
+    PauseInfo {
+        asset_token: Option<String>,
+    },
+    /// Run the operation as `sender` without writing state; the query
+    /// fails with the error the operation would return
+    SimulateOpenPosition {
+        sender: String,
+        collateral: Asset,
+        asset_info: AssetInfo,
+        collateral_ratio: Decimal,
+        short_params: Option<ShortParams>,
+    },
+    SimulateMint {
+        sender: String,
+        position_idx: Uint128,
+        asset: Asset,
+        short_params: Option<ShortParams>,
+    },
+    SimulateBurn {
+        sender: String,
+        position_idx: Uint128,
+        asset: Asset,
+    },
+    SimulateWithdraw {
+        sender: String,
+        position_idx: Uint128,
+        collateral: Option<Asset>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -127,14 +316,22 @@ pub struct ConfigResponse {
     pub base_denom: String,
     pub token_code_id: u64,
     pub protocol_fee_rate: Decimal,
+    pub lock_period: u64,
+    pub position_nft: Option<String>,
+    pub guardian: Option<String>,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AssetConfigResponse {
-    pub token: String,
+    pub token: String, // token address or native denom
+    pub auction_discount: Decimal,
     pub min_collateral_ratio: Decimal,
     pub end_price: Option<Decimal>,
+    pub stability_fee_rate: Decimal,
+    /// cumulative stability fee index at the current block time
+    pub stability_fee_index: Decimal,
+    pub ipo_params: Option<IpoParams>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -143,6 +340,11 @@ pub struct PositionResponse {
     pub owner: String,
     pub collateral: Asset,
     pub asset: Asset,
+    pub is_short: bool,
+    /// collaterals deposited besides the main `collateral`
+    pub other_collaterals: Vec<Asset>,
+    /// stability fee accrued since the last settlement, in asset unit
+    pub accrued_stability_fee: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
@@ -155,6 +357,78 @@ pub struct NextPositionIdxResponse {
     pub next_position_idx: Uint128,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PositionLockInfoResponse {
+    pub idx: Uint128,
+    pub receiver: String,
+    pub locked_amount: Uint128,
+    pub unlock_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PositionHealthResponse {
+    pub idx: Uint128,
+    /// collateral value over asset value, `None` without debt
+    pub collateral_ratio: Option<Decimal>,
+    /// asset minimum collateral ratio times the collateral multipliers,
+    /// averaged over the collateral value
+    pub min_collateral_ratio: Decimal,
+    /// asset price in base denom above which the position can be auctioned,
+    /// `None` without debt or for deprecated assets
+    pub liquidation_price: Option<Decimal>,
+    pub mintable_amount: Uint128,
+    /// amount of the main collateral that can be withdrawn
+    pub withdrawable_collateral: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PositionsHealthResponse {
+    pub positions: Vec<PositionHealthResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulationResponse {
+    pub mint_amount: Uint128,
+    /// collaterals taken as protocol fee, one entry per collateral
+    pub protocol_fee: Vec<Asset>,
+    /// collaterals sent back by a burn or a withdraw
+    pub refund_collateral: Vec<Asset>,
+    /// collateral ratio of the resulting position, `None` without debt
+    /// or once the position is closed
+    pub collateral_ratio: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PositionApprovalResponse {
+    pub owner: String,
+    pub spender: String,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseInfoResponse {
+    pub asset_token: Option<String>,
+    pub open_position: bool,
+    pub mint: bool,
+    pub withdraw: bool,
+    pub all: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UtilizationResponse {
+    pub asset_info: AssetInfo,
+    /// amount minted over all positions
+    pub total_minted: Uint128,
+    pub mint_cap: Option<Uint128>,
+    /// total minted over the mint cap, `None` without a non-zero cap
+    pub mint_utilization: Option<Decimal>,
+    /// amount deposited as collateral over all positions
+    pub total_deposited: Uint128,
+    pub deposit_cap: Option<Uint128>,
+    /// total deposited over the deposit cap, `None` without a non-zero cap
+    pub deposit_utilization: Option<Decimal>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {
     pub melange_oracle_contract: String,
@@ -0,0 +1,35 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Decimal;
+
+/// Execute interface of the Melange price oracle used by the factory
+/// to register the price source of the whitelisted assets
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Registers the proxy contract providing the price of the asset
+    RegisterProxy {
+        asset_token: String,
+        proxy_addr: String,
+    },
+}
+
+/// Query interface of the Melange price oracle consumed by the
+/// mint and collateral oracle contracts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Latest price of the asset, quoted in base denom.
+    /// When `timeframe` is set, prices older than `timeframe` seconds are rejected
+    Price {
+        asset_token: String,
+        timeframe: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceResponse {
+    pub rate: Decimal,
+    pub last_updated: u64,
+}
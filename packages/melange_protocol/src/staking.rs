@@ -36,6 +36,20 @@ pub enum ExecuteMsg {
         new_staking_token: String,
     },
 
+    ////////////////////////
+    /// Mint operations ///
+    ////////////////////////
+    IncreaseShortToken {
+        staker_addr: String,
+        asset_token: String,
+        amount: Uint128,
+    },
+    DecreaseShortToken {
+        staker_addr: String,
+        asset_token: String,
+        amount: Uint128,
+    },
+
     ////////////////////////
     /// User operations ///
     ////////////////////////
@@ -113,9 +127,13 @@ pub struct PoolInfoResponse {
     pub asset_token: String,
     pub staking_token: String,
     pub total_bond_amount: Uint128,
+    pub total_short_amount: Uint128,
     pub reward_index: Decimal,
+    pub short_reward_index: Decimal,
     pub pending_reward: Uint128,
+    pub short_pending_reward: Uint128,
     pub premium_rate: Decimal,
+    pub short_reward_weight: Decimal,
     pub premium_updated_time: u64,
     pub migration_index_snapshot: Option<Decimal>,
     pub migration_deprecated_staking_token: Option<String>,
@@ -133,5 +151,8 @@ pub struct RewardInfoResponseItem {
     pub asset_token: String,
     pub bond_amount: Uint128,
     pub pending_reward: Uint128,
+    pub is_short: bool,
+    // returns true if the position should be closed to keep receiving rewards
+    // with the new lp token
     pub should_migrate: Option<bool>,
 }